@@ -8,7 +8,6 @@ use syn::{
     DataStruct,
     DeriveInput,
     Field,
-    Fields,
     Generics,
     Ident,
     Path,
@@ -27,10 +26,11 @@ pub fn derive(input: TokenStream) -> TokenStream {
     let (components_bodies, write_bodies): (Vec<_>, Vec<_>) = fields
         .into_iter()
         .enumerate()
-        .map(|(i, Field { ident, ty, .. })| {
+        .filter(|(_, field)| !field.ignore)
+        .map(|(i, BundleField { field: Field { ident, ty, .. }, .. })| {
             (
                 quote! {
-                    #ty::components(components)
+                    <#ty as ::#crate_path::component::Bundle>::components(components)
                 },
                 {
                     let field_ident = ident.map(FieldIdent::Named).unwrap_or(
@@ -38,7 +38,10 @@ pub fn derive(input: TokenStream) -> TokenStream {
                     );
 
                     quote! {
-                        self.#field_ident.write(writer);
+                        ::#crate_path::component::Bundle::write(
+                            self.#field_ident,
+                            writer,
+                        );
                     }
                 },
             )
@@ -66,10 +69,16 @@ pub fn derive(input: TokenStream) -> TokenStream {
 struct DeriveBundle {
     ident: Ident,
     generics: Generics,
-    fields: Fields,
+    fields: Vec<BundleField>,
     crate_path: Path,
 }
 
+struct BundleField {
+    field: Field,
+    /// Whether the field has `#[bundle(ignore)]` and isn't inserted.
+    ignore: bool,
+}
+
 impl Parse for DeriveBundle {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let DeriveInput { ident, generics, data, .. } = input.parse()?;
@@ -77,6 +86,30 @@ impl Parse for DeriveBundle {
             return Err(input.error("`Bundle` can only be derived for structs"));
         };
         let crate_path = crate_path()?;
+        let fields = fields
+            .into_iter()
+            .map(|field| {
+                let mut ignore = false;
+
+                for attr in &field.attrs {
+                    if attr.path().is_ident("bundle") {
+                        attr.parse_nested_meta(|meta| {
+                            if !meta.path.is_ident("ignore") {
+                                return Err(meta.error("expected `ignore`"));
+                            }
+
+                            if std::mem::replace(&mut ignore, true) {
+                                return Err(meta.error("duplicate attribute"));
+                            }
+
+                            Ok(())
+                        })?;
+                    }
+                }
+
+                Ok(BundleField { field, ignore })
+            })
+            .collect::<syn::Result<_>>()?;
 
         Ok(Self { ident, generics, fields, crate_path })
     }
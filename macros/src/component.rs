@@ -1,16 +1,22 @@
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
+    parenthesized,
     parse_macro_input,
     DeriveInput,
     Expr,
     Generics,
     Ident,
+    LitInt,
+    LitStr,
     Meta,
     Path,
     Token,
+    Type,
 };
 
 use crate::crate_path;
@@ -20,8 +26,12 @@ pub fn derive(input: TokenStream) -> TokenStream {
         ident,
         generics,
         crate_path,
-        after_insert,
-        before_remove,
+        hooks,
+        requires,
+        map_entities,
+        reflect,
+        align,
+        stable_id,
     } = parse_macro_input!(input);
     let (impl_generics, type_generics, where_clause) =
         generics.split_for_impl();
@@ -34,17 +44,79 @@ pub fn derive(input: TokenStream) -> TokenStream {
             ID.get_or_init()
         }
     };
-    let after_insert = after_insert.map(|expr| {
+    let hook_flags = hooks.iter().any(Option::is_some).then(|| {
+        let flags = HOOKS.iter().zip(&hooks).map(|(name, expr)| {
+            let name = Ident::new(name, Span::call_site());
+            let overridden = expr.is_some();
+
+            quote! { #name: #overridden }
+        });
+
+        quote! {
+            const HOOKS: ::#crate_path::component::ComponentHooks =
+                ::#crate_path::component::ComponentHooks { #(#flags),* };
+        }
+    });
+    let hooks = HOOKS.iter().zip(hooks).filter_map(|(name, expr)| {
+        let name = Ident::new(name, Span::call_site());
+
+        expr.map(|expr| {
+            quote! {
+                fn #name(
+                    entity: ::#crate_path::entity::EntityMut<'_>,
+                    ctx: ::#crate_path::component::HookContext,
+                ) {
+                    (#expr)(entity, ctx);
+                }
+            }
+        })
+    });
+
+    let required = requires.map(|requires| {
+        let requires = requires.iter();
+
         quote! {
-            fn after_insert(entity: ::#crate_path::entity::EntityMut<'_>) {
-                (#expr)(entity);
+            fn required(requirements: &mut ::#crate_path::component::Requirements) {
+                #(requirements.require::<#requires>();)*
             }
         }
     });
-    let before_remove = before_remove.map(|expr| {
+
+    let map_entities = map_entities.then(|| {
+        quote! {
+            fn map_entities(
+                &mut self,
+                mapper: &mut dyn ::#crate_path::entity::EntityMapper,
+            ) {
+                ::#crate_path::entity::MapEntities::map_entities(self, mapper);
+            }
+        }
+    });
+
+    let reflect = reflect.then(|| {
+        quote! {
+            fn as_reflect(&self) -> ::std::option::Option<&dyn ::#crate_path::reflect::Reflect> {
+                ::std::option::Option::Some(self)
+            }
+
+            fn as_reflect_mut(
+                &mut self,
+            ) -> ::std::option::Option<&mut dyn ::#crate_path::reflect::Reflect> {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+
+    let align = align.map(|align| {
+        quote! {
+            const COLUMN_ALIGN: usize = #align;
+        }
+    });
+
+    let stable_id = stable_id.map(|stable_id| {
         quote! {
-            fn before_remove(entity: ::#crate_path::entity::EntityMut<'_>) {
-                (#expr)(entity);
+            fn stable_id() -> &'static str {
+                #stable_id
             }
         }
     });
@@ -54,22 +126,41 @@ pub fn derive(input: TokenStream) -> TokenStream {
         unsafe impl #impl_generics ::#crate_path::component::Component for #ident #type_generics
         #where_clause
         {
+            #align
+
+            #hook_flags
+
             #id
 
-            #after_insert
+            #stable_id
 
-            #before_remove
+            #(#hooks)*
+
+            #required
+
+            #map_entities
+
+            #reflect
         }
     }
     .into()
 }
 
+/// The names of the component hook functions.
+const HOOKS: [&str; 4] =
+    ["after_insert", "on_replace", "before_remove", "after_remove"];
+
 struct DeriveComponent {
     ident: Ident,
     generics: Generics,
     crate_path: Path,
-    after_insert: Option<Expr>,
-    before_remove: Option<Expr>,
+    /// The hooks in [`HOOKS`], in order.
+    hooks: [Option<Expr>; 4],
+    requires: Option<Punctuated<Type, Token![,]>>,
+    map_entities: bool,
+    reflect: bool,
+    align: Option<LitInt>,
+    stable_id: Option<LitStr>,
 }
 
 impl Parse for DeriveComponent {
@@ -77,8 +168,12 @@ impl Parse for DeriveComponent {
         let DeriveInput { ident, generics, attrs, .. } = input.parse()?;
         let crate_path = crate_path()?;
 
-        let mut after_insert = None;
-        let mut before_remove = None;
+        let mut hooks = [None, None, None, None];
+        let mut requires = None;
+        let mut map_entities = false;
+        let mut reflect = false;
+        let mut align = None;
+        let mut stable_id = None;
 
         for attr in attrs {
             if attr.path().is_ident("component") {
@@ -92,6 +187,13 @@ impl Parse for DeriveComponent {
                 };
 
                 list.parse_args_with(|input: ParseStream| {
+                    let add_flag = |flag: &mut bool, span| {
+                        if std::mem::replace(flag, true) {
+                            Err(syn::Error::new(span, "duplicate attribute"))
+                        } else {
+                            Ok(())
+                        }
+                    };
                     let add_hook = |hook: &mut Option<_>, span| {
                         input.parse::<Token![=]>()?;
 
@@ -112,14 +214,69 @@ impl Parse for DeriveComponent {
                         let ident: Ident = input.parse()?;
                         let span = ident.span();
 
-                        if ident == "after_insert" {
-                            add_hook(&mut after_insert, span)?;
-                        } else if ident == "before_remove" {
-                            add_hook(&mut before_remove, span)?;
+                        if let Some(index) =
+                            HOOKS.iter().position(|&hook| ident == hook)
+                        {
+                            add_hook(&mut hooks[index], span)?;
+                        } else if ident == "requires" {
+                            let content;
+
+                            parenthesized!(content in input);
+
+                            let types = Punctuated::parse_terminated(&content)?;
+
+                            if requires.replace(types).is_some() {
+                                return Err(syn::Error::new(
+                                    span,
+                                    "duplicate attribute",
+                                ));
+                            }
+                        } else if ident == "map_entities" {
+                            add_flag(&mut map_entities, span)?;
+                        } else if ident == "reflect" {
+                            if !cfg!(feature = "reflect") {
+                                return Err(syn::Error::new(
+                                    span,
+                                    "`reflect` requires the `reflect` feature \
+                                     of worldlines",
+                                ));
+                            }
+
+                            add_flag(&mut reflect, span)?;
+                        } else if ident == "align" {
+                            input.parse::<Token![=]>()?;
+
+                            let lit: LitInt = input.parse()?;
+
+                            if !lit.base10_parse::<usize>()?.is_power_of_two() {
+                                return Err(syn::Error::new(
+                                    lit.span(),
+                                    "alignment must be a power of two",
+                                ));
+                            }
+
+                            if align.replace(lit).is_some() {
+                                return Err(syn::Error::new(
+                                    span,
+                                    "duplicate attribute",
+                                ));
+                            }
+                        } else if ident == "stable_id" {
+                            input.parse::<Token![=]>()?;
+
+                            if stable_id.replace(input.parse()?).is_some() {
+                                return Err(syn::Error::new(
+                                    span,
+                                    "duplicate attribute",
+                                ));
+                            }
                         } else {
                             return Err(syn::Error::new(
                                 span,
-                                "expected `after_insert` or `before_remove`",
+                                "expected `after_insert`, `on_replace`, \
+                                 `before_remove`, `after_remove`, `requires`, \
+                                 `map_entities`, `reflect`, `align` or \
+                                 `stable_id`",
                             ));
                         }
 
@@ -135,6 +292,16 @@ impl Parse for DeriveComponent {
             }
         }
 
-        Ok(Self { ident, generics, crate_path, after_insert, before_remove })
+        Ok(Self {
+            ident,
+            generics,
+            crate_path,
+            hooks,
+            requires,
+            map_entities,
+            reflect,
+            align,
+            stable_id,
+        })
     }
 }
@@ -5,6 +5,8 @@ use syn::Ident;
 
 mod bundle;
 mod component;
+mod query_data;
+mod reflect;
 mod resource;
 
 #[proc_macro_derive(Component, attributes(component))]
@@ -22,6 +24,16 @@ pub fn derive_resource(input: TokenStream) -> TokenStream {
     resource::derive(input)
 }
 
+#[proc_macro_derive(QueryData)]
+pub fn derive_query_data(input: TokenStream) -> TokenStream {
+    query_data::derive(input)
+}
+
+#[proc_macro_derive(Reflect)]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+    reflect::derive(input)
+}
+
 fn crate_path() -> syn::Result<syn::Path> {
     let crate_path = option_env!("WORLDLINES_PATH").unwrap_or("worldlines");
 
@@ -0,0 +1,128 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input,
+    parse_quote,
+    Data,
+    DataStruct,
+    DeriveInput,
+    Fields,
+    GenericParam,
+    Generics,
+    Ident,
+    Lifetime,
+    Path,
+};
+
+use crate::{crate_path, FieldIdent};
+
+pub fn derive(input: TokenStream) -> TokenStream {
+    let DeriveQueryData { ident, generics, fields, crate_path } =
+        parse_macro_input!(input);
+    let (impl_generics, type_generics, _) = generics.split_for_impl();
+
+    // the output has the lifetime of the world borrow in place of the
+    // lifetime of the struct
+    let mut output_generics = generics.clone();
+
+    for param in &mut output_generics.params {
+        if let GenericParam::Lifetime(param) = param {
+            param.lifetime = Lifetime::new("'__w", Span::call_site());
+        }
+    }
+
+    let (_, output_type_generics, _) = output_generics.split_for_impl();
+
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let field_idents = fields.iter().enumerate().map(|(i, field)| {
+        field
+            .ident
+            .clone()
+            .map(FieldIdent::Named)
+            .unwrap_or(FieldIdent::Indexed(Literal::usize_unsuffixed(i)))
+    });
+
+    // bounds on the field types would shadow their `Output`, so fields that
+    // aren't query data error in the body instead
+    let where_clause = &generics.where_clause;
+
+    // only applies if all fields are read-only
+    let read_only_where_clause = {
+        let mut where_clause = generics.clone().make_where_clause().clone();
+
+        where_clause.predicates.extend(types.iter().map(
+            |ty| -> syn::WherePredicate {
+                parse_quote!(#ty: ::#crate_path::query::ReadOnlyQueryData)
+            },
+        ));
+
+        where_clause
+    };
+
+    quote! {
+        #[automatically_derived]
+        unsafe impl #impl_generics ::#crate_path::query::QueryData for #ident #type_generics
+        #where_clause
+        {
+            type Output<'__w> = #ident #output_type_generics;
+
+            #[allow(unused)]
+            fn world_access(access: &mut ::#crate_path::access::WorldAccess) {
+                #(<#types as ::#crate_path::query::QueryData>::world_access(access);)*
+            }
+
+            #[allow(unused)]
+            unsafe fn get<'__w>(
+                entity: ::#crate_path::entity::EntityPtr<'__w>,
+            ) -> Self::Output<'__w> {
+                #ident {
+                    #(
+                        #field_idents: unsafe {
+                            <#types as ::#crate_path::query::QueryData>::get(entity)
+                        },
+                    )*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        unsafe impl #impl_generics ::#crate_path::query::ReadOnlyQueryData
+            for #ident #type_generics
+        #read_only_where_clause
+        {
+        }
+    }
+    .into()
+}
+
+struct DeriveQueryData {
+    ident: Ident,
+    generics: Generics,
+    fields: Fields,
+    crate_path: Path,
+}
+
+impl Parse for DeriveQueryData {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let DeriveInput { ident, generics, data, .. } = input.parse()?;
+        let Data::Struct(DataStruct { fields, .. }) = data else {
+            return Err(
+                input.error("`QueryData` can only be derived for structs")
+            );
+        };
+        let crate_path = crate_path()?;
+
+        if generics.lifetimes().count() > 1 {
+            return Err(syn::Error::new(
+                generics.span(),
+                "`QueryData` can only be derived for structs with at most one \
+                 lifetime",
+            ));
+        }
+
+        Ok(Self { ident, generics, fields, crate_path })
+    }
+}
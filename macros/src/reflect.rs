@@ -0,0 +1,103 @@
+use proc_macro::TokenStream;
+use proc_macro2::Literal;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    parse_macro_input,
+    Data,
+    DataStruct,
+    DeriveInput,
+    Field,
+    Fields,
+    Generics,
+    Ident,
+    Path,
+};
+
+use crate::{crate_path, FieldIdent};
+
+pub fn derive(input: TokenStream) -> TokenStream {
+    let DeriveReflect { ident, generics, fields, crate_path } =
+        parse_macro_input!(input);
+
+    let (impl_generics, type_generics, where_clause) =
+        generics.split_for_impl();
+
+    // `("name", self.field)`
+    let (names, field_idents): (Vec<_>, Vec<_>) = fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, Field { ident, .. })| match ident {
+            Some(ident) => (ident.to_string(), FieldIdent::Named(ident)),
+            None => (
+                i.to_string(),
+                FieldIdent::Indexed(Literal::usize_unsuffixed(i)),
+            ),
+        })
+        .unzip();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::#crate_path::reflect::Reflect for #ident #type_generics
+        #where_clause
+        {
+            fn type_name(&self) -> &'static str {
+                ::std::any::type_name::<Self>()
+            }
+
+            fn field_names(&self) -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+
+            fn field(
+                &self,
+                name: &str,
+            ) -> ::std::option::Option<&dyn ::#crate_path::reflect::Reflect> {
+                match name {
+                    #(#names => ::std::option::Option::Some(&self.#field_idents),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn field_mut(
+                &mut self,
+                name: &str,
+            ) -> ::std::option::Option<&mut dyn ::#crate_path::reflect::Reflect> {
+                match name {
+                    #(#names => ::std::option::Option::Some(&mut self.#field_idents),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+        }
+    }
+    .into()
+}
+
+struct DeriveReflect {
+    ident: Ident,
+    generics: Generics,
+    fields: Fields,
+    crate_path: Path,
+}
+
+impl Parse for DeriveReflect {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let DeriveInput { ident, generics, data, .. } = input.parse()?;
+        let Data::Struct(DataStruct { fields, .. }) = data else {
+            return Err(
+                input.error("`Reflect` can only be derived for structs")
+            );
+        };
+        let crate_path = crate_path()?;
+
+        Ok(Self { ident, generics, fields, crate_path })
+    }
+}
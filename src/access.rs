@@ -2,17 +2,20 @@
 
 use core::fmt;
 
+use indexmap::IndexSet;
 use thiserror::Error;
 
+use crate::entity::Disabled;
 use crate::prelude::{
     Component,
+    ComponentId,
     ComponentInfo,
     ComponentSet,
-    ComponentVTable,
     Resource,
     ResourceInfo,
 };
-use crate::storage::{SparseIndex, SparseSet};
+use crate::storage::{BitSet, SparseIndex, SparseSet};
+use crate::world::{FromWorld, World};
 
 /// Type that verifies that world access is correct.
 #[derive(Debug)]
@@ -23,12 +26,48 @@ pub struct WorldAccess {
     all_entities: Option<Level>,
     components: SparseSet<ComponentAccess>,
     resources: SparseSet<ResourceAccess>,
+    /// Components that matched tables must contain.
+    required: ComponentSet,
+    /// Components that matched tables must not contain.
+    excluded: ComponentSet,
+    /// Sets of components that matched tables must contain at least one of.
+    any_of: Vec<ComponentSet>,
+    /// Filters built from other accesses that matched tables must pass.
+    filters: Vec<Filter>,
+    /// Functions that create required resources that don't exist.
+    resource_inits: Vec<(ResourceInfo, ResourceInit)>,
+    /// Whether tables of [`Disabled`] entities are matched.
+    include_disabled: bool,
     /// The first error encountered.
     ///
     /// If the error exists, no more accesses can be added.
     error: Option<AccessError>,
 }
 
+/// A function that creates a resource in a world.
+type ResourceInit = fn(&mut World);
+
+/// The components that a [`WorldAccess`] requires of matched tables, without
+/// its borrows.
+#[derive(Debug, Clone)]
+struct TableFilter {
+    required: ComponentSet,
+    excluded: ComponentSet,
+    any_of: Vec<ComponentSet>,
+    filters: Vec<Filter>,
+}
+
+/// A combination of [`TableFilter`]s.
+#[derive(Debug, Clone)]
+enum Filter {
+    /// Matches tables that pass the filter.
+    All(TableFilter),
+    /// Matches tables that pass at least one of the filters.
+    Any(Vec<TableFilter>),
+    /// Matches tables that don't pass the filter.
+    Not(TableFilter),
+}
+
 /// An error for conflicting access.
 #[derive(Debug, Clone, Copy, Error)]
 #[error("conflicting world access\n- lhs: {lhs}\n- rhs: {rhs}")]
@@ -59,12 +98,41 @@ enum AccessKind {
 
 /// Read or write access.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Level {
     Read,
     Write,
 }
 
+/// A compact form of a [`WorldAccess`] for comparing compatibility.
+///
+/// Only the borrows of an access are kept, as bitsets of component and resource
+/// ids. Created by [`WorldAccess::signature`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessSignature {
+    world: Option<Level>,
+    all_entities: Option<Level>,
+    /// Components borrowed at any level.
+    components: BitSet,
+    components_written: BitSet,
+    /// Resources borrowed at any level.
+    resources: BitSet,
+    resources_written: BitSet,
+}
+
+/// Interns [`AccessSignature`]s, so that equal signatures share an id.
+///
+/// As the access of most systems never changes after they are initialized,
+/// executors can intern their signatures once and compare ids afterwards.
+#[derive(Debug, Default)]
+pub struct AccessSignatures {
+    signatures: IndexSet<AccessSignature>,
+}
+
+/// The id of an [`AccessSignature`] in [`AccessSignatures`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccessSignatureId(usize);
+
 /// Represents access to a particular component.
 #[derive(Clone, Copy)]
 struct ComponentAccess {
@@ -89,9 +157,28 @@ impl WorldAccess {
         let all_entities = None;
         let components = SparseSet::new();
         let resources = SparseSet::new();
+        let required = ComponentSet::new();
+        let excluded = ComponentSet::new();
+        let any_of = Vec::new();
+        let filters = Vec::new();
+        let resource_inits = Vec::new();
+        let include_disabled = false;
         let error = None;
 
-        Self { level, world, all_entities, components, resources, error }
+        Self {
+            level,
+            world,
+            all_entities,
+            components,
+            resources,
+            required,
+            excluded,
+            any_of,
+            filters,
+            resource_inits,
+            include_disabled,
+            error,
+        }
     }
 
     /// The current level of this access.
@@ -120,23 +207,101 @@ impl WorldAccess {
             .chain(resources)
     }
 
+    /// Returns an error if any access of this set conflicts with an access of
+    /// another set.
+    pub fn compatible_with(
+        &self,
+        other: &WorldAccess,
+    ) -> Result<(), AccessError> {
+        for lhs in self.accesses() {
+            for rhs in other.accesses() {
+                if lhs.conflicts_with(rhs) {
+                    return Err(AccessError { lhs, rhs });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the [signature](AccessSignature) of the borrows of this set.
+    pub fn signature(&self) -> AccessSignature {
+        let mut signature = AccessSignature::new();
+
+        signature.world = self.world;
+        signature.all_entities = self.all_entities;
+
+        for access in self.components.iter() {
+            signature.components.insert(access.info.sparse_index());
+
+            if access.level == Level::Write {
+                signature.components_written.insert(access.info.sparse_index());
+            }
+        }
+
+        for access in self.resources.iter() {
+            signature.resources.insert(access.info.sparse_index());
+
+            if access.level == Level::Write {
+                signature.resources_written.insert(access.info.sparse_index());
+            }
+        }
+
+        signature
+    }
+
+    /// Returns the components that matched tables must contain.
+    pub(crate) const fn required_components(&self) -> &ComponentSet {
+        &self.required
+    }
+
+    /// Returns an iterator over the resources that must exist for this access.
+    pub(crate) fn required_resources(
+        &self,
+    ) -> impl Iterator<Item = ResourceInfo> + use<'_> {
+        self.resources
+            .iter()
+            .filter(|access| access.required)
+            .map(|access| access.info)
+    }
+
+    /// Returns the functions that create resources of this access that don't
+    /// exist.
+    pub(crate) fn resource_inits(
+        &self,
+    ) -> impl Iterator<Item = (ResourceInfo, ResourceInit)> + use<'_> {
+        self.resource_inits.iter().copied()
+    }
+
     /// Returns `true` if the described component access is valid for a set of
     /// components.
+    ///
+    /// Tables of [`Disabled`] entities are skipped unless
+    /// [`WorldAccess::includes_disabled`] was called or `Disabled` is
+    /// mentioned by the access.
     pub(crate) fn matches(&self, components: &ComponentSet) -> bool {
-        for access in self.accesses() {
-            match access.kind {
-                // doesn't match if this access requires the component but
-                // doesn't contain it
-                AccessKind::Component { info, required: true }
-                    if !components.contains(info.id()) =>
-                {
-                    return false;
-                },
-                _ => {},
-            }
-        }
+        let disabled = Disabled::id();
+
+        self.required.is_subset(components)
+            && self.excluded.is_disjoint(components)
+            && self.any_of.iter().all(|any_of| !any_of.is_disjoint(components))
+            && self.filters.iter().all(|filter| filter.matches(components))
+            && (!components.contains(disabled)
+                || self.include_disabled
+                || self.components.iter().any(|access| access.info == disabled)
+                || self.required.contains(disabled)
+                || self.excluded.contains(disabled)
+                || self.filters.iter().any(|filter| filter.mentions(disabled)))
+    }
 
-        true
+    /// Returns the components that this access requires of matched tables.
+    fn table_filter(&self) -> TableFilter {
+        TableFilter {
+            required: self.required.clone(),
+            excluded: self.excluded.clone(),
+            any_of: self.any_of.clone(),
+            filters: self.filters.clone(),
+        }
     }
 
     /// Adds a world borrow to the set.
@@ -169,6 +334,166 @@ impl WorldAccess {
         self.add(Access::component(info, level));
     }
 
+    /// Adds a non-required borrow of each component in a set.
+    pub fn maybe_borrows_components(
+        &mut self,
+        components: &ComponentSet,
+        level: Level,
+    ) {
+        for info in components {
+            self.add(Access::component(info, level));
+        }
+    }
+
+    /// Requires matched entities to contain a component without borrowing it.
+    pub fn requires_component<C: Component>(&mut self) {
+        self.required.insert(ComponentInfo::of::<C>());
+    }
+
+    /// Requires matched entities to not contain a component.
+    pub fn excludes_component<C: Component>(&mut self) {
+        self.excluded.insert(ComponentInfo::of::<C>());
+    }
+
+    /// Requires matched entities to contain at least one component of a set
+    /// without borrowing them.
+    pub fn requires_any_of(&mut self, components: ComponentSet) {
+        self.any_of.push(components);
+    }
+
+    /// Requires matched entities to match the components of another access,
+    /// without borrowing them.
+    pub fn requires_match(&mut self, filter: &WorldAccess) {
+        self.filters.push(Filter::All(filter.table_filter()));
+    }
+
+    /// Requires matched entities to match the components of at least one of
+    /// the accesses, without borrowing them.
+    pub fn requires_any_match<'a>(
+        &mut self,
+        filters: impl IntoIterator<Item = &'a WorldAccess>,
+    ) {
+        let filters = filters.into_iter().map(WorldAccess::table_filter);
+
+        self.filters.push(Filter::Any(filters.collect()));
+    }
+
+    /// Requires matched entities to not match the components of another
+    /// access.
+    pub fn excludes_match(&mut self, filter: &WorldAccess) {
+        self.filters.push(Filter::Not(filter.table_filter()));
+    }
+
+    /// Matches [`Disabled`] entities, which are skipped by default.
+    pub fn includes_disabled(&mut self) {
+        self.include_disabled = true;
+    }
+
+    /// Adds all accesses of another set to this set.
+    pub fn extend(&mut self, other: &WorldAccess) {
+        for access in other.accesses() {
+            self.add(access);
+        }
+
+        for info in &other.required {
+            self.required.insert(info);
+        }
+
+        for info in &other.excluded {
+            self.excluded.insert(info);
+        }
+
+        self.any_of.extend(other.any_of.iter().cloned());
+        self.filters.extend(other.filters.iter().cloned());
+        self.resource_inits.extend(other.resource_inits.iter().copied());
+        self.include_disabled |= other.include_disabled;
+
+        if self.error.is_none() {
+            self.error = other.error;
+        }
+    }
+
+    /// Adds all accesses of another set that is accessed after this one.
+    ///
+    /// Unlike [`WorldAccess::extend`], overlapping accesses don't conflict, as
+    /// they are never held at the same time. The higher level is kept.
+    pub fn merge(&mut self, other: &WorldAccess) {
+        self.level = self.level.max(other.level);
+        self.world = self.world.max(other.world);
+        self.all_entities = self.all_entities.max(other.all_entities);
+
+        for &access in other.components.iter() {
+            if let Some(existing) = self.components.insert(access) {
+                self.components.insert(ComponentAccess {
+                    info: access.info,
+                    level: access.level.max(existing.level),
+                    required: access.required || existing.required,
+                });
+            }
+        }
+
+        for &access in other.resources.iter() {
+            if let Some(existing) = self.resources.insert(access) {
+                self.resources.insert(ResourceAccess {
+                    info: access.info,
+                    level: access.level.max(existing.level),
+                    required: access.required || existing.required,
+                });
+            }
+        }
+
+        for info in &other.required {
+            self.required.insert(info);
+        }
+
+        for info in &other.excluded {
+            self.excluded.insert(info);
+        }
+
+        self.any_of.extend(other.any_of.iter().cloned());
+        self.filters.extend(other.filters.iter().cloned());
+        self.resource_inits.extend(other.resource_inits.iter().copied());
+        self.include_disabled |= other.include_disabled;
+
+        if self.error.is_none() {
+            self.error = other.error;
+        }
+    }
+
+    /// Adds the accesses of a phase that happens after the accesses of this
+    /// set, such as the sync of a system after it is run.
+    ///
+    /// Accesses within the phase are checked for conflicts with each other,
+    /// but not with earlier phases, so consecutive phases can each borrow the
+    /// world mutably. See [`WorldAccess::merge`].
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// # use worldlines::access::{Level, WorldAccess};
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// let mut access = WorldAccess::new();
+    ///
+    /// access.borrows_component::<Health>(Level::Write);
+    /// access.push_phase(|phase| phase.borrows_world(Level::Write));
+    ///
+    /// assert!(access.result().is_ok());
+    ///
+    /// access.push_phase(|phase| {
+    ///     phase.borrows_world(Level::Read);
+    ///     phase.borrows_component::<Health>(Level::Write);
+    /// });
+    ///
+    /// assert!(access.result().is_err());
+    /// ```
+    pub fn push_phase(&mut self, f: impl FnOnce(&mut WorldAccess)) {
+        let mut phase = WorldAccess::new();
+
+        f(&mut phase);
+        self.merge(&phase);
+    }
+
     /// Adds a required component borrow to the set.
     ///
     /// If you don't require the component to exist, use
@@ -179,6 +504,18 @@ impl WorldAccess {
         self.add(Access::required_resource(info, level));
     }
 
+    /// Adds a required resource borrow to the set, which is created with
+    /// [`FromWorld`] if it doesn't exist when the system is run.
+    pub fn borrows_resource_or_init<R: Resource + FromWorld>(
+        &mut self,
+        level: Level,
+    ) {
+        self.borrows_resource::<R>(level);
+        self.resource_inits.push((ResourceInfo::of::<R>(), |world| {
+            world.init_resource::<R>();
+        }));
+    }
+
     /// Adds a non-required component borrow to the set.
     ///
     /// If you require the component to exist, use
@@ -211,6 +548,10 @@ impl WorldAccess {
             AccessKind::World => self.world = Some(access.level),
             AccessKind::AllEntities => self.all_entities = Some(access.level),
             AccessKind::Component { info, required } => {
+                if required {
+                    self.required.insert(info);
+                }
+
                 self.components.insert(ComponentAccess {
                     info,
                     level: access.level,
@@ -228,6 +569,201 @@ impl WorldAccess {
     }
 }
 
+impl TableFilter {
+    fn matches(&self, components: &ComponentSet) -> bool {
+        self.required.is_subset(components)
+            && self.excluded.is_disjoint(components)
+            && self.any_of.iter().all(|any_of| !any_of.is_disjoint(components))
+            && self.filters.iter().all(|filter| filter.matches(components))
+    }
+
+    /// Returns `true` if the component is mentioned by this filter.
+    fn mentions(&self, component: ComponentId) -> bool {
+        self.required.contains(component)
+            || self.excluded.contains(component)
+            || self.any_of.iter().any(|any_of| any_of.contains(component))
+            || self.filters.iter().any(|filter| filter.mentions(component))
+    }
+}
+
+impl Filter {
+    fn matches(&self, components: &ComponentSet) -> bool {
+        match self {
+            Self::All(filter) => filter.matches(components),
+            Self::Any(filters) => {
+                filters.iter().any(|filter| filter.matches(components))
+            },
+            Self::Not(filter) => !filter.matches(components),
+        }
+    }
+
+    fn mentions(&self, component: ComponentId) -> bool {
+        match self {
+            Self::All(filter) | Self::Not(filter) => filter.mentions(component),
+            Self::Any(filters) => {
+                filters.iter().any(|filter| filter.mentions(component))
+            },
+        }
+    }
+}
+
+impl AccessSignature {
+    const fn new() -> Self {
+        let world = None;
+        let all_entities = None;
+        let components = BitSet::new();
+        let components_written = BitSet::new();
+        let resources = BitSet::new();
+        let resources_written = BitSet::new();
+
+        Self {
+            world,
+            all_entities,
+            components,
+            components_written,
+            resources,
+            resources_written,
+        }
+    }
+
+    /// Returns `true` if nothing is borrowed.
+    pub fn is_empty(&self) -> bool {
+        self.world.is_none()
+            && self.all_entities.is_none()
+            && self.components.is_empty()
+            && self.resources.is_empty()
+    }
+
+    /// Returns `true` if anything is borrowed mutably.
+    pub fn is_write(&self) -> bool {
+        self.world == Some(Level::Write)
+            || self.all_entities == Some(Level::Write)
+            || !self.components_written.is_empty()
+            || !self.resources_written.is_empty()
+    }
+
+    /// Returns `true` if none of the borrows of this signature conflict with
+    /// the borrows of another.
+    ///
+    /// Equivalent to [`WorldAccess::compatible_with`] on the accesses the
+    /// signatures were created from.
+    pub fn compatible_with(&self, other: &AccessSignature) -> bool {
+        self.compatible_one_way(other) && other.compatible_one_way(self)
+    }
+
+    /// Returns `true` if every borrow of another signature is permitted by
+    /// the borrows of this one, at the same or a lower level.
+    ///
+    /// A world borrow permits everything at its level, and a borrow of all
+    /// entities permits every component at its level.
+    pub fn allows(&self, other: &AccessSignature) -> bool {
+        let all_entities = self.all_entities.max(self.world);
+        let components = match all_entities {
+            Some(Level::Write) => true,
+            Some(Level::Read) => other.components_written.is_empty(),
+            None => {
+                other.components.is_subset(&self.components)
+                    && other
+                        .components_written
+                        .is_subset(&self.components_written)
+            },
+        };
+        let resources = match self.world {
+            Some(Level::Write) => true,
+            Some(Level::Read) => other.resources_written.is_empty(),
+            None => {
+                other.resources.is_subset(&self.resources)
+                    && other
+                        .resources_written
+                        .is_subset(&self.resources_written)
+            },
+        };
+
+        other.world <= self.world
+            && other.all_entities <= all_entities
+            && components
+            && resources
+    }
+
+    fn compatible_one_way(&self, other: &AccessSignature) -> bool {
+        let world = match self.world {
+            Some(Level::Write) => other.is_empty(),
+            Some(Level::Read) => !other.is_write(),
+            None => true,
+        };
+        let all_entities = match self.all_entities {
+            Some(Level::Write) => {
+                other.all_entities.is_none() && other.components.is_empty()
+            },
+            Some(Level::Read) => {
+                other.all_entities != Some(Level::Write)
+                    && other.components_written.is_empty()
+            },
+            None => true,
+        };
+
+        world
+            && all_entities
+            && self.components_written.is_disjoint(&other.components)
+            && self.resources_written.is_disjoint(&other.resources)
+    }
+}
+
+impl AccessSignatures {
+    /// Creates an empty signature registry.
+    pub fn new() -> Self {
+        let signatures = IndexSet::new();
+
+        Self { signatures }
+    }
+
+    /// Returns the amount of unique signatures.
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Returns `true` if no signatures have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Returns the id of the signature of an access, interning it if needed.
+    pub fn intern(&mut self, access: &WorldAccess) -> AccessSignatureId {
+        AccessSignatureId(self.signatures.insert_full(access.signature()).0)
+    }
+
+    /// Returns the signature with the id.
+    ///
+    /// Returns `None` if the id is from another registry.
+    pub fn get(&self, id: AccessSignatureId) -> Option<&AccessSignature> {
+        self.signatures.get_index(id.0)
+    }
+
+    /// Returns `true` if the signatures with the ids are
+    /// [compatible](AccessSignature::compatible_with).
+    ///
+    /// # Panics
+    ///
+    /// Panics if either id is from another registry.
+    pub fn compatible(
+        &self,
+        lhs: AccessSignatureId,
+        rhs: AccessSignatureId,
+    ) -> bool {
+        let lhs_signature =
+            self.get(lhs).expect("access signature from another registry");
+
+        if lhs == rhs {
+            return !lhs_signature.is_write();
+        }
+
+        let rhs_signature =
+            self.get(rhs).expect("access signature from another registry");
+
+        lhs_signature.compatible_with(rhs_signature)
+    }
+}
+
 impl Access {
     const fn component(info: ComponentInfo, level: Level) -> Self {
         Self { kind: AccessKind::Component { info, required: false }, level }
@@ -272,8 +808,14 @@ impl AccessKind {
                 Self::Resource { info: lhs, .. },
                 Self::Resource { info: rhs, .. },
             ) => lhs != rhs,
-            (Self::AllEntities, Self::Resource { .. })
-            | (Self::Resource { .. }, Self::AllEntities) => true,
+            (
+                Self::AllEntities | Self::Component { .. },
+                Self::Resource { .. },
+            )
+            | (
+                Self::Resource { .. },
+                Self::AllEntities | Self::Component { .. },
+            ) => true,
             _ => false,
         }
     }
@@ -460,4 +1002,138 @@ mod tests {
             "entities don't access resources",
         );
     }
+
+    #[test]
+    fn components_do_not_conflict_with_resources() {
+        let a = ComponentInfo::of::<A>();
+        let b = ResourceInfo::of::<B>();
+
+        assert!(
+            !Access::component(a, Level::Write)
+                .conflicts_with(Access::resource(b, Level::Write)),
+            "components and resources are stored separately",
+        );
+    }
+
+    #[test]
+    fn signatures_match_accesses() {
+        let mut read_a = WorldAccess::new();
+        let mut write_a = WorldAccess::new();
+        let mut write_b = WorldAccess::new();
+        let mut entities = WorldAccess::new();
+        let mut world = WorldAccess::new();
+
+        read_a.borrows_component::<A>(Level::Read);
+        read_a.borrows_resource::<B>(Level::Read);
+        write_a.borrows_component::<A>(Level::Write);
+        write_b.borrows_component::<B>(Level::Write);
+        write_b.borrows_resource::<A>(Level::Write);
+        entities.borrows_all_entities(Level::Read);
+        world.borrows_world(Level::Read);
+
+        let accesses = [read_a, write_a, write_b, entities, world];
+
+        for lhs in &accesses {
+            for rhs in &accesses {
+                assert_eq!(
+                    lhs.signature().compatible_with(&rhs.signature()),
+                    lhs.compatible_with(rhs).is_ok(),
+                    "{lhs:?} and {rhs:?}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn signature_allows() {
+        let mut read_a = WorldAccess::new();
+        let mut write_a = WorldAccess::new();
+        let mut read_b = WorldAccess::new();
+        let mut entities = WorldAccess::new();
+        let mut world = WorldAccess::new();
+
+        read_a.borrows_component::<A>(Level::Read);
+        write_a.borrows_component::<A>(Level::Write);
+        read_b.borrows_resource::<B>(Level::Read);
+        entities.borrows_all_entities(Level::Read);
+        world.borrows_world(Level::Read);
+
+        let [read_a, write_a, read_b, entities, world] =
+            [read_a, write_a, read_b, entities, world]
+                .map(|access| access.signature());
+
+        assert!(write_a.allows(&read_a));
+        assert!(!read_a.allows(&write_a));
+        assert!(!read_a.allows(&read_b));
+        assert!(entities.allows(&read_a));
+        assert!(!entities.allows(&write_a));
+        assert!(!entities.allows(&read_b));
+        assert!(world.allows(&read_a));
+        assert!(world.allows(&read_b));
+        assert!(world.allows(&entities));
+        assert!(!world.allows(&write_a));
+        assert!(!entities.allows(&world));
+    }
+
+    #[test]
+    fn intern_signatures() {
+        let mut signatures = AccessSignatures::new();
+        let mut read = WorldAccess::new();
+        let mut write = WorldAccess::new();
+
+        read.borrows_component::<A>(Level::Read);
+        write.borrows_component::<A>(Level::Write);
+
+        let read_id = signatures.intern(&read);
+        let write_id = signatures.intern(&write);
+
+        // filters aren't part of the signature
+        read.requires_component::<B>();
+
+        assert_eq!(signatures.intern(&read), read_id);
+        assert_eq!(signatures.len(), 2);
+        assert!(signatures.compatible(read_id, read_id));
+        assert!(!signatures.compatible(write_id, write_id));
+        assert!(!signatures.compatible(read_id, write_id));
+    }
+
+    #[test]
+    fn merged_accesses_do_not_conflict() {
+        let mut lhs = WorldAccess::new();
+        let mut rhs = WorldAccess::new();
+
+        lhs.borrows_component::<A>(Level::Read);
+        rhs.borrows_component::<A>(Level::Write);
+        rhs.borrows_resource::<B>(Level::Write);
+        lhs.merge(&rhs);
+
+        assert!(lhs.result().is_ok());
+        assert_eq!(lhs.level(), Some(Level::Write));
+        assert!(
+            lhs.components.iter().any(|access| access.level == Level::Write),
+            "the higher level is kept",
+        );
+    }
+
+    #[test]
+    fn phases() {
+        let mut access = WorldAccess::new();
+
+        access.push_phase(|init| init.borrows_world(Level::Read));
+        access.push_phase(|run| {
+            run.borrows_component::<A>(Level::Write);
+            run.borrows_resource::<B>(Level::Read);
+        });
+        access.push_phase(|sync| sync.borrows_world(Level::Write));
+
+        assert!(access.result().is_ok());
+        assert_eq!(access.level(), Some(Level::Write));
+
+        access.push_phase(|run| {
+            run.borrows_component::<A>(Level::Write);
+            run.borrows_component::<A>(Level::Read);
+        });
+
+        assert!(access.result().is_err());
+    }
 }
@@ -0,0 +1,318 @@
+//! Typed storage for shared values, referenced by [handles](Handle).
+
+use std::any::type_name;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::num::NonZeroU32;
+use std::{fmt, mem};
+
+use crate::component::{Component, ComponentId};
+use crate::marker::MaybeSendSync;
+use crate::resource::{Resource, ResourceId};
+
+/// A resource that stores values of `T` behind [handles](Handle).
+///
+/// Additions, mutable accesses and removals are recorded as
+/// [asset events](AssetEvent), which can be read with
+/// [`Assets::drain_events`].
+pub struct Assets<T> {
+    slots: Vec<AssetSlot<T>>,
+    /// Indices of empty slots.
+    free: Vec<u32>,
+    len: usize,
+    events: Vec<AssetEvent<T>>,
+}
+
+/// A generational id for a value in [`Assets`].
+///
+/// Handles are components, so entities can reference assets. A handle is
+/// stale once its value is removed, even if its slot is reused.
+pub struct Handle<T> {
+    index: u32,
+    version: NonZeroU32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// A change to a value in [`Assets`].
+pub enum AssetEvent<T> {
+    /// A value was added.
+    Added(Handle<T>),
+    /// A value was accessed mutably.
+    Modified(Handle<T>),
+    /// A value was removed.
+    Removed(Handle<T>),
+}
+
+struct AssetSlot<T> {
+    version: NonZeroU32,
+    value: Option<T>,
+}
+
+impl<T> Assets<T> {
+    /// Creates new empty asset storage.
+    pub const fn new() -> Self {
+        let slots = Vec::new();
+        let free = Vec::new();
+        let len = 0;
+        let events = Vec::new();
+
+        Self { slots, free, len, events }
+    }
+
+    /// Returns the amount of stored values.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no values are stored.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the handle refers to a stored value.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Stores a value and returns a handle to it.
+    pub fn add(&mut self, value: T) -> Handle<T> {
+        let handle = if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+
+            slot.value = Some(value);
+
+            Handle::new(index, slot.version)
+        } else {
+            let index =
+                u32::try_from(self.slots.len()).expect("asset overflow");
+            let version = NonZeroU32::MIN;
+
+            self.slots.push(AssetSlot { version, value: Some(value) });
+
+            Handle::new(index, version)
+        };
+
+        self.len += 1;
+        self.events.push(AssetEvent::Added(handle));
+
+        handle
+    }
+
+    /// Returns a reference to the value of a handle.
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.version == handle.version)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    /// Returns a mutable reference to the value of a handle.
+    ///
+    /// Records an [`AssetEvent::Modified`] if the value exists.
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        let value = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.version == handle.version)
+            .and_then(|slot| slot.value.as_mut())?;
+
+        self.events.push(AssetEvent::Modified(handle));
+
+        Some(value)
+    }
+
+    /// Removes the value of a handle.
+    ///
+    /// Returns `None` if the handle is stale.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.version == handle.version)?;
+        let value = slot.value.take()?;
+
+        // a slot whose version would overflow is retired instead of reused
+        if let Some(version) = slot.version.checked_add(1) {
+            slot.version = version;
+            self.free.push(handle.index);
+        }
+
+        self.len -= 1;
+        self.events.push(AssetEvent::Removed(handle));
+
+        Some(value)
+    }
+
+    /// Returns an iterator over the handles and values in storage.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = Handle::new(index as _, slot.version);
+
+            slot.value.as_ref().map(|value| (handle, value))
+        })
+    }
+
+    /// Takes the events recorded since the last call, in order.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = AssetEvent<T>> {
+        mem::take(&mut self.events).into_iter()
+    }
+}
+
+impl<T> Handle<T> {
+    const fn new(index: u32, version: NonZeroU32) -> Self {
+        Self { index, version, _marker: PhantomData }
+    }
+}
+
+impl<T> AssetEvent<T> {
+    /// Returns the handle of the changed value.
+    pub const fn handle(&self) -> Handle<T> {
+        match *self {
+            Self::Added(handle)
+            | Self::Modified(handle)
+            | Self::Removed(handle) => handle,
+        }
+    }
+}
+
+// ---
+
+/// # Safety
+///
+/// The id is stored with [`ResourceId::of_type`].
+unsafe impl<T: MaybeSendSync + 'static> Resource for Assets<T> {
+    fn id() -> ResourceId {
+        ResourceId::of_type::<Self>()
+    }
+}
+
+/// # Safety
+///
+/// The id is stored with [`ComponentId::of_type`].
+unsafe impl<T: 'static> Component for Handle<T> {
+    fn id() -> ComponentId {
+        ComponentId::of_type::<Self>()
+    }
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.version == other.version
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.version.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Handle<{}>({}v{})",
+            type_name::<T>(),
+            self.index,
+            self.version
+        )
+    }
+}
+
+impl<T> Clone for AssetEvent<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AssetEvent<T> {}
+
+impl<T> PartialEq for AssetEvent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+            && self.handle() == other.handle()
+    }
+}
+
+impl<T> Eq for AssetEvent<T> {}
+
+impl<T> fmt::Debug for AssetEvent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Added(_) => "Added",
+            Self::Modified(_) => "Modified",
+            Self::Removed(_) => "Removed",
+        };
+
+        f.debug_tuple(name).field(&self.handle()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    struct Mesh(u32);
+
+    #[test]
+    fn handles_are_generational() {
+        let mut meshes = Assets::new();
+        let first = meshes.add(Mesh(3));
+
+        assert_eq!(meshes.remove(first).map(|mesh| mesh.0), Some(3));
+
+        let second = meshes.add(Mesh(4));
+
+        assert!(!meshes.contains(first));
+        assert_eq!(meshes.get(second).map(|mesh| mesh.0), Some(4));
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(
+            meshes.drain_events().collect::<Vec<_>>(),
+            [
+                AssetEvent::Added(first),
+                AssetEvent::Removed(first),
+                AssetEvent::Added(second),
+            ]
+        );
+        assert_eq!(meshes.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn handle_components() {
+        let mut world = World::new();
+        let mut meshes = Assets::new();
+        let triangle = meshes.add(Mesh(3));
+
+        world.create(meshes);
+        world.spawn(triangle);
+        world.spawn(triangle);
+
+        let query = world.query::<&Handle<Mesh>>().unwrap();
+        let meshes = world.resource::<Assets<Mesh>>().unwrap();
+        let vertices: u32 = query
+            .iter()
+            .filter_map(|&handle| meshes.get(handle))
+            .map(|mesh| mesh.0)
+            .sum();
+
+        assert_eq!(vertices, 6);
+    }
+}
@@ -1,5 +1,16 @@
-use super::{Commands, EntityCommand};
-use crate::entity::{EntityId, EntityWorld};
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::sync::{Mutex, PoisonError};
+
+use super::{CommandResult, Commands, EntityCommand, IntoCommandResult};
+use crate::access::{Level, WorldAccess};
+use crate::component::{Bundle, Component, ComponentHook, HookContext};
+use crate::entity::{EntityId, EntityPtr, EntityWorld};
+use crate::marker::MaybeSend;
+use crate::query::QueryData;
+use crate::resource::Resource;
+use crate::world::World;
 
 /// A type to queue commands to perform on entities.
 pub struct EntityQueue<'s> {
@@ -7,6 +18,62 @@ pub struct EntityQueue<'s> {
     commands: &'s mut Commands,
 }
 
+/// Query data that queues commands to perform on the queried entity.
+///
+/// Lets iteration code make structural changes to each entity without a
+/// separate [`WorldQueue`](super::WorldQueue). The commands are applied by
+/// [`World::apply_deferred`], which is called after running a system with
+/// [`World::run_system`].
+///
+/// As the commands make structural changes, the query item borrows the queue
+/// mutably, so it can't be used in read-only queries or alongside system inputs
+/// that read the whole world, such as a [`WorldQueue`](super::WorldQueue).
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Hp(u32);
+///
+/// let mut world = World::new();
+/// let dead = world.spawn(Hp(0)).id();
+///
+/// world.spawn(Hp(3));
+///
+/// let mut query = world.query_mut::<(&Hp, EntityCommands)>().unwrap();
+///
+/// for (hp, commands) in query.iter_mut() {
+///     if hp.0 == 0 {
+///         commands.despawn();
+///     }
+/// }
+///
+/// drop(query);
+/// world.apply_deferred();
+///
+/// assert!(!world.contains(dead));
+/// assert_eq!(world.len(), 1);
+/// ```
+#[derive(Clone, Copy)]
+pub struct EntityCommands<'w> {
+    id: EntityId,
+    commands: &'w Mutex<Commands>,
+}
+
+/// The resource borrowed by [`EntityCommands`] in place of [`World::deferred`].
+///
+/// Never inserted into a world. It only exists so that queuing commands is
+/// declared as write access.
+#[derive(Resource)]
+struct DeferredCommands;
+
+/// A queued view into a component of an entity that may not exist.
+///
+/// Returned by [`EntityQueue::entry`].
+pub struct EntityQueueEntry<'s, C: Component> {
+    queue: EntityQueue<'s>,
+    _marker: PhantomData<C>,
+}
+
 impl<'s> EntityQueue<'s> {
     /// Creates a new queue for the given entity.
     pub(crate) fn new(id: EntityId, commands: &'s mut Commands) -> Self {
@@ -26,25 +93,239 @@ impl<'s> EntityQueue<'s> {
     /// Pushes a function command to the entity queue.
     ///
     /// Helpful as using [`EntityQueue::push`] on a closure fails type elision.
-    pub fn push_fn(
+    /// The function can return `()` or a [`CommandResult`].
+    ///
+    /// The command fails with [`EntityNotFound`](crate::entity::EntityNotFound)
+    /// if the entity was despawned before it was applied.
+    pub fn push_fn<R: IntoCommandResult>(
         &mut self,
-        f: impl FnOnce(EntityWorld<'_>) + Send + 'static,
+        f: impl FnOnce(EntityWorld<'_>) -> R + MaybeSend + 'static,
     ) {
         let entity = self.id;
 
-        self.commands.push_fn(move |world| {
-            let Ok(entity) = EntityWorld::new(entity, world) else {
-                return;
-            };
+        self.commands.push_fn(move |world: &mut World| -> CommandResult {
+            f(EntityWorld::new(entity, world)?).into_command_result()
+        })
+    }
 
-            f(entity);
+    /// Pushes an [`after_insert`](Component::after_insert) hook to the entity
+    /// queue, which also updates the indexes of the component.
+    ///
+    /// Unlike other entity commands, hooks are skipped if the entity was
+    /// despawned by an earlier command.
+    pub(crate) fn push_insert_hook(
+        &mut self,
+        hook: ComponentHook,
+        ctx: HookContext,
+    ) {
+        let entity = self.id;
+
+        self.commands.push_fn(move |world: &mut World| {
+            if let Ok(mut entity) = EntityWorld::new(entity, world) {
+                hook(entity.as_mut(), ctx);
+                // SAFETY: the entity was checked to be alive above
+                unsafe { world.notify_insert(ctx.entity(), ctx.component()) };
+            }
         })
     }
 
+    /// Queues inserting a component into this entity.
+    #[track_caller]
+    pub fn insert<C: Component>(&mut self, component: C) -> &mut Self {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).insert(component);
+        });
+
+        self
+    }
+
+    /// Queues inserting a component into this entity if it doesn't already
+    /// contain it.
+    #[track_caller]
+    pub fn insert_if_new<C: Component>(&mut self, component: C) -> &mut Self {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).insert_if_new(component);
+        });
+
+        self
+    }
+
+    /// Queues removing a component from this entity.
+    ///
+    /// Does nothing if the entity doesn't contain the component.
+    #[track_caller]
+    pub fn remove<C: Component>(&mut self) -> &mut Self {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            _ = entity.with_caller(caller).remove::<C>();
+        });
+
+        self
+    }
+
+    /// Queues removing all components of this entity that aren't in `B`.
+    #[track_caller]
+    pub fn retain<B: Bundle>(&mut self) -> &mut Self {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).retain::<B>();
+        });
+
+        self
+    }
+
+    /// Queues removing all components of this entity without despawning it.
+    #[track_caller]
+    pub fn clear(&mut self) -> &mut Self {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).clear();
+        });
+
+        self
+    }
+
+    /// Returns a queued entry for a component of this entity.
+    pub fn entry<C: Component>(&mut self) -> EntityQueueEntry<'_, C> {
+        EntityQueueEntry {
+            queue: EntityQueue::new(self.id, self.commands),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Queues a command to despawn this entity.
+    ///
+    /// The command fails if the entity was already despawned.
+    #[track_caller]
+    pub fn despawn(mut self) {
+        let caller = Location::caller();
+
+        self.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).despawn();
+        });
+    }
+}
+
+impl EntityCommands<'_> {
+    /// Returns the id of this entity.
+    pub const fn id(&self) -> EntityId {
+        self.id
+    }
+
+    /// Runs a function with an [`EntityQueue`] for this entity.
+    pub fn queue<T>(&self, f: impl FnOnce(&mut EntityQueue<'_>) -> T) -> T {
+        let mut commands =
+            self.commands.lock().unwrap_or_else(PoisonError::into_inner);
+
+        f(&mut EntityQueue::new(self.id, &mut commands))
+    }
+
+    /// Queues inserting a component into this entity.
+    #[track_caller]
+    pub fn insert<C: Component>(&self, component: C) {
+        let mut commands =
+            self.commands.lock().unwrap_or_else(PoisonError::into_inner);
+
+        EntityQueue::new(self.id, &mut commands).insert(component);
+    }
+
+    /// Queues removing a component from this entity.
+    ///
+    /// Does nothing if the entity doesn't contain the component.
+    #[track_caller]
+    pub fn remove<C: Component>(&self) {
+        let mut commands =
+            self.commands.lock().unwrap_or_else(PoisonError::into_inner);
+
+        EntityQueue::new(self.id, &mut commands).remove::<C>();
+    }
+
     /// Queues a command to despawn this entity.
-    pub fn despawn(self) {
-        self.commands.push_fn(move |world| {
-            _ = world.despawn(self.id);
+    ///
+    /// The command fails if the entity was already despawned.
+    #[track_caller]
+    pub fn despawn(&self) {
+        let mut commands =
+            self.commands.lock().unwrap_or_else(PoisonError::into_inner);
+
+        EntityQueue::new(self.id, &mut commands).despawn();
+    }
+}
+
+/// # Safety
+///
+/// No components are accessed. Commands are queued through a mutex that is
+/// only accessed mutably by [`World::apply_deferred`]. The access declares a
+/// write borrow of a private resource, so that read-only access can't queue
+/// structural changes.
+unsafe impl QueryData for EntityCommands<'_> {
+    type Output<'w> = EntityCommands<'w>;
+
+    fn world_access(access: &mut WorldAccess) {
+        access.maybe_borrows_resource::<DeferredCommands>(Level::Write);
+    }
+
+    unsafe fn get(entity: EntityPtr<'_>) -> Self::Output<'_> {
+        // SAFETY: the deferred commands aren't borrowed mutably while the
+        // world is borrowed by a query
+        let world = unsafe { entity.world().as_ref() };
+
+        EntityCommands { id: entity.id(), commands: &world.deferred }
+    }
+}
+
+impl fmt::Debug for EntityCommands<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EntityCommands").field(&self.id).finish()
+    }
+}
+
+impl<C: Component> EntityQueueEntry<'_, C> {
+    /// Queues calling a function on the component if it exists.
+    pub fn and_modify(
+        mut self,
+        f: impl FnOnce(&mut C) + MaybeSend + 'static,
+    ) -> Self {
+        self.queue.push_fn(|mut entity: EntityWorld<'_>| {
+            entity.entry::<C>().and_modify(f);
         });
+
+        self
+    }
+
+    /// Queues inserting the component if it doesn't exist.
+    #[track_caller]
+    pub fn or_insert(self, default: C) {
+        self.or_insert_with(|| default);
+    }
+
+    /// Queues inserting the result of a function if the component doesn't
+    /// exist.
+    #[track_caller]
+    pub fn or_insert_with(
+        mut self,
+        f: impl FnOnce() -> C + MaybeSend + 'static,
+    ) {
+        let caller = Location::caller();
+
+        self.queue.push_fn(move |entity: EntityWorld<'_>| {
+            entity.with_caller(caller).entry::<C>().or_insert_with(f);
+        });
+    }
+
+    /// Queues inserting the default value if the component doesn't exist.
+    #[track_caller]
+    pub fn or_default(self)
+    where
+        C: Default,
+    {
+        self.or_insert_with(C::default);
     }
 }
@@ -0,0 +1,76 @@
+use std::error::Error;
+
+use thiserror::Error;
+
+/// The result of applying a [`Command`](super::Command).
+pub type CommandResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// Trait for values that can be returned from a command.
+///
+/// Implemented for `()` and [`CommandResult`], so command closures can either
+/// return nothing or use `?`.
+pub trait IntoCommandResult {
+    /// Converts this value into a [`CommandResult`].
+    fn into_command_result(self) -> CommandResult;
+}
+
+/// An error returned by a failed [`Command`](super::Command).
+#[derive(Debug, Error)]
+#[error("command `{command}` failed: {error}")]
+pub struct CommandError {
+    command: &'static str,
+    #[source]
+    error: Box<dyn Error + Send + Sync>,
+}
+
+/// How a [`World`](crate::world::World) handles [errors](CommandError)
+/// returned by commands.
+///
+/// See [`World::set_command_error_handler`](crate::world::World::set_command_error_handler).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommandErrorHandler {
+    /// Panics with the error.
+    #[default]
+    Panic,
+    /// Prints the error to stderr and continues with the next command.
+    Log,
+    /// Stores the error to be inspected with
+    /// [`World::take_command_errors`](crate::world::World::take_command_errors).
+    Collect,
+}
+
+impl IntoCommandResult for () {
+    fn into_command_result(self) -> CommandResult {
+        Ok(())
+    }
+}
+
+impl IntoCommandResult for CommandResult {
+    fn into_command_result(self) -> CommandResult {
+        self
+    }
+}
+
+impl CommandError {
+    pub(crate) fn new(
+        command: &'static str,
+        error: Box<dyn Error + Send + Sync>,
+    ) -> Self {
+        Self { command, error }
+    }
+
+    /// Returns the [name](super::Command::name) of the failed command.
+    pub const fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// Returns the error returned by the command.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// Returns the error returned by the command if it is an `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref()
+    }
+}
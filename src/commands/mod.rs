@@ -1,21 +1,24 @@
 //! Deferred operations to be performed on the world.
 
 use std::any::type_name;
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 use std::ptr::NonNull;
-use std::{fmt, ptr};
 
 pub use self::entity::*;
+pub use self::error::*;
 pub use self::world::*;
 use crate::entity::EntityWorld;
+use crate::marker::{MaybeSend, ThreadMarker};
 use crate::world::World;
 
 mod entity;
+mod error;
 mod world;
 
 /// A command to be performed on the world.
-pub trait Command: Send + 'static {
+pub trait Command: MaybeSend + 'static {
     /// Returns the name of this commands for debugging purposes.
     ///
     /// Defaults to the [`type_name`].
@@ -24,32 +27,66 @@ pub trait Command: Send + 'static {
     }
 
     /// Apply this command on a world.
-    fn apply(self, world: &mut World);
+    ///
+    /// Errors are passed to the world's [`CommandErrorHandler`].
+    fn apply(self, world: &mut World) -> CommandResult;
 }
 
 /// A command to be performed on an entity.
-pub trait EntityCommand: Send + 'static {
+pub trait EntityCommand: MaybeSend + 'static {
     /// Applies this command to an entity.
-    fn apply(self, entity: EntityWorld<'_>);
+    ///
+    /// Errors are passed to the world's [`CommandErrorHandler`].
+    fn apply(self, entity: EntityWorld<'_>) -> CommandResult;
 }
 
-impl<F: FnOnce(&mut World) + Send + 'static> Command for F {
-    fn apply(self, world: &mut World) {
-        self(world);
+impl<F, R> Command for F
+where
+    F: FnOnce(&mut World) -> R + MaybeSend + 'static,
+    R: IntoCommandResult,
+{
+    fn apply(self, world: &mut World) -> CommandResult {
+        self(world).into_command_result()
     }
 }
 
-impl<F: FnOnce(EntityWorld<'_>) + Send + 'static> EntityCommand for F {
-    fn apply(self, entity: EntityWorld<'_>) {
-        self(entity);
+impl<F, R> EntityCommand for F
+where
+    F: FnOnce(EntityWorld<'_>) -> R + MaybeSend + 'static,
+    R: IntoCommandResult,
+{
+    fn apply(self, entity: EntityWorld<'_>) -> CommandResult {
+        self(entity).into_command_result()
     }
 }
 
 /// A buffer of [commands](Command) to be performed on a world.
+///
+/// Commands are stored unaligned and back-to-back in a byte buffer. The buffer
+/// is only ever accessed through `&mut self`, so `Commands` is [`Send`]
+/// because all commands are and [`Sync`] because a shared reference can't
+/// reach any command. With the `single_threaded` feature it is neither.
+///
+/// # Ordering
+///
+/// Commands are applied in the order they were pushed, and
+/// [`Commands::append`] keeps the order of both buffers. Commands queued while
+/// applying a command, such as by component hooks, are applied when the world
+/// is next flushed, which structural changes do before returning. So they
+/// are applied before the next command of the buffer.
+///
+/// Each [`WorldQueue`] system input has its own buffer, which is applied right
+/// after the system runs. A system applies the buffers of its inputs in
+/// parameter order, and a [piped](crate::system::IntoSystem::pipe) system
+/// applies those of its first system before those of its second. Commands of
+/// [`EntityCommands`] are applied after those of the system inputs, by
+/// [`World::apply_deferred`]. The changes of one system are therefore visible
+/// to any system run after it.
 #[derive(Default)]
 pub struct Commands {
     commands: Vec<&'static dyn CommandInfo>,
     bytes: Vec<MaybeUninit<u8>>,
+    _marker: ThreadMarker,
 }
 
 /// # Safety
@@ -57,25 +94,27 @@ pub struct Commands {
 /// The value returned by [`CommandInfo::size`] must equal the size of the
 /// represented command. The function returned by [`CommandInfo::drop`] must
 /// only call the type's drop implementation.
-unsafe trait CommandInfo {
+unsafe trait CommandInfo: Send + Sync {
     /// [`Command::name`].
     fn name(&self) -> &'static str;
 
     /// Size in bytes.
     fn size(&self) -> usize;
 
-    /// A function that can drop the command.
+    /// A function that can drop the command from an unaligned pointer.
     fn drop(&self) -> unsafe fn(*mut u8);
 
     /// Call [`Command::apply`] on a pointer to a command.
-    unsafe fn call(&self, ptr: NonNull<u8>, world: &mut World);
+    unsafe fn call(&self, ptr: NonNull<u8>, world: &mut World)
+        -> CommandResult;
 }
 
 fn command_info_of_val<C: Command>(_: &C) -> &'static dyn CommandInfo {
-    &PhantomData::<C>
+    &PhantomData::<fn() -> C>
 }
 
-unsafe impl<C: Command> CommandInfo for PhantomData<C> {
+// `fn() -> C` so that the info is `Send + Sync` regardless of `C`
+unsafe impl<C: Command> CommandInfo for PhantomData<fn() -> C> {
     fn name(&self) -> &'static str {
         C::name()
     }
@@ -85,13 +124,19 @@ unsafe impl<C: Command> CommandInfo for PhantomData<C> {
     }
 
     fn drop(&self) -> unsafe fn(*mut u8) {
-        |ptr| unsafe { ptr::drop_in_place(ptr.cast::<C>()) }
+        // commands are stored unaligned, so they must be read out before being
+        // dropped
+        |ptr| drop(unsafe { ptr.cast::<C>().read_unaligned() })
     }
 
-    unsafe fn call(&self, ptr: NonNull<u8>, world: &mut World) {
+    unsafe fn call(
+        &self,
+        ptr: NonNull<u8>,
+        world: &mut World,
+    ) -> CommandResult {
         let command = unsafe { ptr.cast().read_unaligned() };
 
-        C::apply(command, world);
+        C::apply(command, world)
     }
 }
 
@@ -101,7 +146,16 @@ impl Commands {
         let commands = Vec::new();
         let bytes = Vec::new();
 
-        Self { bytes, commands }
+        Self { bytes, commands, _marker: PhantomData }
+    }
+
+    /// Creates a new empty command buffer with space for at least `commands`
+    /// commands totalling `bytes` bytes.
+    pub fn with_capacity(commands: usize, bytes: usize) -> Self {
+        let commands = Vec::with_capacity(commands);
+        let bytes = Vec::with_capacity(bytes);
+
+        Self { bytes, commands, _marker: PhantomData }
     }
 
     /// Returns the amount of commands in the queue.
@@ -133,23 +187,54 @@ impl Commands {
             unsafe { self.bytes.as_mut_ptr().byte_add(byte_index).cast() };
 
         unsafe { write_unaligned(ptr, command) };
+        // SAFETY: the command was written to the reserved bytes
+        unsafe { self.bytes.set_len(byte_index + info.size()) };
+    }
+
+    /// Moves all commands of `other` to the end of this buffer, leaving
+    /// `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        // commands are stored back-to-back, so the byte offsets of the
+        // commands of `other` are preserved
+        self.commands.append(&mut other.commands);
+        self.bytes.append(&mut other.bytes);
     }
 
     /// Pushes a function command to the queue.
     ///
     /// Helpful as using [`Commands::push`] on a closure fails type
-    /// elision.
-    pub fn push_fn(&mut self, f: impl FnOnce(&mut World) + Send + 'static) {
+    /// elision. The function can return `()` or a [`CommandResult`].
+    pub fn push_fn<R: IntoCommandResult>(
+        &mut self,
+        f: impl FnOnce(&mut World) -> R + MaybeSend + 'static,
+    ) {
         self.push(f);
     }
 
     /// Applies stored commands to the world.
+    ///
+    /// Errors returned by commands are passed to the world's
+    /// [`CommandErrorHandler`].
     #[track_caller]
     pub fn apply(&mut self, world: &mut World) {
         self.for_each(|info, ptr| {
             // SAFETY: the pointer is to a valid instance of the command as it
             // resides at the current index
-            unsafe { info.call(ptr, world) };
+            if let Err(error) = unsafe { info.call(ptr, world) } {
+                world.handle_command_error(CommandError::new(
+                    info.name(),
+                    error,
+                ));
+            }
+        });
+    }
+
+    /// Drops all stored commands without applying them.
+    ///
+    /// Keeps the allocated capacity of this buffer.
+    pub fn clear(&mut self) {
+        self.for_each(|info, ptr| unsafe {
+            info.drop()(ptr.as_ptr());
         });
     }
 
@@ -166,14 +251,19 @@ impl Commands {
         &mut self,
         mut f: impl FnMut(&'static dyn CommandInfo, NonNull<u8>),
     ) {
+        let len = self.bytes.len();
+        let bytes = self.bytes.as_mut_ptr();
+
+        // cleared up front, so that the buffer is left empty if `f` panics, as
+        // `drain` then forgets the remaining commands. The bytes stay valid,
+        // as they don't need to be dropped and nothing is pushed meanwhile
+        self.bytes.clear();
         self.commands
             .drain(..)
             .scan(0, |byte_index, info| {
                 // less-than-or-equal-to, as the command could be a ZST
-                let ptr = (*byte_index <= self.bytes.len()).then(|| unsafe {
-                    NonNull::new_unchecked(
-                        self.bytes.as_mut_ptr().byte_add(*byte_index).cast(),
-                    )
+                let ptr = (*byte_index <= len).then(|| unsafe {
+                    NonNull::new_unchecked(bytes.byte_add(*byte_index).cast())
                 });
 
                 if ptr.is_some() {
@@ -186,21 +276,9 @@ impl Commands {
     }
 }
 
-/// # Safety
-///
-/// All commands are [`Send`].
-unsafe impl Send for Commands {}
-
-/// # Safety
-///
-/// [`Commands`] does not expose references to internal commands.
-unsafe impl Sync for Commands {}
-
 impl Drop for Commands {
     fn drop(&mut self) {
-        self.for_each(|info, ptr| unsafe {
-            info.drop()(ptr.as_ptr());
-        });
+        self.clear();
     }
 }
 
@@ -216,11 +294,11 @@ impl fmt::Debug for Commands {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::{self, AtomicBool};
+    use std::sync::atomic::{self, AtomicBool, AtomicUsize};
 
     use super::*;
-    use crate::entity::EntityId;
-    use crate::prelude::{Bundle, Component};
+    use crate::entity::{EntityId, EntityNotFound};
+    use crate::prelude::{Bundle, Component, Query, Resource, With};
 
     #[derive(Component)]
     struct Name(&'static str);
@@ -228,13 +306,23 @@ mod tests {
     #[derive(Component)]
     struct Age(u32);
 
+    struct DropCounter(&'static AtomicUsize);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
     #[test]
     fn apply() {
         struct Spawn<B: Bundle>(B);
 
         impl<B: Bundle> Command for Spawn<B> {
-            fn apply(self, world: &mut World) {
+            fn apply(self, world: &mut World) -> CommandResult {
                 world.spawn(self.0);
+
+                Ok(())
             }
         }
 
@@ -259,6 +347,241 @@ mod tests {
         assert_eq!(*age, u32::MAX);
     }
 
+    #[test]
+    fn apply_multiple() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+
+        for age in 0..3 {
+            commands.push_fn(move |world: &mut World| {
+                world.spawn(Age(age));
+            });
+        }
+
+        assert_eq!(commands.len(), 3);
+
+        commands.apply(&mut world);
+
+        assert!(commands.is_empty());
+        assert_eq!(world.len(), 3);
+    }
+
+    #[test]
+    fn entity_queue_operations() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let entity = world.spawn(Name("Alexandra")).id();
+
+        EntityQueue::new(entity, &mut commands)
+            .insert(Age(1))
+            .insert_if_new(Age(2))
+            .remove::<Name>();
+        EntityQueue::new(entity, &mut commands)
+            .entry::<Age>()
+            .and_modify(|age| age.0 += 1)
+            .or_insert(Age(0));
+        commands.apply(&mut world);
+
+        let alexandra = world.entity(entity).unwrap();
+
+        assert!(!alexandra.contains::<Name>());
+        assert_eq!(alexandra.get::<Age>().unwrap().0, 2);
+
+        EntityQueue::new(entity, &mut commands)
+            .insert(Name("Alexandra"))
+            .retain::<Name>();
+        commands.apply(&mut world);
+
+        assert!(!world.entity(entity).unwrap().contains::<Age>());
+
+        EntityQueue::new(entity, &mut commands).clear();
+        commands.apply(&mut world);
+
+        assert!(!world.entity(entity).unwrap().contains::<Name>());
+    }
+
+    #[test]
+    fn batches() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let entities: Vec<_> = world.spawn_iter((0..3).map(Age)).collect();
+        let mut queue = commands.as_world_queue(&world);
+
+        queue.spawn_batch((3..5).map(Age));
+        queue.insert_batch(
+            entities
+                .clone()
+                .into_iter()
+                .map(|entity| (entity, Name("Alexandra"))),
+        );
+        commands.apply(&mut world);
+
+        assert_eq!(world.len(), 5);
+        assert_eq!(world.query::<&Name>().unwrap().len(), 3);
+
+        for (i, entity) in entities.into_iter().enumerate() {
+            assert_eq!(
+                world.entity(entity).unwrap().get::<Age>().unwrap().0,
+                i as u32
+            );
+        }
+    }
+
+    #[test]
+    fn collect_command_errors() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let entity = world.spawn(Age(0)).id();
+
+        world.set_command_error_handler(CommandErrorHandler::Collect);
+
+        for _ in 0..2 {
+            commands.push_fn(move |world: &mut World| {
+                world.despawn(entity)?;
+
+                Ok(())
+            });
+        }
+
+        commands.apply(&mut world);
+
+        let errors = world.take_command_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].downcast_ref::<EntityNotFound>().is_some());
+        assert!(world.take_command_errors().is_empty());
+    }
+
+    #[test]
+    #[should_panic = "entity not found"]
+    fn command_errors_panic_by_default() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let entity = world.spawn(Age(0)).id();
+
+        EntityQueue::new(entity, &mut commands).despawn();
+        EntityQueue::new(entity, &mut commands).despawn();
+        commands.apply(&mut world);
+    }
+
+    #[test]
+    fn append() {
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let mut other = Commands::new();
+
+        commands.push_fn(|world: &mut World| {
+            world.spawn(Age(0));
+        });
+        other.push_fn(|world: &mut World| {
+            world.spawn((Name("Alexandra"), Age(1)));
+        });
+        other.push(|world: &mut World| {
+            world.spawn(Age(2));
+        });
+        commands.append(&mut other);
+
+        assert!(other.is_empty());
+        assert_eq!(commands.len(), 3);
+
+        commands.apply(&mut world);
+
+        let mut ages: Vec<_> =
+            world.query::<&Age>().unwrap().iter().map(|age| age.0).collect();
+
+        ages.sort();
+
+        assert_eq!(ages, [0, 1, 2]);
+        assert_eq!(world.query::<&Name>().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn apply_in_order() {
+        use crate::system::{In, IntoSystem};
+
+        #[derive(Resource, Default)]
+        struct Log(Vec<&'static str>);
+
+        fn log(name: &'static str) -> impl FnOnce(&mut World) {
+            move |world| world.resource_mut::<Log>().unwrap().0.push(name)
+        }
+
+        fn queue(mut first: WorldQueue, mut second: WorldQueue) {
+            // entity commands are applied after the system inputs
+            second.push_fn(|world: &mut World| {
+                assert_eq!(world.len(), 1);
+                log("second")(world);
+            });
+            first.push_fn(log("first"));
+        }
+
+        // a world queue reads the whole world, so entity commands are queued
+        // by a piped system
+        fn despawn(
+            In(()): In<()>,
+            mut query: Query<(EntityCommands, With<Age>)>,
+        ) {
+            for (commands, ()) in query.iter_mut() {
+                commands.despawn();
+            }
+        }
+
+        let mut world = World::new();
+        let mut commands = Commands::new();
+        let mut other = Commands::new();
+
+        world.create(Log::default());
+        world.spawn(Age(0));
+        other.push_fn(log("b"));
+        commands.push_fn(log("a"));
+        commands.append(&mut other);
+        other.push_fn(log("c"));
+        commands.append(&mut other);
+        commands.apply(&mut world);
+        world.run_system(queue.pipe(despawn)).unwrap();
+
+        assert_eq!(
+            world.resource::<Log>().unwrap().0,
+            ["a", "b", "c", "first", "second"],
+        );
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single_threaded"))]
+    fn commands_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Commands>();
+    }
+
+    #[test]
+    fn clear_drops_commands() {
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut world = World::new();
+        let mut commands = Commands::with_capacity(2, 64);
+
+        for _ in 0..2 {
+            let name = Name("Alexandra");
+            let guard = DropCounter(&DROPPED);
+
+            commands.push_fn(move |world: &mut World| {
+                _ = &guard;
+                world.spawn(name);
+            });
+        }
+
+        commands.clear();
+
+        assert!(commands.is_empty());
+        assert_eq!(DROPPED.load(atomic::Ordering::Relaxed), 2);
+
+        commands.apply(&mut world);
+
+        assert!(world.is_empty());
+    }
+
     #[test]
     fn queue_drops_all_commands() {
         struct HasToDrop;
@@ -266,7 +589,9 @@ mod tests {
         static HAS_DROPPED: AtomicBool = AtomicBool::new(false);
 
         impl Command for HasToDrop {
-            fn apply(self, _world: &mut World) {}
+            fn apply(self, _world: &mut World) -> CommandResult {
+                Ok(())
+            }
         }
 
         impl Drop for HasToDrop {
@@ -282,4 +607,63 @@ mod tests {
 
         assert!(HAS_DROPPED.load(atomic::Ordering::Relaxed));
     }
+
+    #[test]
+    fn entity_commands_in_query() {
+        fn age(mut query: Query<(&mut Age, EntityCommands)>) {
+            for (age, commands) in query.iter_mut() {
+                age.0 += 1;
+
+                if age.0 > 100 {
+                    commands.remove::<Age>();
+                    commands.insert(Name("ancestor"));
+                }
+            }
+        }
+
+        let mut world = World::new();
+        let old = world.spawn(Age(100)).id();
+        let young = world.spawn(Age(3)).id();
+
+        world.run_system(age).unwrap();
+
+        let old = world.entity(old).unwrap();
+
+        assert!(!old.contains::<Age>());
+        assert_eq!(old.get::<Name>().unwrap().0, "ancestor");
+        assert_eq!(world.entity(young).unwrap().get::<Age>().unwrap().0, 4);
+    }
+
+    #[test]
+    fn reuse_after_panicking_apply() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut world = World::new();
+        let entity = world.spawn(()).id();
+        let mut commands = Commands::new();
+        let name = String::from("left behind");
+
+        for _ in 0..2 {
+            commands.push_fn(move |world: &mut World| {
+                world.despawn(entity).map_err(Into::into)
+            });
+        }
+
+        commands.push_fn(move |_: &mut World| drop(name));
+
+        // the second despawn fails, which panics by default
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            commands.apply(&mut world);
+        }));
+
+        assert!(result.is_err());
+        assert!(commands.is_empty());
+
+        let values = vec![1_u64, 2, 3];
+
+        commands.push_fn(move |_: &mut World| {
+            assert_eq!(values, [1, 2, 3]);
+        });
+        commands.apply(&mut world);
+    }
 }
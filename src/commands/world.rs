@@ -1,9 +1,12 @@
-use super::{Commands, EntityQueue};
+use std::panic::Location;
+
+use super::{Command, CommandResult, Commands, EntityQueue, IntoCommandResult};
 use crate::access::{Level, WorldAccess};
 use crate::component::Bundle;
 use crate::entity::{Entities, EntityId, EntityNotFound};
+use crate::marker::MaybeSend;
 use crate::prelude::WorldPtr;
-use crate::system::{ReadOnlySystemInput, SystemInput};
+use crate::system::{ReadOnlySystemInput, SystemId, SystemInput};
 use crate::world::World;
 
 /// [`Commands`] with a world reference to queue commands with a world-like
@@ -40,20 +43,142 @@ impl<'w, 's> WorldQueue<'w, 's> {
         }
     }
 
+    /// Pushes a command to the queue.
+    pub fn push(&mut self, command: impl Command) {
+        self.commands.push(command);
+    }
+
+    /// Pushes a function command to the queue.
+    ///
+    /// See [`Commands::push_fn`].
+    pub fn push_fn<R: IntoCommandResult>(
+        &mut self,
+        f: impl FnOnce(&mut World) -> R + MaybeSend + 'static,
+    ) {
+        self.commands.push_fn(f);
+    }
+
     /// Queues spawning a new entity with its components.
+    #[track_caller]
     pub fn spawn(&mut self, bundle: impl Bundle) -> EntityQueue<'_> {
         let entity = self.entities.reserve();
+        let caller = Location::caller();
 
         self.commands.push_fn(move |world| unsafe {
-            world.spawn_at(entity, bundle);
+            world.spawn_at(entity, bundle, caller);
         });
 
         EntityQueue::new(entity, self.commands)
     }
 
+    /// Queues spawning an entity reserved with
+    /// [`World::reserve_entity`] with its components.
+    ///
+    /// The command fails with [`EntityNotFound`] if the entity wasn't reserved
+    /// or was already spawned when it is applied.
+    #[track_caller]
+    pub fn spawn_reserved(
+        &mut self,
+        entity: EntityId,
+        bundle: impl Bundle,
+    ) -> EntityQueue<'_> {
+        let caller = Location::caller();
+
+        self.commands.push_fn(move |world: &mut World| -> CommandResult {
+            world.entities.flush();
+
+            if !world.entities.is_unspawned(entity) {
+                return Err(EntityNotFound(entity).into());
+            }
+
+            // SAFETY: the entity is allocated but wasn't spawned
+            unsafe { world.spawn_at(entity, bundle, caller) };
+
+            Ok(())
+        });
+
+        EntityQueue::new(entity, self.commands)
+    }
+
+    /// Queues spawning the prefab registered with the key in the
+    /// [`Prefabs`](crate::world::Prefabs) of the world.
+    ///
+    /// Components inserted with the returned queue override those of the
+    /// prefab. The command fails with
+    /// [`PrefabNotFound`](crate::world::PrefabNotFound) if the prefab isn't
+    /// registered when it is applied.
+    #[track_caller]
+    pub fn spawn_prefab(&mut self, key: impl Into<String>) -> EntityQueue<'_> {
+        let entity = self.entities.reserve();
+        let key = key.into();
+        let caller = Location::caller();
+
+        self.commands.push_fn(move |world: &mut World| -> CommandResult {
+            // SAFETY: the entity was reserved above
+            unsafe { world.spawn_prefab_at(entity, &key, caller) }?;
+
+            Ok(())
+        });
+
+        EntityQueue::new(entity, self.commands)
+    }
+
+    /// Queues spawning an entity for each bundle in an iterator.
+    ///
+    /// Queued as a single command that uses [`World::spawn_iter`].
+    #[track_caller]
+    pub fn spawn_batch<I>(&mut self, bundles: I)
+    where
+        I: IntoIterator<Item: Bundle> + MaybeSend + 'static,
+    {
+        let caller = Location::caller();
+
+        self.commands.push_fn(move |world: &mut World| {
+            world.spawn_iter_with_caller(bundles, caller);
+        });
+    }
+
+    /// Queues inserting a bundle into each entity in an iterator.
+    ///
+    /// Queued as a single command that uses [`World::insert_iter`]. The
+    /// command fails if any of the entities doesn't exist.
+    #[track_caller]
+    pub fn insert_batch<I, B>(&mut self, entities: I)
+    where
+        I: IntoIterator<Item = (EntityId, B)> + MaybeSend + 'static,
+        B: Bundle,
+    {
+        let caller = Location::caller();
+
+        self.commands.push_fn(move |world: &mut World| -> CommandResult {
+            world.insert_iter_with_caller(entities, caller)?;
+
+            Ok(())
+        });
+    }
+
+    /// Queues running a system registered with [`World::register_system`].
+    ///
+    /// The output of the system is discarded. The command fails if the system
+    /// can't be run.
+    pub fn run_system<O: 'static>(&mut self, id: SystemId<O>) {
+        self.commands.push_fn(move |world: &mut World| -> CommandResult {
+            world.run_system_by_id(id)?;
+
+            Ok(())
+        });
+    }
+
     /// Queues despawning the entity with the given id.
+    #[track_caller]
     pub fn despawn(&mut self, entity: EntityId) -> Result<(), EntityNotFound> {
-        self.entity(entity).map(EntityQueue::despawn)
+        if self.entities.contains(entity) {
+            EntityQueue::new(entity, self.commands).despawn();
+
+            Ok(())
+        } else {
+            Err(EntityNotFound(entity))
+        }
     }
 }
 
@@ -1,3 +1,7 @@
+use std::mem::ManuallyDrop;
+use std::panic::Location;
+use std::ptr::NonNull;
+
 pub use worldlines_macros::Bundle;
 
 use super::{
@@ -6,18 +10,28 @@ use super::{
     ComponentSet,
     ComponentVTable,
     Components,
+    HookContext,
+    Requirement,
 };
 use crate::commands::EntityQueue;
 use crate::entity::EntityAddr;
+use crate::marker::MaybeSend;
 
 /// A bundle of components to add to an entity.
 ///
+/// # Deriving
+///
+/// `Bundle` can be derived for structs whose fields are all bundles. Fields
+/// that are bundles themselves are flattened into the components of the
+/// struct. Fields with `#[bundle(ignore)]` aren't inserted and are dropped
+/// instead.
+///
 /// # Safety
 ///
 /// The output of [`Bundle::components`] must always set the same access.
 /// [`Bundle::write`] must call [`ComponentWriter::write`] on every component
 /// declared in [`Bundle::write`].
-pub unsafe trait Bundle: Send + 'static {
+pub unsafe trait Bundle: MaybeSend + 'static {
     /// Adds the components of the bundle to the component set.
     fn components(components: &mut ComponentSet);
 
@@ -31,6 +45,9 @@ pub struct ComponentWriter<'w, 's> {
     queue: EntityQueue<'s>,
     components: &'w mut Components,
     addr: EntityAddr,
+    /// Components of the entity whose values are being replaced.
+    replaced: ComponentSet,
+    caller: &'static Location<'static>,
 }
 
 unsafe impl<C: Component> Bundle for C {
@@ -48,28 +65,79 @@ impl<'w, 's> ComponentWriter<'w, 's> {
         queue: EntityQueue<'s>,
         components: &'w mut Components,
         addr: EntityAddr,
+        replaced: ComponentSet,
+        caller: &'static Location<'static>,
     ) -> Self {
-        Self { queue, components, addr }
+        Self { queue, components, addr, replaced, caller }
+    }
+
+    fn hook_context(&self, info: ComponentInfo) -> HookContext {
+        let replace = self.replaced.contains(info.id());
+
+        HookContext::new(self.queue.id(), info.id(), replace, self.caller)
     }
 
-    /// Writes a component to storage.
+    /// Writes a component to storage, dropping the previous value if the
+    /// component is being replaced.
     ///
     /// # Panics
     ///
     /// Panics if the entity doesn't contain the component.
     pub fn write<C: Component>(&mut self, component: C) {
         let info = ComponentInfo::of::<C>();
+        let mut component = ManuallyDrop::new(component);
+        let ptr = NonNull::from(&mut *component).cast();
 
+        // SAFETY: the pointer is to a `C`, which is moved into the table and
+        // isn't dropped here because of `ManuallyDrop`. Replaced components
+        // are initialized
         unsafe {
             let table = self.components.get_unchecked_mut(self.addr.table);
 
-            table.write(self.addr.row, info.id(), component).expect(
+            if self.replaced.contains(info.id()) {
+                table.replace_component(self.addr.row, info.id(), ptr)
+            } else {
+                table.init_component(self.addr.row, info.id(), ptr)
+            }
+            .expect(
                 "attempted to write a bundle component to an entity that \
                  doesn't contain the component",
             )
         };
 
-        self.queue.push_fn(|mut entity| C::after_insert(entity.as_mut()));
+        let ctx = self.hook_context(info);
+
+        self.queue.push_insert_hook(C::after_insert, ctx);
+    }
+
+    /// Writes the default values of the components required by `B` that it
+    /// doesn't contain.
+    pub(crate) fn write_required<B: Bundle>(&mut self) {
+        for i in 0..self.components.required::<B>().len() {
+            let requirement = self.components.required::<B>()[i];
+
+            self.write_requirement(requirement);
+        }
+    }
+
+    /// Writes the default values of required components.
+    pub(crate) fn write_requirements(&mut self, requirements: &[Requirement]) {
+        for &requirement in requirements {
+            self.write_requirement(requirement);
+        }
+    }
+
+    fn write_requirement(&mut self, requirement: Requirement) {
+        let hook = requirement.info.after_insert();
+        let ctx = self.hook_context(requirement.info);
+
+        unsafe {
+            let table = self.components.get_unchecked_mut(self.addr.table);
+
+            (requirement.init)(table, self.addr.row);
+        }
+
+        self.queue.push_insert_hook(hook, ctx);
     }
 }
 
@@ -107,4 +175,39 @@ mod tests {
         assert_eq!(entity.get::<Name>().unwrap().0, "Alexandra");
         assert_eq!(entity.get::<Age>().unwrap().0, u32::MAX);
     }
+
+    #[test]
+    fn nested_bundle() {
+        #[derive(Component)]
+        struct Employer(&'static str);
+
+        #[derive(Bundle)]
+        struct EmployeeBundle {
+            person: PersonBundle,
+            employer: Employer,
+            #[bundle(ignore)]
+            _note: String,
+        }
+
+        let mut components = ComponentSet::new();
+
+        EmployeeBundle::components(&mut components);
+
+        assert_eq!(components.len(), 4);
+
+        let mut world = World::new();
+        let entity = world.spawn(EmployeeBundle {
+            person: PersonBundle {
+                person: Person,
+                name: Name("Alexandra"),
+                age: Age(0),
+            },
+            employer: Employer("the world"),
+            _note: String::from("not a component"),
+        });
+
+        assert!(entity.contains::<Person>());
+        assert_eq!(entity.get::<Name>().unwrap().0, "Alexandra");
+        assert_eq!(entity.get::<Employer>().unwrap().0, "the world");
+    }
 }
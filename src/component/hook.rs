@@ -0,0 +1,89 @@
+use std::panic::Location;
+
+use super::ComponentId;
+use crate::entity::{EntityId, EntityMut};
+
+/// A function called when a component is added to or removed from an entity.
+///
+/// See [`Component::after_insert`](super::Component::after_insert).
+pub type ComponentHook = fn(EntityMut<'_>, HookContext);
+
+/// Which hooks of a component are overridden.
+///
+/// See [`Component::HOOKS`](super::Component::HOOKS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComponentHooks {
+    /// Whether [`Component::after_insert`](super::Component::after_insert) is
+    /// overridden.
+    pub after_insert: bool,
+    /// Whether [`Component::on_replace`](super::Component::on_replace) is
+    /// overridden.
+    pub on_replace: bool,
+    /// Whether [`Component::before_remove`](super::Component::before_remove)
+    /// is overridden.
+    pub before_remove: bool,
+    /// Whether [`Component::after_remove`](super::Component::after_remove) is
+    /// overridden.
+    pub after_remove: bool,
+}
+
+/// Information about the change that triggered a [`ComponentHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookContext {
+    entity: EntityId,
+    component: ComponentId,
+    replace: bool,
+    caller: &'static Location<'static>,
+}
+
+impl ComponentHooks {
+    /// No overridden hooks.
+    pub const NONE: Self = Self {
+        after_insert: false,
+        on_replace: false,
+        before_remove: false,
+        after_remove: false,
+    };
+
+    /// Returns `true` if any hook is overridden.
+    pub const fn any(self) -> bool {
+        self.after_insert
+            || self.on_replace
+            || self.before_remove
+            || self.after_remove
+    }
+}
+
+impl HookContext {
+    pub(crate) const fn new(
+        entity: EntityId,
+        component: ComponentId,
+        replace: bool,
+        caller: &'static Location<'static>,
+    ) -> Self {
+        Self { entity, component, replace, caller }
+    }
+
+    /// Returns the entity that was changed.
+    pub const fn entity(&self) -> EntityId {
+        self.entity
+    }
+
+    /// Returns the id of the component that triggered the hook.
+    pub const fn component(&self) -> ComponentId {
+        self.component
+    }
+
+    /// Returns `true` if the change replaces an existing value of the
+    /// component, rather than adding or removing it.
+    pub const fn is_replace(&self) -> bool {
+        self.replace
+    }
+
+    /// Returns the location of the call that caused the change.
+    ///
+    /// For queued changes, this is where the command was queued.
+    pub const fn caller(&self) -> &'static Location<'static> {
+        self.caller
+    }
+}
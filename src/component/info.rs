@@ -2,15 +2,24 @@ use std::alloc::Layout;
 use std::any::{type_name, TypeId};
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ptr::NonNull;
 use std::sync::atomic::{self, AtomicUsize};
 use std::sync::{LazyLock, OnceLock};
 use std::{fmt, ptr};
 
 use dashmap::DashMap;
 
-use super::Component;
-use crate::entity::EntityMut;
-use crate::storage::{SparseIndex, UsizeHasher};
+use super::{
+    Component,
+    ComponentHook,
+    ComponentHooks,
+    Requirements,
+    StableIdError,
+};
+use crate::entity::EntityMapper;
+#[cfg(feature = "reflect")]
+use crate::reflect::Reflect;
+use crate::storage::{SparseIndex, TypeIdHasher, UsizeHasher};
 
 /// The sparse index for components.
 #[repr(transparent)]
@@ -29,7 +38,8 @@ pub struct ComponentInfo {
 /// # Safety
 ///
 /// [`ComponentVTable::drop`] must drop the component represented by this
-/// vtable.
+/// vtable. [`ComponentVTable::column_align`] must be a power of two that is
+/// at least the alignment of the component.
 pub unsafe trait ComponentVTable: Send + Sync + 'static {
     /// Returns the id of the component.
     fn id(&self) -> ComponentId;
@@ -40,24 +50,63 @@ pub unsafe trait ComponentVTable: Send + Sync + 'static {
     /// Returns the [type name](std::any::type_name) of the component.
     fn type_name(&self) -> &'static str;
 
+    /// Returns the [`Component::stable_id`] of the component.
+    fn stable_id(&self) -> &'static str;
+
     /// Returns the layout of the component in memory.
     fn layout(&self) -> Layout;
 
+    /// Returns the alignment of the storage of the component.
+    ///
+    /// Must be a power of two that is at least the alignment of
+    /// [`ComponentVTable::layout`]. See [`Component::COLUMN_ALIGN`].
+    fn column_align(&self) -> usize;
+
     /// Returns a function that [drops the component
     /// in-place](std::ptr::drop_in_place).
     fn drop(&self) -> unsafe fn(*mut u8);
 
+    /// Returns [`Component::HOOKS`].
+    fn hooks(&self) -> ComponentHooks;
+
     /// Returns the [`Component::after_insert`] function.
-    fn after_insert(&self) -> fn(EntityMut<'_>);
+    fn after_insert(&self) -> ComponentHook;
+
+    /// Returns the [`Component::on_replace`] function.
+    fn on_replace(&self) -> ComponentHook;
 
     /// Returns the [`Component::before_remove`] function.
-    fn before_remove(&self) -> fn(EntityMut<'_>);
+    fn before_remove(&self) -> ComponentHook;
+
+    /// Returns the [`Component::after_remove`] function.
+    fn after_remove(&self) -> ComponentHook;
+
+    /// Calls [`Component::required`].
+    fn required(&self, requirements: &mut Requirements);
+
+    /// Returns a function that calls [`Component::map_entities`] on a pointer
+    /// to the component.
+    fn map_entities(&self) -> unsafe fn(NonNull<u8>, &mut dyn EntityMapper);
+
+    /// Returns a function that calls [`Component::as_reflect`] on a pointer to
+    /// the component.
+    #[cfg(feature = "reflect")]
+    fn as_reflect(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>>;
+
+    /// Returns a function that calls [`Component::as_reflect_mut`] on a
+    /// pointer to the component.
+    #[cfg(feature = "reflect")]
+    fn as_reflect_mut(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>>;
 }
 
 /// A static container for allocating [`ComponentId`]'s.
 pub struct ComponentIdCell<C: Component> {
     inner: OnceLock<ComponentId>,
-    _marker: PhantomData<C>,
+    _marker: PhantomData<fn() -> C>,
 }
 
 /// Registry of [`ComponentInfo`] by their [id](ComponentId).
@@ -67,24 +116,64 @@ pub struct ComponentIdCell<C: Component> {
 static REGISTRY: LazyLock<DashMap<ComponentId, ComponentInfo, UsizeHasher>> =
     LazyLock::new(Default::default);
 
+/// Registry of [`ComponentId`]'s by their [stable id](Component::stable_id).
+///
+/// `None` if multiple components have the same stable id.
+static STABLE_IDS: LazyLock<DashMap<&'static str, Option<ComponentId>>> =
+    LazyLock::new(Default::default);
+
 impl ComponentId {
     /// Returns the id of the given component.
     pub fn of<C: Component>() -> Self {
         C::id()
     }
 
-    /// Used internally by [`Component::id`].
-    pub(super) fn next() -> Self {
+    /// Returns the id of a component type, initializing it if necessary.
+    ///
+    /// Used to implement [`Component::id`] for generic types, which can't use
+    /// a static [`ComponentIdCell`]. Slower, as the id is looked up in a map.
+    pub fn of_type<C: Component>() -> Self {
+        static IDS: LazyLock<DashMap<TypeId, ComponentId, TypeIdHasher>> =
+            LazyLock::new(Default::default);
+
+        *IDS.entry(TypeId::of::<C>()).or_insert_with(Self::register::<C>)
+    }
+
+    /// Returns the id of the registered component with a
+    /// [stable id](Component::stable_id).
+    ///
+    /// Components are registered when their id is first requested, so the
+    /// component must have been used or passed to [`ComponentId::of`]
+    /// beforehand. Returns an error if no registered component or multiple
+    /// registered components have the stable id.
+    pub fn of_stable_id(stable_id: &str) -> Result<Self, StableIdError> {
+        match STABLE_IDS.get(stable_id).as_deref() {
+            Some(&Some(id)) => Ok(id),
+            Some(None) => Err(StableIdError::Ambiguous(stable_id.to_owned())),
+            None => Err(StableIdError::NotFound(stable_id.to_owned())),
+        }
+    }
+
+    /// Allocates an id for a component and adds it to the registries.
+    fn register<C: Component>() -> Self {
         static COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        Self(COUNTER.fetch_add(1, atomic::Ordering::Relaxed))
+        let id = Self(COUNTER.fetch_add(1, atomic::Ordering::Relaxed));
+
+        REGISTRY.insert(id, ComponentInfo::of::<C>());
+        STABLE_IDS
+            .entry(C::stable_id())
+            .and_modify(|other| *other = None)
+            .or_insert(Some(id));
+
+        id
     }
 }
 
 impl ComponentInfo {
     /// Returns the component info of the provided component.
     pub const fn of<C: Component>() -> Self {
-        Self { inner: &PhantomData::<C> }
+        Self { inner: &PhantomData::<fn() -> C> }
     }
 
     /// Returns the info of the component with the given id.
@@ -95,6 +184,20 @@ impl ComponentInfo {
         // this invariant.
         unsafe { *REGISTRY.get(&id).unwrap_unchecked() }
     }
+
+    /// Returns the info of all registered components, in order of their id.
+    ///
+    /// Components are registered when their id is first requested, so this
+    /// only contains components that were used or passed to
+    /// [`ComponentId::of`] beforehand. Meant for tooling, such as editors and
+    /// code generators, that enumerates the components of the program.
+    pub fn iter_registered() -> impl Iterator<Item = Self> {
+        let mut infos: Vec<_> =
+            REGISTRY.iter().map(|entry| *entry.value()).collect();
+
+        infos.sort_unstable_by_key(|info| info.id());
+        infos.into_iter()
+    }
 }
 
 impl<C: Component> ComponentIdCell<C> {
@@ -107,18 +210,18 @@ impl<C: Component> ComponentIdCell<C> {
 
     /// Returns the stored component id, initializing it if necessary.
     pub fn get_or_init(&self) -> ComponentId {
-        *self.inner.get_or_init(|| {
-            let id = ComponentId::next();
-
-            REGISTRY.insert(id, ComponentInfo::of::<C>());
-
-            id
-        })
+        *self.inner.get_or_init(ComponentId::register::<C>)
     }
 }
 
 // ---
 
+impl<C: Component> Default for ComponentIdCell<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// # Safety
 ///
 /// Delegates to another implementation of [`ComponentVTable`].
@@ -135,21 +238,63 @@ unsafe impl ComponentVTable for ComponentInfo {
         self.inner.type_name()
     }
 
+    fn stable_id(&self) -> &'static str {
+        self.inner.stable_id()
+    }
+
     fn layout(&self) -> Layout {
         self.inner.layout()
     }
 
+    fn column_align(&self) -> usize {
+        self.inner.column_align()
+    }
+
     fn drop(&self) -> unsafe fn(*mut u8) {
         self.inner.drop()
     }
 
-    fn after_insert(&self) -> fn(EntityMut<'_>) {
+    fn hooks(&self) -> ComponentHooks {
+        self.inner.hooks()
+    }
+
+    fn after_insert(&self) -> ComponentHook {
         self.inner.after_insert()
     }
 
-    fn before_remove(&self) -> fn(EntityMut<'_>) {
+    fn on_replace(&self) -> ComponentHook {
+        self.inner.on_replace()
+    }
+
+    fn before_remove(&self) -> ComponentHook {
         self.inner.before_remove()
     }
+
+    fn after_remove(&self) -> ComponentHook {
+        self.inner.after_remove()
+    }
+
+    fn required(&self, requirements: &mut Requirements) {
+        self.inner.required(requirements);
+    }
+
+    fn map_entities(&self) -> unsafe fn(NonNull<u8>, &mut dyn EntityMapper) {
+        self.inner.map_entities()
+    }
+
+    #[cfg(feature = "reflect")]
+    fn as_reflect(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>> {
+        self.inner.as_reflect()
+    }
+
+    #[cfg(feature = "reflect")]
+    fn as_reflect_mut(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>> {
+        self.inner.as_reflect_mut()
+    }
 }
 
 impl SparseIndex for ComponentInfo {
@@ -211,8 +356,9 @@ impl PartialEq<ComponentInfo> for ComponentId {
 
 /// # Safety
 ///
-/// [`ComponentVTable::drop`] is a valid drop function pointer.
-unsafe impl<C: Component> ComponentVTable for PhantomData<C> {
+/// [`ComponentVTable::drop`] is a valid drop function pointer and
+/// [`ComponentVTable::column_align`] is checked to be a power of two.
+unsafe impl<C: Component> ComponentVTable for PhantomData<fn() -> C> {
     fn id(&self) -> ComponentId {
         C::id()
     }
@@ -225,21 +371,76 @@ unsafe impl<C: Component> ComponentVTable for PhantomData<C> {
         type_name::<C>()
     }
 
+    fn stable_id(&self) -> &'static str {
+        C::stable_id()
+    }
+
     fn layout(&self) -> Layout {
         Layout::new::<C>()
     }
 
+    fn column_align(&self) -> usize {
+        const {
+            assert!(
+                C::COLUMN_ALIGN.is_power_of_two(),
+                "`Component::COLUMN_ALIGN` must be a power of two",
+            );
+        }
+
+        C::COLUMN_ALIGN.max(align_of::<C>())
+    }
+
     fn drop(&self) -> unsafe fn(*mut u8) {
         |ptr| unsafe { ptr::drop_in_place(ptr.cast::<C>()) }
     }
 
-    fn after_insert(&self) -> fn(EntityMut<'_>) {
+    fn hooks(&self) -> ComponentHooks {
+        C::HOOKS
+    }
+
+    fn after_insert(&self) -> ComponentHook {
         C::after_insert
     }
 
-    fn before_remove(&self) -> fn(EntityMut<'_>) {
+    fn on_replace(&self) -> ComponentHook {
+        C::on_replace
+    }
+
+    fn before_remove(&self) -> ComponentHook {
         C::before_remove
     }
+
+    fn after_remove(&self) -> ComponentHook {
+        C::after_remove
+    }
+
+    fn required(&self, requirements: &mut Requirements) {
+        C::required(requirements);
+    }
+
+    fn map_entities(&self) -> unsafe fn(NonNull<u8>, &mut dyn EntityMapper) {
+        |ptr, mapper| unsafe { ptr.cast::<C>().as_mut() }.map_entities(mapper)
+    }
+
+    #[cfg(feature = "reflect")]
+    fn as_reflect(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>> {
+        |ptr| {
+            unsafe { ptr.cast::<C>().as_ref() }.as_reflect().map(NonNull::from)
+        }
+    }
+
+    #[cfg(feature = "reflect")]
+    fn as_reflect_mut(
+        &self,
+    ) -> unsafe fn(NonNull<u8>) -> Option<NonNull<dyn Reflect>> {
+        |ptr| {
+            unsafe { ptr.cast::<C>().as_mut() }
+                .as_reflect_mut()
+                .map(NonNull::from)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -263,6 +464,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn iter_registered() {
+        #[derive(Component)]
+        #[component(after_insert = |_, _| {})]
+        struct Hooked(#[expect(unused)] u64);
+
+        let info = ComponentInfo::of_id(ComponentId::of::<Hooked>());
+        let registered: Vec<_> = ComponentInfo::iter_registered().collect();
+
+        assert!(registered.contains(&info));
+        assert!(registered.is_sorted_by_key(|info| info.id()));
+        assert_eq!(info.layout(), Layout::new::<u64>());
+        assert_eq!(
+            info.hooks(),
+            ComponentHooks { after_insert: true, ..ComponentHooks::NONE },
+        );
+        assert!(!ComponentInfo::of::<A>().hooks().any());
+    }
+
     #[test]
     fn id_eq() {
         assert_eq!(ComponentInfo::of::<A>(), ComponentInfo::of::<A>());
@@ -6,14 +6,25 @@ use thiserror::Error;
 pub use worldlines_macros::Component;
 
 pub use self::bundle::*;
+pub use self::hook::*;
 pub use self::info::*;
+pub use self::ptr::*;
+pub use self::required::*;
 pub use self::set::*;
+pub use self::stable::*;
 pub(crate) use self::storage::*;
-use crate::entity::{EntityId, EntityMut};
+use crate::entity::{EntityId, EntityMapper, EntityMut};
+use crate::marker::MaybeSendSync;
+#[cfg(feature = "reflect")]
+use crate::reflect::Reflect;
 
 mod bundle;
+mod hook;
 mod info;
+mod ptr;
+mod required;
 mod set;
+mod stable;
 mod storage;
 mod tuple_impl;
 
@@ -26,15 +37,23 @@ mod tuple_impl;
 /// component hooks of fields components.
 ///
 /// The derive macro accepts the attribute `#[component(...)]`. It can be used
-/// to specify [`Component::after_insert`] and [`Component::before_remove`] with
-/// `#[component(after_insert = after_insert_fn, before_remove =
-/// before_remove_fn)]`.
+/// to specify [component hooks](ComponentHook) with `#[component(after_insert
+/// = after_insert_fn, before_remove = before_remove_fn)]` (as well as
+/// `on_replace` and `after_remove`), and [required
+/// components](Component::required) with `#[component(requires(A, B))]`.
+/// `#[component(map_entities)]` implements [`Component::map_entities`] for
+/// components that implement [`MapEntities`](crate::entity::MapEntities). With
+/// the `reflect` feature, `#[component(reflect)]` implements
+/// [`Component::as_reflect`] for components that implement `Reflect`.
+/// `#[component(align = 64)]` sets [`Component::COLUMN_ALIGN`].
+/// `#[component(stable_id = "game::Health")]` sets [`Component::stable_id`].
 ///
 /// # Safety
 ///
 /// The implementation of [`Component::id`] must use a static
-/// [`ComponentIdCell`] to store the id. The implementation must only create a
-/// [`ComponentIdCell`] for `Self`.
+/// [`ComponentIdCell`] to store the id, or call [`ComponentId::of_type`] for
+/// generic types. The implementation must only create a [`ComponentIdCell`]
+/// for `Self`.
 ///
 /// ```
 /// # use worldlines::prelude::*;
@@ -49,19 +68,88 @@ mod tuple_impl;
 ///     }
 /// }
 /// ```
-pub unsafe trait Component: Send + Sync + 'static {
+pub unsafe trait Component: MaybeSendSync + 'static {
+    /// The minimum alignment of the storage of this component.
+    ///
+    /// Components of the same table are stored contiguously, without padding
+    /// between them, starting at an address aligned to the greater of this
+    /// and the alignment of the component. Can be set to the size of a cache
+    /// line or SIMD register for data that is processed a
+    /// [chunk](crate::query::Query::iter_chunks) at a time, without
+    /// changing the layout of the component itself.
+    ///
+    /// Must be a power of two.
+    const COLUMN_ALIGN: usize = 1;
+
+    /// The hooks that this component overrides, for tooling that lists
+    /// registered components with [`ComponentInfo::iter_registered`].
+    ///
+    /// Set by `#[derive(Component)]`. Overriding a hook can't be detected
+    /// otherwise, so manual implementations should set this as well.
+    const HOOKS: ComponentHooks = ComponentHooks::NONE;
+
     /// Returns the id of this component.
     fn id() -> ComponentId;
 
-    /// Called after this component is added to an entity that does not already
-    /// contain it, including when spawned.
+    /// Returns an identifier for this component that is the same between runs
+    /// and builds of the program, unlike [`Component::id`], which depends on
+    /// the order that components are first used in.
+    ///
+    /// Used to map saved component data to components with a
+    /// [`ComponentIdMap`]. Defaults to the [type name](std::any::type_name),
+    /// so it should be set explicitly for components whose path may change.
+    fn stable_id() -> &'static str {
+        type_name::<Self>()
+    }
+
+    /// Called after this component is added to an entity, including when
+    /// spawned.
+    ///
+    /// Also called after an insertion replaces an existing value, in which case
+    /// [`HookContext::is_replace`] returns `true`.
     #[expect(unused)]
-    fn after_insert(entity: EntityMut<'_>) {}
+    fn after_insert(entity: EntityMut<'_>, ctx: HookContext) {}
 
-    /// Called before this component is removed from and entity, including
+    /// Called before an insertion replaces the value of this component. The
+    /// entity still contains the previous value.
+    #[expect(unused)]
+    fn on_replace(entity: EntityMut<'_>, ctx: HookContext) {}
+
+    /// Called before this component is removed from an entity, including
     /// despawn.
     #[expect(unused)]
-    fn before_remove(entity: EntityMut<'_>) {}
+    fn before_remove(entity: EntityMut<'_>, ctx: HookContext) {}
+
+    /// Called after this component is removed from an entity.
+    ///
+    /// Not called when the entity is despawned, as it no longer exists.
+    #[expect(unused)]
+    fn after_remove(entity: EntityMut<'_>, ctx: HookContext) {}
+
+    /// Adds the components required by this component.
+    ///
+    /// When this component is inserted, required components that the entity
+    /// doesn't contain are inserted with their [`Default`] value.
+    #[expect(unused)]
+    fn required(requirements: &mut Requirements) {}
+
+    /// Maps the entity ids stored in this component.
+    ///
+    /// Does nothing by default.
+    #[expect(unused)]
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {}
+
+    /// Returns this component as [`Reflect`], if it supports reflection.
+    #[cfg(feature = "reflect")]
+    fn as_reflect(&self) -> Option<&dyn Reflect> {
+        None
+    }
+
+    /// Returns this component as [`Reflect`], if it supports reflection.
+    #[cfg(feature = "reflect")]
+    fn as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        None
+    }
 }
 
 /// Error when accessing a [`Component`] an entity does not contain.
@@ -78,6 +166,12 @@ impl ComponentNotFound {
 
         Self { entity, component }
     }
+
+    pub(crate) fn of_id(entity: EntityId, component: ComponentId) -> Self {
+        let component = ComponentInfo::of_id(component).type_name();
+
+        Self { entity, component }
+    }
 }
 
 #[cfg(test)]
@@ -86,17 +180,31 @@ mod tests {
     use crate::world::World;
 
     #[derive(Component)]
-    #[component(after_insert = |_| panic!("boom!"))]
+    #[component(after_insert = |_, _| panic!("boom!"))]
     struct Bomb;
 
     #[derive(Component)]
     #[component(before_remove = entity_go_boom)]
     struct DeadManSwitch;
 
-    fn entity_go_boom(entity: EntityMut<'_>) {
+    fn entity_go_boom(entity: EntityMut<'_>, _ctx: HookContext) {
         panic!("{:?} went boom!", entity.id());
     }
 
+    #[derive(Component)]
+    #[component(requires(Transform, Visibility))]
+    struct Sprite;
+
+    #[derive(Component, Default)]
+    #[component(requires(GlobalTransform))]
+    struct Transform(u32);
+
+    #[derive(Component, Default)]
+    struct GlobalTransform;
+
+    #[derive(Component, Default)]
+    struct Visibility;
+
     #[test]
     #[should_panic]
     fn derived_on_insert_works() {
@@ -112,4 +220,76 @@ mod tests {
 
         world.spawn(DeadManSwitch).despawn();
     }
+
+    #[test]
+    fn required_components_are_spawned() {
+        let mut world = World::new();
+        let entity = world.spawn(Sprite);
+
+        assert_eq!(entity.get::<Transform>().unwrap().0, 0);
+        assert!(entity.contains::<Visibility>());
+        assert!(entity.contains::<GlobalTransform>());
+
+        let entity = world.spawn((Sprite, Transform(3)));
+
+        assert_eq!(entity.get::<Transform>().unwrap().0, 3);
+        assert!(entity.contains::<Visibility>());
+    }
+
+    #[test]
+    fn required_components_are_inserted() {
+        let mut world = World::new();
+        let mut entity = world.spawn(Transform(3));
+
+        entity.insert(Sprite);
+
+        assert_eq!(entity.get::<Transform>().unwrap().0, 3);
+        assert!(entity.contains::<Visibility>());
+        assert!(entity.contains::<GlobalTransform>());
+    }
+
+    #[test]
+    fn hook_context() {
+        use std::cell::RefCell;
+
+        thread_local! {
+            static EVENTS: RefCell<Vec<(&'static str, bool, u32)>> =
+                const { RefCell::new(Vec::new()) };
+        }
+
+        fn record(hook: &'static str, ctx: HookContext) {
+            assert_eq!(ctx.component(), Score::id());
+
+            EVENTS.with_borrow_mut(|events| {
+                events.push((hook, ctx.is_replace(), ctx.caller().line()));
+            });
+        }
+
+        #[derive(Component)]
+        #[component(
+            after_insert = |_, ctx| record("after_insert", ctx),
+            on_replace = |_, ctx| record("on_replace", ctx),
+            before_remove = |_, ctx| record("before_remove", ctx),
+            after_remove = |_, ctx| record("after_remove", ctx),
+        )]
+        struct Score(#[expect(unused)] u32);
+
+        let mut world = World::new();
+        let line = line!();
+        let mut entity = world.spawn(Score(0));
+
+        entity.insert(Score(1));
+        _ = entity.remove::<Score>();
+
+        assert_eq!(
+            EVENTS.take(),
+            [
+                ("after_insert", false, line + 1),
+                ("on_replace", true, line + 3),
+                ("after_insert", true, line + 3),
+                ("before_remove", false, line + 4),
+                ("after_remove", false, line + 4),
+            ]
+        );
+    }
 }
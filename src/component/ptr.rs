@@ -0,0 +1,200 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+
+use super::Component;
+use crate::entity::{EntityAddr, EntityId};
+use crate::world::World;
+
+/// A checked handle to a component of an entity.
+///
+/// Created with [`World::component_ptr`]. Resolving the handle is cheaper
+/// than looking the component up again, and returns `None` if the entity was
+/// despawned or moved to another table (for example because a component was
+/// inserted or removed). Create a new handle in that case.
+pub struct ComponentPtr<C: Component> {
+    entity: EntityId,
+    addr: EntityAddr,
+    _marker: PhantomData<fn() -> C>,
+}
+
+/// A type-erased pointer to a value that is moved out of by its receiver.
+///
+/// Used to pass components whose type isn't known at compile time, as in
+/// [`EntityWorld::insert_by_id`]. The value isn't dropped with the pointer, so
+/// it is leaked if the receiver doesn't move out of it.
+///
+/// [`EntityWorld::insert_by_id`]: crate::entity::EntityWorld::insert_by_id
+pub struct OwningPtr<'a> {
+    ptr: NonNull<u8>,
+    _marker: PhantomData<&'a mut u8>,
+}
+
+impl<C: Component> ComponentPtr<C> {
+    pub(crate) const fn new(entity: EntityId, addr: EntityAddr) -> Self {
+        Self { entity, addr, _marker: PhantomData }
+    }
+
+    /// Returns the entity this component belongs to.
+    pub const fn entity(self) -> EntityId {
+        self.entity
+    }
+
+    /// Returns `true` if this handle still refers to the component.
+    pub fn is_valid(self, world: &World) -> bool {
+        world.contains(self.entity)
+            && world.entities.get(self.entity) == Some(self.addr)
+            // SAFETY: the entity is alive at the address, so the table exists
+            && unsafe { world.components.get_unchecked(self.addr.table) }
+                .components()
+                .contains(C::id())
+    }
+
+    /// Returns a reference to the component.
+    ///
+    /// Returns `None` if this handle is no longer valid.
+    pub fn get(self, world: &World) -> Option<&C> {
+        self.is_valid(world).then(|| unsafe {
+            // SAFETY: the table contains the entity and the component
+            world
+                .components
+                .get_unchecked(self.addr.table)
+                .get_unchecked(self.addr.row, C::id())
+                .cast()
+                .as_ref()
+        })
+    }
+
+    /// Returns a mutable reference to the component.
+    ///
+    /// Returns `None` if this handle is no longer valid.
+    pub fn get_mut(self, world: &mut World) -> Option<&mut C> {
+        self.is_valid(world).then(|| unsafe {
+            // SAFETY: the table contains the entity and the component
+            world
+                .components
+                .get_unchecked_mut(self.addr.table)
+                .get_unchecked_mut(self.addr.row, C::id())
+                .cast()
+                .as_mut()
+        })
+    }
+}
+
+impl<'a> OwningPtr<'a> {
+    /// Creates an owning pointer from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to an initialized, aligned value that is valid for
+    /// reads for `'a`. The value must not be used or dropped by its previous
+    /// owner afterwards.
+    pub const unsafe fn new(ptr: NonNull<u8>) -> Self {
+        Self { ptr, _marker: PhantomData }
+    }
+
+    /// Moves a value behind an owning pointer for the duration of a closure.
+    pub fn make<T, R>(value: T, f: impl FnOnce(OwningPtr<'_>) -> R) -> R {
+        let mut value = ManuallyDrop::new(value);
+
+        // SAFETY: the value isn't dropped or used after the closure
+        f(unsafe { OwningPtr::new(NonNull::from(&mut *value).cast()) })
+    }
+
+    /// Returns the underlying pointer.
+    pub const fn as_ptr(&self) -> NonNull<u8> {
+        self.ptr
+    }
+
+    /// Moves the value out of this pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to a value of `T`.
+    pub const unsafe fn read<T>(self) -> T {
+        // SAFETY: the caller ensures that the pointer is to a `T`
+        unsafe { self.ptr.cast().read() }
+    }
+}
+
+// ---
+
+impl<C: Component> Clone for ComponentPtr<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Component> Copy for ComponentPtr<C> {}
+
+impl<C: Component> PartialEq for ComponentPtr<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity && self.addr == other.addr
+    }
+}
+
+impl<C: Component> Eq for ComponentPtr<C> {}
+
+impl<C: Component> Hash for ComponentPtr<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entity.hash(state);
+        self.addr.hash(state);
+    }
+}
+
+impl<C: Component> fmt::Debug for ComponentPtr<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentPtr")
+            .field("entity", &self.entity)
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+impl fmt::Debug for OwningPtr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwningPtr").field(&self.ptr).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A(u32);
+
+    #[derive(Component)]
+    struct B;
+
+    #[test]
+    fn resolve() {
+        let mut world = World::new();
+        let entity = world.spawn(A(1)).id();
+        let ptr = world.component_ptr::<A>(entity).unwrap();
+
+        ptr.get_mut(&mut world).unwrap().0 += 1;
+
+        assert_eq!(ptr.get(&world).unwrap().0, 2);
+        assert!(world.component_ptr::<B>(entity).is_none());
+    }
+
+    #[test]
+    fn invalidated_by_move_and_despawn() {
+        let mut world = World::new();
+        let entity = world.spawn(A(1)).id();
+        let ptr = world.component_ptr::<A>(entity).unwrap();
+
+        world.entity_mut(entity).unwrap().insert(B);
+
+        assert!(ptr.get(&world).is_none());
+
+        let ptr = world.component_ptr::<A>(entity).unwrap();
+
+        world.despawn(entity).unwrap();
+
+        assert!(ptr.get(&world).is_none());
+    }
+}
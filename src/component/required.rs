@@ -0,0 +1,78 @@
+use std::fmt;
+
+use super::{Component, ComponentInfo, ComponentSet, ComponentVTable};
+use crate::storage::{Table, TableRow};
+
+/// The components required by a [`Component`].
+///
+/// See [`Component::required`].
+pub struct Requirements {
+    inner: Vec<Requirement>,
+}
+
+/// A component that is inserted with its default value when required.
+#[derive(Clone, Copy)]
+pub(crate) struct Requirement {
+    pub info: ComponentInfo,
+    /// Writes the default value of the component to a row.
+    pub init: unsafe fn(&mut Table, TableRow),
+}
+
+impl Requirements {
+    const fn new() -> Self {
+        let inner = Vec::new();
+
+        Self { inner }
+    }
+
+    /// Returns the requirements of the components in `new` that are contained
+    /// in neither `new` nor `existing`.
+    pub(crate) fn missing(
+        new: &ComponentSet,
+        existing: &ComponentSet,
+    ) -> Vec<Requirement> {
+        let mut requirements = Self::new();
+
+        for component in new {
+            component.required(&mut requirements);
+        }
+
+        requirements.inner.retain(|requirement| {
+            let id = requirement.info.id();
+
+            !new.contains(id) && !existing.contains(id)
+        });
+        requirements.inner
+    }
+
+    /// Requires `C`, as well as all components required by `C`.
+    pub fn require<C: Component + Default>(&mut self) {
+        let info = ComponentInfo::of::<C>();
+
+        if self.inner.iter().any(|requirement| requirement.info == info) {
+            return;
+        }
+
+        self.inner.push(Requirement { info, init: init_default::<C> });
+        C::required(self);
+    }
+}
+
+/// # Safety
+///
+/// The table must contain `C` and the entity at the row.
+unsafe fn init_default<C: Component + Default>(
+    table: &mut Table,
+    row: TableRow,
+) {
+    // SAFETY: the caller ensures that the table contains `C`
+    let written = unsafe { table.init(row, C::default()) };
+
+    debug_assert!(written.is_some());
+}
+
+impl fmt::Debug for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.info.fmt(f)
+    }
+}
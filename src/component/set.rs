@@ -1,21 +1,38 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::iter::Copied;
 
-use super::{ComponentId, ComponentInfo, ComponentVTable};
-use crate::storage::{SparseIter, SparseSet};
+use super::{ComponentId, ComponentInfo};
+use crate::storage::{BitSet, SparseIndex, SparseIter, SparseSet};
 
 /// A set of component types.
-#[derive(Default, Clone, PartialEq, Eq, Hash)]
+///
+/// Comparisons, hashing and set operations are word-wise operations on a bitset
+/// of component ids.
+#[derive(Default, Clone)]
 pub struct ComponentSet {
     inner: SparseSet<ComponentInfo>,
+    /// The ids of the components in the set, for word-wise comparisons.
+    bits: BitSet,
 }
 
 impl ComponentSet {
     /// Returns a new empty component set.
     pub const fn new() -> Self {
         let inner = SparseSet::new();
+        let bits = BitSet::new();
 
-        Self { inner }
+        Self { inner, bits }
+    }
+
+    /// Returns the amount of components in the set.
+    pub const fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the set contains no components.
+    pub const fn is_empty(&self) -> bool {
+        self.inner.is_empty()
     }
 
     /// Returns an iterator over the component info in the set
@@ -32,27 +49,54 @@ impl ComponentSet {
 
     /// Returns `true` if the set contains the given component.
     pub fn contains(&self, component: ComponentId) -> bool {
-        self.inner.contains(&component)
+        self.bits.contains(component.sparse_index())
+    }
+
+    /// Returns `true` if every component in `self` is also in `other`.
+    pub fn is_subset(&self, other: &ComponentSet) -> bool {
+        self.bits.is_subset(&other.bits)
+    }
+
+    /// Returns `true` if every component in `other` is also in `self`.
+    pub fn is_superset(&self, other: &ComponentSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if `self` and `other` have no components in common.
+    pub fn is_disjoint(&self, other: &ComponentSet) -> bool {
+        self.bits.is_disjoint(&other.bits)
     }
 
     /// Returns a new component set containing the intersection of `self` and
     /// `other`.
     pub fn intersection(&self, other: &ComponentSet) -> Self {
-        let mut intersection = self.clone();
+        let bits = self.bits.intersection(&other.bits);
+        let mut inner = SparseSet::new();
 
-        for &component in &self.inner {
-            let id = component.id();
+        for index in bits.iter() {
+            // SAFETY: the bits of `self` are the indices of `self.inner`
+            inner.insert(unsafe { *self.inner.get(index).unwrap_unchecked() });
+        }
 
-            if !other.contains(id) {
-                intersection.remove(id);
-            }
+        Self { inner, bits }
+    }
+
+    /// Returns a new component set containing the union of `self` and
+    /// `other`.
+    pub fn union(&self, other: &ComponentSet) -> Self {
+        let bits = self.bits.union(&other.bits);
+        let mut inner = self.inner.clone();
+
+        for component in other {
+            inner.insert(component);
         }
 
-        intersection
+        Self { inner, bits }
     }
 
     /// Inserts a new component type into the set.
     pub fn insert(&mut self, component: ComponentInfo) {
+        self.bits.insert(component.sparse_index());
         self.inner.insert(component);
     }
 
@@ -65,6 +109,7 @@ impl ComponentSet {
 
     /// Removes a component type from the set.
     pub fn remove(&mut self, component: ComponentId) -> Option<ComponentInfo> {
+        self.bits.remove(component.sparse_index());
         self.inner.remove(&component)
     }
 
@@ -78,6 +123,20 @@ impl ComponentSet {
 
 // ---
 
+impl PartialEq for ComponentSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl Eq for ComponentSet {}
+
+impl Hash for ComponentSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
 impl fmt::Debug for ComponentSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         struct DebugDisplay<'a, T: fmt::Display>(&'a T);
@@ -153,4 +212,26 @@ mod tests {
         assert_eq!(&a_b_c.intersection(&a_b_c), &a_b_c);
         assert_eq!(&a_b_c.intersection(&d), &empty);
     }
+
+    #[test]
+    fn subset_superset_disjoint() {
+        let a = ComponentSet::new().and_insert(ComponentInfo::of::<A>());
+        let a_b = a.clone().and_insert(ComponentInfo::of::<B>());
+        let c_d = ComponentSet::new()
+            .and_insert(ComponentInfo::of::<C>())
+            .and_insert(ComponentInfo::of::<D>());
+        let empty = ComponentSet::new();
+
+        assert!(a.is_subset(&a));
+        assert!(a.is_subset(&a_b));
+        assert!(!a_b.is_subset(&a));
+        assert!(empty.is_subset(&a));
+
+        assert!(a_b.is_superset(&a));
+        assert!(!a.is_superset(&a_b));
+
+        assert!(a_b.is_disjoint(&c_d));
+        assert!(!a.is_disjoint(&a_b));
+        assert!(empty.is_disjoint(&empty));
+    }
 }
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::{ComponentId, ComponentInfo, ComponentVTable};
+
+/// A mapping between the indices of components in saved data and their
+/// [`ComponentId`]'s in the running program.
+///
+/// [`ComponentId`]'s depend on the order that components are first used in, so
+/// they can't be saved directly. Instead, a serializer assigns each saved
+/// component an index with [`ComponentIdMap::index`] and saves the
+/// [stable ids](crate::component::Component::stable_id) of the indexed
+/// components alongside the data. When loading, the map is rebuilt from the
+/// saved stable ids with [`ComponentIdMap::from_stable_ids`].
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// # use worldlines::component::ComponentIdMap;
+/// #[derive(Component)]
+/// #[component(stable_id = "game::Health")]
+/// struct Health(u32);
+///
+/// let mut map = ComponentIdMap::new();
+/// let index = map.index(ComponentId::of::<Health>());
+/// let saved: Vec<String> = map.stable_ids().map(String::from).collect();
+///
+/// assert_eq!(saved, ["game::Health"]);
+///
+/// let map = ComponentIdMap::from_stable_ids(&saved).unwrap();
+///
+/// assert_eq!(map.get(index), Some(ComponentId::of::<Health>()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ComponentIdMap {
+    /// The ids of the components by their index.
+    ids: Vec<ComponentId>,
+    indices: HashMap<ComponentId, u32>,
+}
+
+/// Error when looking up a component by its
+/// [stable id](crate::component::Component::stable_id).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StableIdError {
+    /// No registered component has the stable id.
+    #[error("no registered component has the stable id {0}")]
+    NotFound(String),
+    /// Multiple registered components have the stable id.
+    #[error("multiple components have the stable id {0}")]
+    Ambiguous(String),
+}
+
+impl ComponentIdMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a map from the stable ids of saved components, in order of
+    /// their index.
+    ///
+    /// Returns an error if a stable id doesn't belong to exactly one
+    /// registered component. See [`ComponentId::of_stable_id`].
+    pub fn from_stable_ids<S: AsRef<str>>(
+        stable_ids: impl IntoIterator<Item = S>,
+    ) -> Result<Self, StableIdError> {
+        let mut map = Self::new();
+
+        for stable_id in stable_ids {
+            map.index(ComponentId::of_stable_id(stable_id.as_ref())?);
+        }
+
+        Ok(map)
+    }
+
+    /// Returns the amount of components in this map.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if this map doesn't contain any components.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the index of a component, assigning it the next index if it
+    /// isn't in this map.
+    pub fn index(&mut self, id: ComponentId) -> u32 {
+        *self.indices.entry(id).or_insert_with(|| {
+            let index = self.ids.len() as u32;
+
+            self.ids.push(id);
+
+            index
+        })
+    }
+
+    /// Returns the index of a component if it is in this map.
+    pub fn get_index(&self, id: ComponentId) -> Option<u32> {
+        self.indices.get(&id).copied()
+    }
+
+    /// Returns the id of the component with an index.
+    pub fn get(&self, index: u32) -> Option<ComponentId> {
+        self.ids.get(index as usize).copied()
+    }
+
+    /// Returns an iterator over the stable ids of the components in this map,
+    /// in order of their index.
+    pub fn stable_ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.ids.iter().map(|&id| ComponentInfo::of_id(id).stable_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    #[component(stable_id = "stable::Position")]
+    struct Position;
+
+    #[derive(Component)]
+    struct Velocity;
+
+    mod renamed {
+        use crate::prelude::*;
+
+        #[derive(Component)]
+        #[component(stable_id = "stable::Duplicate")]
+        pub struct First;
+
+        #[derive(Component)]
+        #[component(stable_id = "stable::Duplicate")]
+        pub struct Second;
+    }
+
+    #[test]
+    fn stable_ids() {
+        let position = ComponentId::of::<Position>();
+        let velocity = ComponentId::of::<Velocity>();
+
+        assert_eq!(ComponentId::of_stable_id("stable::Position"), Ok(position));
+        assert_eq!(
+            ComponentId::of_stable_id(type_name::<Velocity>()),
+            Ok(velocity),
+        );
+        assert!(matches!(
+            ComponentId::of_stable_id("stable::Missing"),
+            Err(StableIdError::NotFound(_)),
+        ));
+
+        ComponentId::of::<renamed::First>();
+        ComponentId::of::<renamed::Second>();
+
+        assert!(matches!(
+            ComponentId::of_stable_id("stable::Duplicate"),
+            Err(StableIdError::Ambiguous(_)),
+        ));
+    }
+
+    #[test]
+    fn remap_saved_indices() {
+        let mut map = ComponentIdMap::new();
+
+        assert_eq!(map.index(ComponentId::of::<Velocity>()), 0);
+        assert_eq!(map.index(ComponentId::of::<Position>()), 1);
+        assert_eq!(map.index(ComponentId::of::<Velocity>()), 0);
+
+        let saved: Vec<_> = map.stable_ids().collect();
+        let loaded = ComponentIdMap::from_stable_ids(&saved).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(1), Some(ComponentId::of::<Position>()));
+        assert_eq!(loaded.get_index(ComponentId::of::<Velocity>()), Some(0));
+        assert!(ComponentIdMap::from_stable_ids(["stable::Missing"]).is_err());
+    }
+}
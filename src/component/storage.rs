@@ -1,18 +1,28 @@
 use std::any::TypeId;
 use std::collections::HashMap;
 use std::mem::MaybeUninit;
+use std::sync::Arc;
 
-use super::{Bundle, ComponentSet};
+use super::{Bundle, ComponentSet, Requirement, Requirements};
 use crate::entity::{EntityAddr, EntityId};
 use crate::prelude::ComponentVTable;
-use crate::storage::{SparseIndex, Table, TableRow, TypeIdHasher};
+use crate::storage::{SparseIndex, Table, TypeIdHasher, WorldAllocator};
 
 /// Storage for all components.
 #[derive(Debug)]
 pub struct Components {
-    bundle_indices: HashMap<TypeId, TableId, TypeIdHasher>,
+    bundles: HashMap<TypeId, BundleInfo, TypeIdHasher>,
     set_indices: HashMap<ComponentSet, TableId>,
     tables: Vec<Table>,
+    alloc: Arc<dyn WorldAllocator>,
+}
+
+/// Cached storage information for a bundle type.
+#[derive(Debug)]
+struct BundleInfo {
+    table: TableId,
+    /// Components required by the bundle that it doesn't contain.
+    required: Vec<Requirement>,
 }
 
 /// Newtype for the index of a table in [`Components`].
@@ -24,13 +34,20 @@ impl Components {
     /// Default table capacity.
     const DEFAULT_TABLES: usize = 16;
 
-    /// Creates empty component storage.
-    pub fn new() -> Self {
-        let bundle_indices = HashMap::default();
+    /// Creates empty component storage that allocates tables with an
+    /// allocator.
+    pub fn new_in(alloc: impl WorldAllocator) -> Self {
+        let bundles = HashMap::default();
         let set_indices = HashMap::with_capacity(Self::DEFAULT_TABLES);
         let tables = Vec::with_capacity(Self::DEFAULT_TABLES);
+        let alloc = Arc::new(alloc);
 
-        Self { bundle_indices, set_indices, tables }
+        Self { bundles, set_indices, tables, alloc }
+    }
+
+    /// Returns a reference to the table with the given index.
+    pub fn get(&self, index: TableId) -> Option<&Table> {
+        self.tables.get(index.0)
     }
 
     /// Returns a reference to the table with the given index.
@@ -51,6 +68,11 @@ impl Components {
         unsafe { self.tables.get_unchecked_mut(index.0) }
     }
 
+    /// Returns the allocator of the tables in storage.
+    pub fn allocator(&self) -> &Arc<dyn WorldAllocator> {
+        &self.alloc
+    }
+
     /// Returns an iterator over the tables in storage.
     pub fn tables(&self) -> impl ExactSizeIterator<Item = (TableId, &Table)> {
         self.tables.iter().enumerate().map(|(i, table)| (TableId(i), table))
@@ -59,42 +81,46 @@ impl Components {
     /// Returns the table for the specified bundle.
     ///
     /// Will allocate a new table if one for that bundle didn't already exist.
+    /// The table also contains the components required by the bundle.
     pub fn alloc<B: Bundle>(&mut self, count: usize) -> EntityAddr {
-        let table = self
-            .bundle_indices
-            .get(&TypeId::of::<B>())
-            .copied()
-            .unwrap_or_else(|| {
+        let table = match self.bundles.get(&TypeId::of::<B>()) {
+            Some(bundle) => bundle.table,
+            None => {
                 let mut components = ComponentSet::new();
 
                 B::components(&mut components);
 
-                let table =
-                    self.set_indices.get(&components).copied().unwrap_or_else(
-                        || {
-                            let table = TableId(self.tables.len());
+                let required =
+                    Requirements::missing(&components, &ComponentSet::new());
 
-                            self.set_indices.insert(components.clone(), table);
-                            self.tables
-                                .push(Table::with_capacity(components, count));
+                for requirement in &required {
+                    components.insert(requirement.info);
+                }
 
-                            table
-                        },
-                    );
+                let table = self.alloc_set(count, components).table;
 
-                self.bundle_indices.insert(TypeId::of::<B>(), table);
+                self.bundles
+                    .insert(TypeId::of::<B>(), BundleInfo { table, required });
 
                 table
-            });
-        let row = {
-            let table = unsafe { self.get_unchecked_mut(table) };
-
-            TableRow(table.entities().len())
+            },
         };
+        // SAFETY: the table was allocated above
+        let row = unsafe { self.get_unchecked(table) }.next_row();
 
         EntityAddr { table, row }
     }
 
+    /// Returns the components required by a bundle that it doesn't contain.
+    ///
+    /// Empty if the bundle hasn't been allocated with [`Components::alloc`].
+    pub(crate) fn required<B: Bundle>(&self) -> &[Requirement] {
+        self.bundles
+            .get(&TypeId::of::<B>())
+            .map(|bundle| bundle.required.as_slice())
+            .unwrap_or_default()
+    }
+
     /// Returns the table for the given component set.
     ///
     /// Will allocate a new table if one didn't already exist.
@@ -107,15 +133,16 @@ impl Components {
         let table =
             self.set_indices.get(&components).copied().unwrap_or_else(|| {
                 self.set_indices.insert(components.clone(), next);
-                self.tables.push(Table::with_capacity(components, count));
+                self.tables.push(Table::with_capacity(
+                    components,
+                    count,
+                    &self.alloc,
+                ));
 
                 next
             });
-        let row = {
-            let table = unsafe { self.get_unchecked_mut(table) };
-
-            TableRow(table.entities().len())
-        };
+        // SAFETY: the table was allocated above
+        let row = unsafe { self.get_unchecked(table) }.next_row();
 
         EntityAddr { table, row }
     }
@@ -165,7 +192,7 @@ impl Components {
             unsafe {
                 let ptr = old_table.get_unchecked_mut(old_addr.row, component);
 
-                new_table.write_ptr(new_addr.row, component, ptr);
+                new_table.init_component(new_addr.row, component, ptr);
             }
         }
 
@@ -18,7 +18,11 @@ pub struct Entities {
     cursor: AtomicIsize,
     pending: Vec<u32>,
     allocated: usize,
+    /// Amount of entities with an address.
+    spawned: usize,
     reserved: AtomicUsize,
+    /// Incremented whenever entities are allocated, freed or moved.
+    epoch: u64,
 }
 
 /// Describes a possibly-live entity.
@@ -54,9 +58,11 @@ impl Entities {
         let cursor = AtomicIsize::new(0);
         let pending = Vec::new();
         let allocated = 0;
+        let spawned = 0;
         let reserved = AtomicUsize::new(0);
+        let epoch = 0;
 
-        Self { slots, cursor, pending, allocated, reserved }
+        Self { slots, cursor, pending, allocated, spawned, reserved, epoch }
     }
 
     /// Amount of allocated entities.
@@ -64,11 +70,35 @@ impl Entities {
         self.allocated + self.reserved.load(Ordering::Relaxed)
     }
 
+    /// Amount of allocated entities, excluding those acquired from
+    /// [`Entities::reserve`].
+    ///
+    /// Equal to the amount of entities yielded by [`Entities::iter`].
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Amount of entities with an address, set by [`Entities::set`].
+    ///
+    /// Excludes entities that were allocated or reserved, but weren't spawned
+    /// into a table yet.
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
     /// Whether there are any allocated entities.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns the structure epoch, which changes whenever entities are
+    /// allocated, freed or moved.
+    ///
+    /// Used to detect structural changes during query iteration.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
     /// Whether the entity is currently alive.
     pub fn contains(&self, entity: EntityId) -> bool {
         if let Some(slot) = self.slots.get(entity.index as usize) {
@@ -83,6 +113,11 @@ impl Entities {
         }
     }
 
+    /// Returns the amount of entity slots, live or dead.
+    pub fn slots_len(&self) -> usize {
+        self.slots.len()
+    }
+
     /// Iterate over the entities in storage.
     ///
     /// Only iterates over allocated entities so as to preserve order of index.
@@ -110,9 +145,12 @@ impl Entities {
     }
 
     /// Get the address of a entity.
+    ///
+    /// Returns `None` for stale ids whose slot was reused by a newer entity.
     pub fn get(&self, entity: EntityId) -> Option<EntityAddr> {
         self.slots
             .get(entity.index as usize)
+            .filter(|slot| slot.version == entity.version)
             .and_then(|EntitySlot { addr, .. }| *addr)
     }
 
@@ -137,13 +175,18 @@ impl Entities {
     /// This will also allocated all reserved entities.
     pub fn alloc(&mut self) -> EntityId {
         self.flush();
+        self.epoch += 1;
 
         self.allocated += 1;
 
         if let Some(index) = self.pending.pop() {
             *self.cursor.get_mut() = self.pending.len() as _;
 
-            EntityId::new(index, self.slots[index as usize].version)
+            let slot = &mut self.slots[index as usize];
+
+            slot.alive = true;
+
+            EntityId::new(index, slot.version)
         } else {
             self.slots.push(EntitySlot::new());
 
@@ -158,6 +201,7 @@ impl Entities {
     ///
     /// Does not call [`Entities::flush`].
     pub fn alloc_end(&mut self) -> EntityId {
+        self.epoch += 1;
         self.allocated += 1;
         self.slots.push(EntitySlot::new());
 
@@ -167,11 +211,54 @@ impl Entities {
         )
     }
 
+    /// Allocates a specific entity, such as one moved from another world.
+    ///
+    /// Returns `false` if its slot is alive or has a newer version, as that
+    /// would make stale ids valid again. Slots skipped to reach the entity
+    /// are freed for reuse.
+    pub fn alloc_at(&mut self, entity: EntityId) -> bool {
+        self.flush();
+
+        let index = entity.index as usize;
+
+        if let Some(slot) = self.slots.get(index) {
+            if slot.alive || slot.version > entity.version {
+                return false;
+            }
+
+            // dead slots are pending after the flush above
+            if let Some(pending) =
+                self.pending.iter().rposition(|&index| index == entity.index)
+            {
+                self.pending.remove(pending);
+            }
+        } else {
+            for skipped in self.slots.len()..index {
+                self.slots
+                    .push(EntitySlot { alive: false, ..EntitySlot::new() });
+                self.pending.push(skipped as u32);
+            }
+
+            self.slots.push(EntitySlot::new());
+        }
+
+        let slot = &mut self.slots[index];
+
+        slot.version = entity.version;
+        slot.alive = true;
+        *self.cursor.get_mut() = self.pending.len() as _;
+        self.allocated += 1;
+        self.epoch += 1;
+
+        true
+    }
+
     /// Allocates multiple entities at once.
     ///
     /// Returns the range of allocated [`EntitySlot`]s.
     pub fn alloc_many(&mut self, count: usize) -> Range<usize> {
         self.flush();
+        self.epoch += 1;
         self.allocated += count;
 
         let start = self.slots.len();
@@ -202,12 +289,53 @@ impl Entities {
         }
     }
 
+    /// Reserve many new entities at once.
+    ///
+    /// Equivalent to calling [`Entities::reserve`] for each entity, but only
+    /// contends on the cursor once.
+    pub fn reserve_many(
+        &self,
+        count: u32,
+    ) -> impl ExactSizeIterator<Item = EntityId> + '_ {
+        self.reserved.fetch_add(count as usize, Ordering::Relaxed);
+
+        let end = self.cursor.fetch_sub(count as isize, Ordering::Relaxed);
+        let start = end - count as isize;
+        let pending = &self.pending[start.max(0) as usize..end.max(0) as usize];
+        // new indices are handed out past the slots, like in `reserve`
+        let new = if start >= 0 {
+            0..0
+        } else {
+            let len = self.slots.len() as isize;
+
+            u32::try_from(len - end.min(0)).expect("entity overflow")
+                ..u32::try_from(len - start).expect("entity overflow")
+        };
+
+        pending
+            .iter()
+            .rev()
+            .map(|&index| {
+                EntityId::new(index, self.slots[index as usize].version)
+            })
+            .chain(new.map(EntityId::from_index))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Returns `true` if the entity was reserved or allocated, but wasn't
+    /// spawned into a table.
+    pub fn is_unspawned(&self, entity: EntityId) -> bool {
+        self.contains(entity) && self.get(entity).is_none()
+    }
+
     /// Free an entity, allowing its id to be reused.
     ///
     /// Returns the entity address if the entity existed (and thus was freed)
     /// and the table was set.
     pub fn free(&mut self, entity: EntityId) -> Option<EntityAddr> {
         self.flush();
+        self.epoch += 1;
 
         let slot = self.slots.get_mut(entity.index as usize)?;
 
@@ -217,6 +345,10 @@ impl Entities {
 
         let addr = slot.addr.take();
 
+        if addr.is_some() {
+            self.spawned -= 1;
+        }
+
         slot.version =
             slot.version.checked_add(1).expect("entity version overflow");
         slot.alive = false;
@@ -234,19 +366,28 @@ impl Entities {
     /// Returns `Some` if the entity exists.
     pub fn set(&mut self, entity: EntityId, addr: EntityAddr) -> Option<()> {
         self.flush();
+        self.epoch += 1;
 
-        self.slots
+        let slot = self
+            .slots
             .get_mut(entity.index as usize)
-            .filter(|slot| slot.alive && slot.version == entity.version)
-            .map(|slot| slot.addr = Some(addr))
+            .filter(|slot| slot.alive && slot.version == entity.version)?;
+
+        if slot.addr.replace(addr).is_none() {
+            self.spawned += 1;
+        }
+
+        Some(())
     }
 
     /// Clear allocation state and all entities.
     pub fn clear(&mut self) {
+        self.epoch += 1;
         self.slots.clear();
         *self.cursor.get_mut() = 0;
         self.pending.clear();
         self.allocated = 0;
+        self.spawned = 0;
         *self.reserved.get_mut() = 0;
     }
 
@@ -272,8 +413,13 @@ impl Entities {
 
         *cursor = new_cursor;
 
+        // pending entities past the cursor were reserved
+        for &index in &self.pending[new_cursor as usize..] {
+            self.slots[index as usize].alive = true;
+        }
+
         self.allocated += self.pending.len() - new_cursor as usize;
-        self.pending.clear();
+        self.pending.truncate(new_cursor as usize);
         // all reserved entities are now fully allocated
         *self.reserved.get_mut() = 0;
 
@@ -380,6 +526,36 @@ mod tests {
         assert_eq!(entities.allocated, 0);
     }
 
+    #[test]
+    fn reserve_many() {
+        let mut entities = Entities::new();
+        let [e0, e1, e2] =
+            [entities.alloc(), entities.alloc(), entities.alloc()];
+
+        _ = entities.free(e0);
+        _ = entities.free(e2);
+
+        let reserved: Vec<_> = entities.reserve_many(4).collect();
+        let e3 = entities.reserve();
+
+        assert_eq!(reserved.len(), 4);
+        assert_eq!(reserved[0].index, e2.index);
+        assert_eq!(reserved[1].index, e0.index);
+        assert_eq!(reserved[0].version.get(), 2);
+        assert_eq!(reserved[2], EntityId::from_index(3));
+        assert_eq!(reserved[3], EntityId::from_index(4));
+        assert_eq!(e3, EntityId::from_index(5));
+
+        entities.flush();
+
+        for &entity in reserved.iter().chain([&e1, &e3]) {
+            assert!(entities.contains(entity));
+        }
+
+        assert_eq!(entities.len(), 6);
+        assert_eq!(entities.allocated, 6);
+    }
+
     #[test]
     fn clear() {
         let mut entities = Entities::new();
@@ -475,4 +651,32 @@ mod tests {
 
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn alloc_at() {
+        let mut entities = Entities::new();
+        let e0 = entities.alloc();
+
+        assert!(!entities.alloc_at(e0));
+
+        _ = entities.free(e0);
+
+        assert!(!entities.alloc_at(e0));
+
+        let e3 = EntityId::from_index(3);
+
+        assert!(entities.alloc_at(e3));
+        assert!(entities.contains(e3));
+        assert_eq!(entities.len(), 1);
+
+        let reused: Vec<_> =
+            [entities.alloc(), entities.alloc(), entities.alloc()]
+                .map(|entity| entity.index)
+                .into();
+
+        assert!(reused.contains(&0));
+        assert!(reused.contains(&1));
+        assert!(reused.contains(&2));
+        assert_eq!(entities.alloc().index, 4);
+    }
 }
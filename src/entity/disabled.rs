@@ -0,0 +1,13 @@
+//! Defines [`Disabled`], a marker that hides entities from queries.
+
+use crate::component::Component;
+
+/// A marker for entities that queries should skip.
+///
+/// Queries don't match disabled entities unless they mention `Disabled`
+/// themselves (like `With<Disabled>` or `Option<&Disabled>`) or include
+/// [`IncludeDisabled`](crate::query::IncludeDisabled). Disabled entities can
+/// still be accessed directly through
+/// [`World::entity`](crate::world::World::entity).
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Disabled;
@@ -0,0 +1,63 @@
+//! Defines [`ComponentEntry`], a view into a component that may not exist.
+
+use std::marker::PhantomData;
+
+use super::EntityWorld;
+use crate::component::Component;
+
+/// A view into a component of an entity that may not exist.
+///
+/// Returned by [`EntityWorld::entry`].
+pub struct ComponentEntry<'a, 'w, C: Component> {
+    entity: &'a mut EntityWorld<'w>,
+    _marker: PhantomData<C>,
+}
+
+impl<'a, 'w, C: Component> ComponentEntry<'a, 'w, C> {
+    pub(crate) fn new(entity: &'a mut EntityWorld<'w>) -> Self {
+        Self { entity, _marker: PhantomData }
+    }
+
+    /// Calls a function on the component if it exists.
+    pub fn and_modify(self, f: impl FnOnce(&mut C)) -> Self {
+        if let Ok(component) = self.entity.get_mut::<C>() {
+            f(component);
+        }
+
+        self
+    }
+
+    /// Inserts the component if it doesn't exist and returns a mutable
+    /// reference to it.
+    #[track_caller]
+    pub fn or_insert(self, default: C) -> &'a mut C {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of a function if the component doesn't exist and
+    /// returns a mutable reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is removed by an insertion hook.
+    #[track_caller]
+    pub fn or_insert_with(self, f: impl FnOnce() -> C) -> &'a mut C {
+        if !self.entity.contains::<C>() {
+            self.entity.insert(f());
+        }
+
+        self.entity
+            .get_mut::<C>()
+            .expect("the component was removed by an insertion hook")
+    }
+
+    /// Inserts the default value if the component doesn't exist and returns a
+    /// mutable reference to it.
+    #[track_caller]
+    pub fn or_default(self) -> &'a mut C
+    where
+        C: Default,
+    {
+        self.or_insert_with(C::default)
+    }
+}
@@ -0,0 +1,127 @@
+//! Defines [`FilteredEntityRef`] and [`FilteredEntityMut`], entity references
+//! restricted to a subset of components.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::sync::LazyLock;
+
+use dashmap::DashMap;
+
+use super::{EntityId, EntityMut, EntityRef};
+use crate::component::{Bundle, Component, ComponentNotFound, ComponentSet};
+use crate::prelude::ComponentId;
+use crate::storage::TypeIdHasher;
+
+/// A reference to an entity that can only access the components in `B`.
+///
+/// As query data, it only declares access to the components of `B`, so it can
+/// be used alongside queries of other components.
+pub struct FilteredEntityRef<'w, B: Bundle> {
+    inner: EntityRef<'w>,
+    _marker: PhantomData<B>,
+}
+
+/// A mutable reference to an entity that can only access the components in
+/// `B`.
+///
+/// As query data, it only declares access to the components of `B`, so it can
+/// be used alongside queries of other components.
+pub struct FilteredEntityMut<'w, B: Bundle> {
+    inner: EntityMut<'w>,
+    _marker: PhantomData<B>,
+}
+
+/// Returns `true` if the component is in the component set of `B`.
+///
+/// The set of each bundle is built once, so that accessing a component doesn't
+/// allocate.
+fn allows<B: Bundle>(component: ComponentId) -> bool {
+    static SETS: LazyLock<DashMap<TypeId, ComponentSet, TypeIdHasher>> =
+        LazyLock::new(Default::default);
+
+    let type_id = TypeId::of::<B>();
+
+    if let Some(components) = SETS.get(&type_id) {
+        return components.contains(component);
+    }
+
+    SETS.entry(type_id)
+        .or_insert_with(|| {
+            let mut components = ComponentSet::new();
+
+            B::components(&mut components);
+
+            components
+        })
+        .contains(component)
+}
+
+impl<'w, B: Bundle> FilteredEntityRef<'w, B> {
+    /// Restricts an entity reference to the components in `B`.
+    pub fn new(inner: EntityRef<'w>) -> Self {
+        Self { inner, _marker: PhantomData }
+    }
+
+    /// Returns the id of this entity.
+    pub const fn id(&self) -> EntityId {
+        self.inner.id()
+    }
+
+    /// Returns `true` if this entity contains the component.
+    pub fn contains<C: Component>(&self) -> bool {
+        self.inner.contains::<C>()
+    }
+
+    /// Returns a reference to a component of this entity.
+    ///
+    /// Returns an error if the component doesn't exist or isn't in `B`.
+    pub fn get<C: Component>(&self) -> Result<&'w C, ComponentNotFound> {
+        if allows::<B>(ComponentId::of::<C>()) {
+            self.inner.get()
+        } else {
+            Err(ComponentNotFound::new::<C>(self.id()))
+        }
+    }
+}
+
+impl<'w, B: Bundle> FilteredEntityMut<'w, B> {
+    /// Restricts a mutable entity reference to the components in `B`.
+    pub fn new(inner: EntityMut<'w>) -> Self {
+        Self { inner, _marker: PhantomData }
+    }
+
+    /// Returns the id of this entity.
+    pub const fn id(&self) -> EntityId {
+        self.inner.id()
+    }
+
+    /// Borrows this entity as a [`FilteredEntityRef`].
+    pub fn as_ref(&self) -> FilteredEntityRef<'w, B> {
+        FilteredEntityRef::new(self.inner.as_ref())
+    }
+
+    /// Returns `true` if this entity contains the component.
+    pub fn contains<C: Component>(&self) -> bool {
+        self.inner.contains::<C>()
+    }
+
+    /// Returns a reference to a component of this entity.
+    ///
+    /// Returns an error if the component doesn't exist or isn't in `B`.
+    pub fn get<C: Component>(&self) -> Result<&'w C, ComponentNotFound> {
+        self.as_ref().get()
+    }
+
+    /// Returns a mutable reference to a component of this entity.
+    ///
+    /// Returns an error if the component doesn't exist or isn't in `B`.
+    pub fn get_mut<C: Component>(
+        &mut self,
+    ) -> Result<&'w mut C, ComponentNotFound> {
+        if allows::<B>(ComponentId::of::<C>()) {
+            self.inner.get_mut()
+        } else {
+            Err(ComponentNotFound::new::<C>(self.id()))
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Defines [`EntityHandle`], an entity id for long-lived references.
+
+use super::{EntityId, EntityMapper, EntityRef, EntityWorld, MapEntities};
+use crate::world::World;
+
+/// A weak reference to an entity that may have been despawned.
+///
+/// Wraps an [`EntityId`] with methods that return `None` for stale ids instead
+/// of an error, for code that keeps ids around between ticks.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityHandle {
+    id: EntityId,
+}
+
+impl EntityHandle {
+    /// Creates a new handle to an entity.
+    pub const fn new(id: EntityId) -> Self {
+        Self { id }
+    }
+
+    /// Returns the id of the entity.
+    pub const fn id(self) -> EntityId {
+        self.id
+    }
+
+    /// Returns `true` if the entity is alive in the world.
+    pub fn is_alive(self, world: &World) -> bool {
+        world.contains(self.id)
+    }
+
+    /// Borrows the entity, or returns `None` if it was despawned.
+    pub fn get(self, world: &World) -> Option<EntityRef<'_>> {
+        world.entity(self.id).ok()
+    }
+
+    /// Mutably borrows the entity and the world, or returns `None` if it was
+    /// despawned.
+    pub fn get_mut(self, world: &mut World) -> Option<EntityWorld<'_>> {
+        world.entity_mut(self.id).ok()
+    }
+}
+
+impl From<EntityId> for EntityHandle {
+    fn from(id: EntityId) -> Self {
+        Self::new(id)
+    }
+}
+
+impl MapEntities for EntityHandle {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        self.id.map_entities(mapper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Hp(u32);
+
+    #[test]
+    fn stale_handle() {
+        let mut world = World::new();
+        let handle = EntityHandle::new(world.spawn(Hp(1)).id());
+
+        assert_eq!(handle.get(&world).unwrap().get::<Hp>().unwrap().0, 1);
+
+        world.despawn(handle.id()).unwrap();
+
+        // reuses the slot of the despawned entity
+        world.spawn(Hp(2));
+
+        assert!(!handle.is_alive(&world));
+        assert!(handle.get(&world).is_none());
+        assert!(handle.get_mut(&mut world).is_none());
+
+        let query = world.query::<&Hp>().unwrap();
+
+        assert!(query.get_or_despawned(handle.id()).unwrap().is_none());
+    }
+}
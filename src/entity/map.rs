@@ -0,0 +1,200 @@
+//! Defines [`MapEntities`], for remapping entity ids stored in components.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+
+use super::EntityId;
+
+/// A [`HashMap`] keyed by [`EntityId`]s, using [`EntityIdHasher`].
+///
+/// `EntityHashMap<EntityId>` implements [`EntityMapper`], so it can be used
+/// to translate ids between worlds.
+pub type EntityHashMap<V> = HashMap<EntityId, V, EntityIdHasher>;
+
+/// A [`HashSet`] of [`EntityId`]s, using [`EntityIdHasher`].
+pub type EntityHashSet = HashSet<EntityId, EntityIdHasher>;
+
+/// A hasher that specializes in hashing [`EntityId`]s.
+///
+/// Much faster than the default hasher, but not resistant to HashDoS.
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EntityIdHasher {
+    inner: u64,
+}
+
+/// Trait for mapping entity ids, such as from one world to another.
+pub trait EntityMapper {
+    /// Returns the id that `entity` maps to.
+    fn map(&mut self, entity: EntityId) -> EntityId;
+}
+
+/// Trait for types that contain entity ids.
+///
+/// Components that implement `MapEntities` should add
+/// `#[component(map_entities)]` to their derive, so their ids can be remapped
+/// through an entity with
+/// [`EntityMut::map_entities`](super::EntityMut::map_entities).
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #
+/// #[derive(Component)]
+/// #[component(map_entities)]
+/// struct Target(EntityId);
+///
+/// impl MapEntities for Target {
+///     fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+///         self.0.map_entities(mapper);
+///     }
+/// }
+/// ```
+pub trait MapEntities {
+    /// Maps the entity ids in this value.
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper);
+}
+
+impl Hasher for EntityIdHasher {
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        self.inner
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        unimplemented!(
+            "attempted to hash a non-`EntityId` with `EntityIdHasher`"
+        );
+    }
+
+    #[inline(always)]
+    fn write_u64(&mut self, i: u64) {
+        // keeps the index in the lower bits while mixing it into the upper
+        // bits, which hash tables use to filter buckets
+        const UPPER_PHI: u64 = 0x9e37_79b9_0000_0001;
+
+        self.inner = i.wrapping_mul(UPPER_PHI);
+    }
+}
+
+impl BuildHasher for EntityIdHasher {
+    type Hasher = Self;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        *self
+    }
+}
+
+/// Ids that aren't in the map are left unchanged.
+impl<S: BuildHasher> EntityMapper for HashMap<EntityId, EntityId, S> {
+    fn map(&mut self, entity: EntityId) -> EntityId {
+        self.get(&entity).copied().unwrap_or(entity)
+    }
+}
+
+impl<F: FnMut(EntityId) -> EntityId> EntityMapper for F {
+    fn map(&mut self, entity: EntityId) -> EntityId {
+        self(entity)
+    }
+}
+
+impl MapEntities for EntityId {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        *self = mapper.map(*self);
+    }
+}
+
+impl<T: MapEntities> MapEntities for Option<T> {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        if let Some(value) = self {
+            value.map_entities(mapper);
+        }
+    }
+}
+
+impl<T: MapEntities> MapEntities for [T] {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        for value in self {
+            value.map_entities(mapper);
+        }
+    }
+}
+
+impl<T: MapEntities, const N: usize> MapEntities for [T; N] {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        self.as_mut_slice().map_entities(mapper);
+    }
+}
+
+impl<T: MapEntities> MapEntities for Vec<T> {
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        self.as_mut_slice().map_entities(mapper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    #[component(map_entities)]
+    struct Targets(Vec<EntityId>, Option<EntityId>);
+
+    #[derive(Component)]
+    struct Unmapped(EntityId);
+
+    impl MapEntities for Targets {
+        fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+            self.0.map_entities(mapper);
+            self.1.map_entities(mapper);
+        }
+    }
+
+    #[test]
+    fn map_entity_components() {
+        let mut world = World::new();
+        let a = world.spawn(()).id();
+        let b = world.spawn(()).id();
+        let entity =
+            world.spawn((Targets(vec![a, b], Some(a)), Unmapped(a))).id();
+
+        let mut map = HashMap::from([(a, b)]);
+
+        world.entity_mut(entity).unwrap().as_mut().map_entities(&mut map);
+
+        let entity = world.entity(entity).unwrap();
+        let targets = entity.get::<Targets>().unwrap();
+
+        assert_eq!(targets.0, [b, b]);
+        assert_eq!(targets.1, Some(b));
+        assert_eq!(entity.get::<Unmapped>().unwrap().0, a);
+    }
+
+    #[test]
+    fn entity_hash_map() {
+        let mut world = World::new();
+        let entities: Vec<_> = (0..64).map(|_| world.spawn(()).id()).collect();
+
+        world.despawn(entities[0]).unwrap();
+
+        let recycled = world.spawn(()).id();
+        let mut map: EntityHashMap<EntityId> = entities
+            .iter()
+            .rev()
+            .copied()
+            .zip(entities.iter().copied())
+            .collect();
+
+        map.insert(recycled, entities[1]);
+
+        assert_eq!(map.len(), 65);
+        assert_eq!(map.map(entities[63]), entities[0]);
+        assert_eq!(map.map(recycled), entities[1]);
+
+        let set: EntityHashSet = entities.iter().copied().collect();
+
+        assert!(set.contains(&entities[0]));
+        assert!(!set.contains(&recycled));
+    }
+}
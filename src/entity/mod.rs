@@ -1,25 +1,52 @@
 //! Defines entities, the individuals objects in an ECS.
 
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroU32;
 
 use thiserror::Error;
 
 pub(crate) use self::allocator::*;
+pub use self::disabled::*;
+pub use self::entry::*;
+pub use self::filtered::*;
+pub use self::handle::*;
+pub use self::map::*;
+pub use self::name::*;
 pub use self::ptr::*;
 pub use self::reference::*;
+pub(crate) use self::relation::Relations;
+pub use self::relation::*;
+pub use self::tasks::*;
 pub use self::world::*;
 use crate::storage::SparseIndex;
 
 mod allocator;
+mod disabled;
+mod entry;
+mod filtered;
+mod handle;
+mod map;
+mod name;
 mod ptr;
 mod reference;
+mod relation;
+mod tasks;
 #[cfg(test)]
 mod tests;
 mod world;
 
 /// An identifier for an entity in the ECS.
+///
+/// Ids are ordered by [index](EntityId::index), then by
+/// [generation](EntityId::generation). This order is stable and doesn't depend
+/// on the platform, so it can be relied on by serializers and editors.
+///
+/// The [`Display`](fmt::Display) format is the index and generation separated
+/// by `v`, like `3v1`.
 #[repr(C, align(8))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EntityId {
     #[cfg(target_endian = "little")]
     pub(crate) index: u32,
@@ -33,6 +60,17 @@ pub struct EntityId {
 #[error("entity not found: {0:?}")]
 pub struct EntityNotFound(pub EntityId);
 
+/// An error for when several entities can't be borrowed at once.
+#[derive(Debug, Clone, Copy, Error)]
+pub enum ManyEntitiesError {
+    /// Error when an entity doesn't exist in the world.
+    #[error("entity not found: {0:?}")]
+    NotFound(EntityId),
+    /// Error when an entity was requested more than once.
+    #[error("entity requested more than once: {0:?}")]
+    Duplicate(EntityId),
+}
+
 impl EntityId {
     pub(crate) const fn new(index: u32, version: NonZeroU32) -> Self {
         Self { index, version }
@@ -41,6 +79,72 @@ impl EntityId {
     pub(crate) const fn from_index(index: u32) -> Self {
         Self::new(index, unsafe { NonZeroU32::new_unchecked(1) })
     }
+
+    /// Returns the index of this entity.
+    ///
+    /// Indices are reused after an entity is despawned.
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    /// Returns the version of this entity, incremented each time its index is
+    /// reused.
+    pub const fn version(self) -> NonZeroU32 {
+        self.version
+    }
+
+    /// Returns the generation of this entity, the [version](EntityId::version)
+    /// as a `u32`.
+    pub const fn generation(self) -> u32 {
+        self.version.get()
+    }
+
+    /// Packs this id into a `u64`, with the version in the upper 32 bits and
+    /// the index in the lower 32 bits.
+    ///
+    /// The result is never `0` and can be converted back with
+    /// [`EntityId::from_bits`].
+    pub const fn to_bits(self) -> u64 {
+        (self.version.get() as u64) << 32 | self.index as u64
+    }
+
+    /// Unpacks an id from the format of [`EntityId::to_bits`].
+    ///
+    /// Returns `None` if the version is `0`.
+    pub const fn from_bits(bits: u64) -> Option<Self> {
+        match NonZeroU32::new((bits >> 32) as u32) {
+            Some(version) => Some(Self::new(bits as u32, version)),
+            None => None,
+        }
+    }
+}
+
+impl PartialOrd for EntityId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares the index, then the generation. Field order differs between
+/// endiannesses, so this can't be derived.
+impl Ord for EntityId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.version).cmp(&(other.index, other.version))
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.version)
+    }
+}
+
+/// Hashes the [packed bits](EntityId::to_bits) as a single `u64`, which lets
+/// [`EntityIdHasher`] handle them in one step.
+impl Hash for EntityId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.to_bits());
+    }
 }
 
 impl SparseIndex for EntityId {
@@ -0,0 +1,223 @@
+//! Defines [`Name`], a component for looking up entities by name.
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::{fmt, ptr};
+
+use super::{EntityId, EntityMut};
+use crate::component::{Component, HookContext};
+use crate::world::World;
+
+/// A name for an entity.
+///
+/// Named entities can be found with [`World::find_by_name`]. Names don't have
+/// to be unique. Use `With<Name>` to query only named entities.
+///
+/// The index used for lookup is updated when the component is inserted,
+/// replaced or removed. Changing the value through a mutable reference isn't
+/// tracked, so to rename an entity, insert a new name.
+#[derive(Component, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[component(
+    after_insert = index_name,
+    on_replace = unindex_name,
+    before_remove = unindex_name,
+)]
+pub struct Name(Cow<'static, str>);
+
+/// A world-maintained index of entities by their [`Name`].
+#[derive(Debug, Default)]
+pub(crate) struct Names {
+    inner: HashMap<Cow<'static, str>, Vec<EntityId>>,
+}
+
+impl Name {
+    /// Creates a new name.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    /// Returns this name as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Names {
+    /// Creates a new empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entities that were given the name, in insertion order.
+    pub fn get(&self, name: &str) -> &[EntityId] {
+        self.inner.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn insert(&mut self, name: &Name, entity: EntityId) {
+        self.inner.entry(name.0.clone()).or_default().push(entity);
+    }
+
+    fn remove(&mut self, name: &Name, entity: EntityId) {
+        if let Some(entities) = self.inner.get_mut(name.as_str()) {
+            entities.retain(|&other| other != entity);
+
+            if entities.is_empty() {
+                self.inner.remove(name.as_str());
+            }
+        }
+    }
+
+    /// Removes all names.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+/// Returns the name index of the world of an entity.
+///
+/// # Safety
+///
+/// The name index must not be borrowed elsewhere.
+unsafe fn names<'w>(entity: &EntityMut<'w>) -> &'w mut Names {
+    // SAFETY: the name index isn't borrowed by entity references, so this
+    // doesn't alias the components of the entity
+    unsafe { &mut *ptr::addr_of_mut!((*entity.world_ptr().as_raw()).names) }
+}
+
+fn index_name(entity: EntityMut<'_>, _ctx: HookContext) {
+    if let Ok(name) = entity.get::<Name>() {
+        // SAFETY: hooks have exclusive access to the world
+        unsafe { names(&entity) }.insert(name, entity.id());
+    }
+}
+
+fn unindex_name(entity: EntityMut<'_>, _ctx: HookContext) {
+    if let Ok(name) = entity.get::<Name>() {
+        // SAFETY: hooks have exclusive access to the world
+        unsafe { names(&entity) }.remove(name, entity.id());
+    }
+}
+
+impl World {
+    /// Returns the first entity with the [`Name`].
+    pub fn find_by_name(&self, name: &str) -> Option<EntityId> {
+        self.find_all_by_name(name).next()
+    }
+
+    /// Returns an iterator over all entities with the [`Name`].
+    pub fn find_all_by_name<'w>(
+        &'w self,
+        name: &'w str,
+    ) -> impl Iterator<Item = EntityId> + use<'w> {
+        // entries are verified in case a name was replaced in-place
+        self.names.get(name).iter().copied().filter(move |&entity| {
+            self.entity(entity)
+                .ok()
+                .and_then(|entity| entity.get::<Name>().ok())
+                .is_some_and(|other| other.as_str() == name)
+        })
+    }
+}
+
+// ---
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&'static str> for Name {
+    fn from(name: &'static str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Player;
+
+    #[test]
+    fn find_by_name() {
+        let mut world = World::new();
+        let alice = world.spawn((Name::new("alice"), Player)).id();
+        let bob = world.spawn(Name::new("bob")).id();
+
+        assert_eq!(world.find_by_name("alice"), Some(alice));
+        assert_eq!(world.find_by_name("bob"), Some(bob));
+        assert_eq!(world.find_by_name("eve"), None);
+
+        world.despawn(alice).unwrap();
+
+        assert_eq!(world.find_by_name("alice"), None);
+
+        world.entity_mut(bob).unwrap().remove::<Name>().unwrap();
+
+        assert_eq!(world.find_by_name("bob"), None);
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let mut world = World::new();
+        let a = world.spawn(Name::new("enemy")).id();
+        let b = world.spawn(()).id();
+
+        world.entity_mut(b).unwrap().insert(Name::new("enemy"));
+
+        let enemies: Vec<_> = world.find_all_by_name("enemy").collect();
+
+        assert_eq!(enemies, [a, b]);
+    }
+
+    #[test]
+    fn replaced_name_is_not_found() {
+        let mut world = World::new();
+        let mut entity = world.spawn(Name::new("old"));
+
+        *entity.get_mut::<Name>().unwrap() = Name::new("new");
+
+        assert_eq!(world.find_by_name("old"), None);
+    }
+
+    #[test]
+    fn inserting_a_name_renames() {
+        let mut world = World::new();
+        let id = world.spawn(Name::new("old")).id();
+
+        world.entity_mut(id).unwrap().insert(Name::new("new"));
+
+        assert_eq!(world.find_by_name("old"), None);
+        assert_eq!(world.find_by_name("new"), Some(id));
+        assert_eq!(world.names.get("old"), &[]);
+    }
+}
@@ -3,11 +3,19 @@
 
 use std::ptr;
 
-use super::{EntityAddr, EntityId, EntityNotFound, EntityPtr};
-use crate::component::{Component, ComponentNotFound};
-use crate::prelude::ComponentId;
+use super::{EntityAddr, EntityId, EntityMapper, EntityNotFound, EntityPtr};
+use crate::component::{
+    Component,
+    ComponentId,
+    ComponentInfo,
+    ComponentNotFound,
+    ComponentSet,
+    ComponentVTable,
+};
+#[cfg(feature = "reflect")]
+use crate::reflect::Reflect;
 use crate::storage::Table;
-use crate::world::World;
+use crate::world::{World, WorldPtr};
 
 /// A reference to an entity and its components.
 #[derive(Debug, Clone, Copy)]
@@ -48,6 +56,19 @@ impl<'w> EntityRef<'w> {
         Self { ptr, addr }
     }
 
+    /// Creates a new entity reference from the address of an entity.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be alive and located at the address. The world must be
+    /// valid for reads to this entity.
+    pub(crate) unsafe fn from_addr(
+        ptr: EntityPtr<'w>,
+        addr: EntityAddr,
+    ) -> Self {
+        Self { ptr, addr }
+    }
+
     /// Returns the id of this entity.
     pub const fn id(self) -> EntityId {
         self.ptr.id()
@@ -59,9 +80,24 @@ impl<'w> EntityRef<'w> {
         }
     }
 
+    /// Returns the set of components of this entity.
+    pub fn archetype(self) -> &'w ComponentSet {
+        self.table().components()
+    }
+
+    /// Returns an iterator over the info of the components of this entity.
+    pub fn components(self) -> impl Iterator<Item = ComponentInfo> + 'w {
+        self.archetype().iter()
+    }
+
     /// Returns `true` if this entity contains the component.
     pub fn contains<C: Component>(self) -> bool {
-        self.table().components().contains(ComponentId::of::<C>())
+        self.contains_id(ComponentId::of::<C>())
+    }
+
+    /// Returns `true` if this entity contains the component with the id.
+    pub fn contains_id(self, component: ComponentId) -> bool {
+        self.archetype().contains(component)
     }
 
     /// Returns a reference to a component of this entity.
@@ -82,6 +118,20 @@ impl<'w> EntityRef<'w> {
             })
             .ok_or(ComponentNotFound::new::<C>(self.id()))
     }
+
+    /// Returns an iterator over the components of this entity that support
+    /// reflection.
+    #[cfg(feature = "reflect")]
+    pub fn reflect_components(self) -> impl Iterator<Item = &'w dyn Reflect> {
+        let table = self.table();
+
+        table.components().iter().filter_map(move |component| unsafe {
+            // SAFETY: the table contains the component and this entity
+            let ptr = table.get_unchecked(self.addr.row, component.id());
+
+            component.as_reflect()(ptr).map(|reflect| reflect.as_ref())
+        })
+    }
 }
 
 impl<'w> EntityMut<'w> {
@@ -115,11 +165,28 @@ impl<'w> EntityMut<'w> {
         Self { ptr, addr: table }
     }
 
+    /// Creates a new mutable entity reference from the address of an entity.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be alive and located at the address. The world must be
+    /// valid for reads/writes to this entity.
+    pub(crate) unsafe fn from_addr(
+        ptr: EntityPtr<'w>,
+        addr: EntityAddr,
+    ) -> Self {
+        Self { ptr, addr }
+    }
+
     /// Returns the id of this entity.
     pub const fn id(&self) -> EntityId {
         self.ptr.id()
     }
 
+    pub(crate) fn world_ptr(&self) -> WorldPtr<'w> {
+        self.ptr.world()
+    }
+
     fn table_mut(&mut self) -> &mut Table {
         unsafe {
             self.ptr
@@ -136,11 +203,26 @@ impl<'w> EntityMut<'w> {
         unsafe { *ptr::from_ref(self).cast() }
     }
 
+    /// Returns the set of components of this entity.
+    pub fn archetype(&self) -> &'w ComponentSet {
+        self.as_ref().archetype()
+    }
+
+    /// Returns an iterator over the info of the components of this entity.
+    pub fn components(&self) -> impl Iterator<Item = ComponentInfo> + 'w {
+        self.as_ref().components()
+    }
+
     /// Returns `true` if this entity contains the component.
     pub fn contains<C: Component>(&self) -> bool {
         self.as_ref().contains::<C>()
     }
 
+    /// Returns `true` if this entity contains the component with the id.
+    pub fn contains_id(&self, component: ComponentId) -> bool {
+        self.as_ref().contains_id(component)
+    }
+
     /// Returns a reference to a component of this entity.
     ///
     /// Returns an error if the component doesn't exist.
@@ -166,4 +248,48 @@ impl<'w> EntityMut<'w> {
             })
             .ok_or(ComponentNotFound::new::<C>(self.id()))
     }
+
+    /// Maps the entity ids stored in the components of this entity.
+    ///
+    /// Only components with `#[component(map_entities)]` are mapped. See
+    /// [`MapEntities`](super::MapEntities).
+    pub fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        let row = self.addr.row;
+        let table = self.table_mut();
+
+        for component in table.components().clone().iter() {
+            // SAFETY: the table contains the component and this entity
+            unsafe {
+                let ptr = table.get_unchecked_mut(row, component.id());
+
+                component.map_entities()(ptr, mapper);
+            }
+        }
+    }
+
+    /// Returns an iterator over the components of this entity that support
+    /// reflection.
+    #[cfg(feature = "reflect")]
+    pub fn reflect_components_mut(
+        &mut self,
+    ) -> impl Iterator<Item = &'w mut dyn Reflect> + use<'_, 'w> {
+        let row = self.addr.row;
+        // SAFETY: this reference has mutable access to the entity
+        let table = unsafe {
+            self.ptr
+                .world()
+                .as_mut()
+                .components
+                .get_unchecked_mut(self.addr.table)
+        };
+        let components: Vec<_> = table.components().iter().collect();
+
+        components.into_iter().filter_map(move |component| unsafe {
+            // SAFETY: the table contains the component and this entity. Each
+            // component is only yielded once.
+            let ptr = table.get_unchecked_mut(row, component.id());
+
+            component.as_reflect_mut()(ptr).map(|mut reflect| reflect.as_mut())
+        })
+    }
 }
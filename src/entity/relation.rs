@@ -0,0 +1,383 @@
+//! Defines [`Relation`], a component for typed relations between entities.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::{fmt, ptr};
+
+use super::{
+    EntityHashMap,
+    EntityId,
+    EntityMapper,
+    EntityMut,
+    EntityNotFound,
+    EntityWorld,
+    MapEntities,
+};
+use crate::component::{Component, ComponentHooks, ComponentId, HookContext};
+use crate::world::World;
+
+/// Relations of an entity to other entities.
+///
+/// `R` is a marker type for the kind of relation, so an entity can be related
+/// to different targets by `Relation<Likes>` and `Relation<Follows>`. Targets
+/// are added with [`EntityWorld::relate`] and queried with
+/// `&Relation<R>`.
+///
+/// The world keeps an index from each target to the entities related to it,
+/// used by [`World::related`]. The index is updated when the component is
+/// inserted, replaced or removed, and despawned targets are removed from the
+/// relations of all entities related to them. Changing the value through a
+/// mutable reference isn't tracked, so use [`EntityWorld::relate`] and
+/// [`EntityWorld::unrelate`] instead.
+pub struct Relation<R> {
+    targets: Vec<EntityId>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// A world-maintained index of entities by the targets of their relations.
+#[derive(Debug, Default)]
+pub(crate) struct Relations {
+    /// Indexes by the id of their [`Relation`] component.
+    inner: HashMap<ComponentId, RelationIndex>,
+}
+
+#[derive(Debug)]
+struct RelationIndex {
+    sources: EntityHashMap<Vec<EntityId>>,
+    unrelate: Unrelate,
+}
+
+/// Removes a target from the relation of an entity.
+type Unrelate = fn(EntityMut<'_>, EntityId);
+
+impl<R: 'static> Relation<R> {
+    /// Creates a new relation to the targets.
+    pub fn new(targets: impl IntoIterator<Item = EntityId>) -> Self {
+        let mut targets: Vec<_> = targets.into_iter().collect();
+
+        targets.sort_unstable();
+        targets.dedup();
+
+        Self { targets, _marker: PhantomData }
+    }
+
+    /// Returns the targets of this relation.
+    pub fn targets(&self) -> &[EntityId] {
+        &self.targets
+    }
+
+    /// Returns `true` if this relation contains the target.
+    pub fn contains(&self, target: EntityId) -> bool {
+        self.targets.contains(&target)
+    }
+
+    /// Returns the amount of targets of this relation.
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns `true` if this relation has no targets.
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+}
+
+/// # Safety
+///
+/// The id is retrieved with [`ComponentId::of_type`].
+unsafe impl<R: 'static> Component for Relation<R> {
+    const HOOKS: ComponentHooks = ComponentHooks {
+        after_insert: true,
+        on_replace: true,
+        before_remove: true,
+        after_remove: false,
+    };
+
+    fn id() -> ComponentId {
+        ComponentId::of_type::<Self>()
+    }
+
+    fn after_insert(entity: EntityMut<'_>, _ctx: HookContext) {
+        index_relation::<R>(entity);
+    }
+
+    fn on_replace(entity: EntityMut<'_>, _ctx: HookContext) {
+        unindex_relation::<R>(entity);
+    }
+
+    fn before_remove(entity: EntityMut<'_>, _ctx: HookContext) {
+        unindex_relation::<R>(entity);
+    }
+
+    fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+        for target in &mut self.targets {
+            target.map_entities(mapper);
+        }
+    }
+}
+
+impl Relations {
+    /// Creates a new empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entities with a relation to the target.
+    pub fn get(&self, relation: ComponentId, target: EntityId) -> &[EntityId] {
+        self.inner
+            .get(&relation)
+            .and_then(|index| index.sources.get(&target))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn insert<R: 'static>(&mut self, target: EntityId, source: EntityId) {
+        self.inner
+            .entry(Relation::<R>::id())
+            .or_insert_with(|| RelationIndex {
+                sources: EntityHashMap::default(),
+                unrelate: unrelate_target::<R>,
+            })
+            .sources
+            .entry(target)
+            .or_default()
+            .push(source);
+    }
+
+    fn remove(
+        &mut self,
+        relation: ComponentId,
+        target: EntityId,
+        source: EntityId,
+    ) {
+        let Some(index) = self.inner.get_mut(&relation) else {
+            return;
+        };
+
+        if let Some(sources) = index.sources.get_mut(&target) {
+            sources.retain(|&other| other != source);
+
+            if sources.is_empty() {
+                index.sources.remove(&target);
+            }
+        }
+    }
+
+    /// Removes a target from the index, returning the functions to remove it
+    /// from the relations of the entities related to it.
+    fn remove_target(&mut self, target: EntityId) -> Vec<(Unrelate, EntityId)> {
+        self.inner
+            .values_mut()
+            .flat_map(|index| {
+                let unrelate = index.unrelate;
+
+                index
+                    .sources
+                    .remove(&target)
+                    .into_iter()
+                    .flatten()
+                    .map(move |source| (unrelate, source))
+            })
+            .collect()
+    }
+
+    /// Removes all relations.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+/// Returns the relation index of the world of an entity.
+///
+/// # Safety
+///
+/// The relation index must not be borrowed elsewhere.
+unsafe fn relations<'w>(entity: &EntityMut<'w>) -> &'w mut Relations {
+    // SAFETY: the relation index isn't borrowed by entity references, so this
+    // doesn't alias the components of the entity
+    unsafe { &mut *ptr::addr_of_mut!((*entity.world_ptr().as_raw()).relations) }
+}
+
+fn index_relation<R: 'static>(entity: EntityMut<'_>) {
+    if let Ok(relation) = entity.get::<Relation<R>>() {
+        // SAFETY: hooks have exclusive access to the world
+        let relations = unsafe { relations(&entity) };
+
+        for &target in &relation.targets {
+            relations.insert::<R>(target, entity.id());
+        }
+    }
+}
+
+fn unindex_relation<R: 'static>(entity: EntityMut<'_>) {
+    if let Ok(relation) = entity.get::<Relation<R>>() {
+        // SAFETY: hooks have exclusive access to the world
+        let relations = unsafe { relations(&entity) };
+
+        for &target in &relation.targets {
+            relations.remove(Relation::<R>::id(), target, entity.id());
+        }
+    }
+}
+
+fn unrelate_target<R: 'static>(mut entity: EntityMut<'_>, target: EntityId) {
+    if let Ok(relation) = entity.get_mut::<Relation<R>>() {
+        relation.targets.retain(|&other| other != target);
+    }
+}
+
+impl World {
+    /// Returns an iterator over all entities with a [`Relation<R>`] to the
+    /// target.
+    pub fn related<R: 'static>(
+        &self,
+        target: EntityId,
+    ) -> impl Iterator<Item = EntityId> + '_ {
+        self.relations.get(Relation::<R>::id(), target).iter().copied()
+    }
+
+    /// Removes an entity that is about to be despawned from the relations of
+    /// all entities related to it.
+    pub(crate) fn unrelate_target(&mut self, target: EntityId) {
+        for (unrelate, source) in self.relations.remove_target(target) {
+            if let Ok(source) = EntityMut::new(source, self) {
+                unrelate(source, target);
+            }
+        }
+    }
+}
+
+impl EntityWorld<'_> {
+    /// Adds a target to the [`Relation<R>`] of this entity, inserting the
+    /// component if it doesn't exist.
+    ///
+    /// Returns an error if the target doesn't exist.
+    #[track_caller]
+    pub fn relate<R: 'static>(
+        &mut self,
+        target: EntityId,
+    ) -> Result<(), EntityNotFound> {
+        if !self.world().contains(target) {
+            return Err(EntityNotFound(target));
+        }
+
+        if let Ok(relation) = self.get_mut::<Relation<R>>() {
+            if !relation.contains(target) {
+                relation.targets.push(target);
+
+                let id = self.id();
+
+                self.world_mut().relations.insert::<R>(target, id);
+            }
+        } else {
+            self.insert(Relation::<R>::new([target]));
+        }
+
+        Ok(())
+    }
+
+    /// Removes a target from the [`Relation<R>`] of this entity.
+    ///
+    /// Returns `true` if the entity was related to the target. The component
+    /// is kept when its last target is removed.
+    pub fn unrelate<R: 'static>(&mut self, target: EntityId) -> bool {
+        let Ok(relation) = self.get_mut::<Relation<R>>() else {
+            return false;
+        };
+        let len = relation.len();
+
+        relation.targets.retain(|&other| other != target);
+
+        if relation.len() == len {
+            return false;
+        }
+
+        let id = self.id();
+
+        self.world_mut().relations.remove(Relation::<R>::id(), target, id);
+
+        true
+    }
+}
+
+impl<R: 'static> Default for Relation<R> {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl<R> Clone for Relation<R> {
+    fn clone(&self) -> Self {
+        Self { targets: self.targets.clone(), _marker: PhantomData }
+    }
+}
+
+impl<R> fmt::Debug for Relation<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Relation").field(&self.targets).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    struct Likes;
+
+    struct Follows;
+
+    #[test]
+    fn relate_and_query() {
+        let mut world = World::new();
+        let alice = world.spawn(()).id();
+        let bob = world.spawn(()).id();
+        let carol = world.spawn(Relation::<Likes>::new([bob])).id();
+
+        world.entity_mut(alice).unwrap().relate::<Likes>(bob).unwrap();
+        world.entity_mut(alice).unwrap().relate::<Likes>(carol).unwrap();
+        world.entity_mut(alice).unwrap().relate::<Follows>(bob).unwrap();
+
+        let mut likes_bob: Vec<_> = world.related::<Likes>(bob).collect();
+
+        likes_bob.sort();
+
+        assert_eq!(likes_bob, [alice, carol]);
+        assert_eq!(world.related::<Follows>(bob).collect::<Vec<_>>(), [alice]);
+        assert_eq!(
+            world.query::<&Relation<Likes>>().unwrap().iter().count(),
+            2
+        );
+
+        assert!(world.entity_mut(alice).unwrap().unrelate::<Likes>(bob));
+        assert!(!world.entity_mut(alice).unwrap().unrelate::<Likes>(bob));
+        assert_eq!(world.related::<Likes>(bob).collect::<Vec<_>>(), [carol]);
+
+        let dead = world.spawn(()).id();
+
+        world.despawn(dead).unwrap();
+
+        assert!(world
+            .entity_mut(alice)
+            .unwrap()
+            .relate::<Likes>(dead)
+            .is_err());
+    }
+
+    #[test]
+    fn despawn_cleans_up_relations() {
+        let mut world = World::new();
+        let bob = world.spawn(()).id();
+        let carol = world.spawn(()).id();
+        let alice = world.spawn(Relation::<Likes>::new([bob, carol])).id();
+
+        world.despawn(bob).unwrap();
+
+        let relation = world.entity(alice).unwrap().get::<Relation<Likes>>();
+
+        assert_eq!(relation.unwrap().targets(), [carol]);
+
+        world.despawn(alice).unwrap();
+
+        assert_eq!(world.related::<Likes>(carol).count(), 0);
+    }
+}
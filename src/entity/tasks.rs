@@ -0,0 +1,191 @@
+//! Defines [`EntityTasks`], background work tied to the lifetime of an entity.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::{EntityMut, EntityWorld};
+use crate::component::Component;
+
+/// A token shared with a task, signalling that it should stop.
+///
+/// Tasks that don't support being cancelled externally should poll
+/// [`CancelToken::is_cancelled`] and return early once it is set.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Tasks spawned on behalf of an entity.
+///
+/// All tasks are cancelled when this component is removed from its entity
+/// (including when the entity is despawned) or when it is dropped, so that no
+/// background work outlives the entity it refers to.
+#[derive(Component, Default)]
+#[component(before_remove = |mut entity: EntityMut<'_>, _| {
+    if let Ok(tasks) = entity.get_mut::<EntityTasks>() {
+        tasks.cancel();
+    }
+})]
+pub struct EntityTasks {
+    token: CancelToken,
+    handles: Vec<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl CancelToken {
+    /// Creates a new token that isn't cancelled.
+    pub fn new() -> Self {
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        Self { cancelled }
+    }
+
+    /// Returns `true` if this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Cancels this token and all of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+}
+
+impl EntityTasks {
+    /// Creates an empty set of tasks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the token shared by all tasks of this entity.
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+
+    /// Returns the amount of attached task handles.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns `true` if no task handles are attached.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Attaches a task to this entity.
+    ///
+    /// `cancel` is called once when the tasks of this entity are cancelled. It
+    /// should abort the task, for example by calling the cancel method of the
+    /// handle returned by a task pool.
+    pub fn attach(&mut self, cancel: impl FnOnce() + Send + Sync + 'static) {
+        if self.token.is_cancelled() {
+            cancel();
+        } else {
+            self.handles.push(Box::new(cancel));
+        }
+    }
+
+    /// Returns `true` if the tasks of this entity have been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Cancels all tasks of this entity.
+    ///
+    /// Tasks attached afterwards are cancelled immediately.
+    pub fn cancel(&mut self) {
+        self.token.cancel();
+
+        for cancel in self.handles.drain(..) {
+            cancel();
+        }
+    }
+}
+
+impl Drop for EntityTasks {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl fmt::Debug for EntityTasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntityTasks")
+            .field("token", &self.token)
+            .field("handles", &self.handles.len())
+            .finish()
+    }
+}
+
+impl EntityWorld<'_> {
+    /// Returns the tasks of this entity, inserting [`EntityTasks`] if it
+    /// doesn't exist.
+    pub fn tasks(&mut self) -> &mut EntityTasks {
+        if !self.contains::<EntityTasks>() {
+            self.insert(EntityTasks::new());
+        }
+
+        // SAFETY: `EntityTasks` was inserted above
+        unsafe { self.get_mut::<EntityTasks>().unwrap_unchecked() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn despawn_cancels_tasks() {
+        let mut world = World::new();
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        let mut entity = world.spawn(());
+        let token = entity.tasks().token();
+
+        for _ in 0..2 {
+            let cancelled = cancelled.clone();
+
+            entity.tasks().attach(move || {
+                cancelled.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        assert!(!token.is_cancelled());
+
+        entity.despawn();
+
+        assert!(token.is_cancelled());
+        assert_eq!(cancelled.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn remove_cancels_tasks() {
+        let mut world = World::new();
+        let mut entity = world.spawn(EntityTasks::new());
+        let token = entity.tasks().token();
+
+        let tasks = entity.remove::<EntityTasks>().unwrap();
+
+        assert!(tasks.is_cancelled());
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn attach_after_cancel() {
+        let mut tasks = EntityTasks::new();
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        tasks.cancel();
+        tasks.attach({
+            let cancelled = cancelled.clone();
+
+            move || _ = cancelled.fetch_add(1, Ordering::Relaxed)
+        });
+
+        assert!(tasks.is_empty());
+        assert_eq!(cancelled.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,4 +1,7 @@
+use std::mem;
+
 use crate::component::Component;
+use crate::entity::EntityId;
 use crate::world::World;
 
 #[derive(Component)]
@@ -18,3 +21,46 @@ fn spawned_entity_contains_initial_components() {
     assert_eq!(name.0, "Alexandra");
     assert_eq!(age.0, u32::MAX);
 }
+
+#[test]
+fn entity_id_bits() {
+    let mut world = World::new();
+    let first = world.spawn(()).id();
+
+    world.despawn(first).unwrap();
+
+    let second = world.spawn(()).id();
+
+    assert_eq!(first.index(), second.index());
+    assert_ne!(first.to_bits(), second.to_bits());
+
+    for entity in [first, second] {
+        assert_eq!(EntityId::from_bits(entity.to_bits()), Some(entity));
+    }
+
+    assert_eq!(second.to_bits() >> 32, second.version().get() as u64);
+    assert_eq!(EntityId::from_bits(u32::MAX as u64), None);
+    assert_eq!(mem::size_of::<Option<EntityId>>(), 8);
+}
+
+#[test]
+fn entity_id_display_and_order() {
+    let mut world = World::new();
+    let first = world.spawn(()).id();
+    let other = world.spawn(()).id();
+
+    world.despawn(first).unwrap();
+
+    let second = world.spawn(()).id();
+
+    assert_eq!(first.to_string(), "0v1");
+    assert_eq!(second.to_string(), "0v2");
+    assert_eq!(other.to_string(), "1v1");
+    assert_eq!(second.generation(), 2);
+
+    let mut ids = [other, second, first];
+
+    ids.sort();
+
+    assert_eq!(ids, [first, second, other]);
+}
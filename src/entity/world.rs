@@ -1,10 +1,33 @@
 use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::panic::Location;
 use std::ptr::NonNull;
 
-use super::{EntityId, EntityMut, EntityNotFound, EntityRef};
-use crate::component::{Component, ComponentNotFound};
-use crate::prelude::{ComponentInfo, ComponentVTable};
-use crate::world::World;
+use super::{
+    ComponentEntry,
+    EntityAddr,
+    EntityId,
+    EntityMut,
+    EntityNotFound,
+    EntityRef,
+};
+use crate::commands::EntityQueue;
+use crate::component::{
+    Bundle,
+    Component,
+    ComponentHook,
+    ComponentId,
+    ComponentInfo,
+    ComponentNotFound,
+    ComponentSet,
+    ComponentVTable,
+    ComponentWriter,
+    HookContext,
+    OwningPtr,
+    Requirements,
+};
+use crate::storage::CriticalSection;
+use crate::world::{DespawnReason, EntityRecord, World};
 
 /// A borrow of an entity and the world it resides in.
 ///
@@ -12,6 +35,8 @@ use crate::world::World;
 pub struct EntityWorld<'w> {
     id: EntityId,
     world: NonNull<World>,
+    /// Overrides the caller passed to hooks, for queued changes.
+    caller: Option<&'static Location<'static>>,
     _lt: PhantomData<&'w World>,
 }
 
@@ -37,8 +62,55 @@ impl<'w> EntityWorld<'w> {
     /// The entity must be alive in the world.
     pub unsafe fn new_unchecked(id: EntityId, world: &'w mut World) -> Self {
         let world = NonNull::from(world);
+        let caller = None;
 
-        Self { id, world, _lt: PhantomData }
+        Self { id, world, caller, _lt: PhantomData }
+    }
+
+    /// Sets the caller reported to hooks by changes through this entity.
+    pub(crate) const fn with_caller(
+        mut self,
+        caller: &'static Location<'static>,
+    ) -> Self {
+        self.caller = Some(caller);
+
+        self
+    }
+
+    /// Returns the caller to report to hooks.
+    #[track_caller]
+    fn caller(&self) -> &'static Location<'static> {
+        self.caller.unwrap_or(Location::caller())
+    }
+
+    /// Runs a component hook on this entity.
+    fn run_hook(
+        &mut self,
+        hook: ComponentHook,
+        component: ComponentInfo,
+        replace: bool,
+        caller: &'static Location<'static>,
+    ) {
+        let ctx = HookContext::new(self.id, component.id(), replace, caller);
+
+        hook(self.as_mut(), ctx);
+    }
+
+    /// Runs an [`Component::after_insert`] hook on this entity, then updates
+    /// the indexes of the component.
+    fn run_insert_hook(
+        &mut self,
+        hook: ComponentHook,
+        component: ComponentInfo,
+        replace: bool,
+        caller: &'static Location<'static>,
+    ) {
+        self.run_hook(hook, component, replace, caller);
+
+        let id = self.id;
+
+        // SAFETY: the entity is alive for this `EntityWorld` to exist
+        unsafe { self.world_mut().notify_insert(id, component.id()) };
     }
 
     /// Returns the id of this entity.
@@ -56,6 +128,18 @@ impl<'w> EntityWorld<'w> {
         unsafe { self.world.as_mut() }
     }
 
+    /// Returns the set of components of this entity.
+    pub fn archetype(&self) -> &'w ComponentSet {
+        let world = self.world();
+
+        // SAFETY: this entity is alive, so its address is valid
+        unsafe {
+            let addr = world.entities.get(self.id).unwrap_unchecked();
+
+            world.components.get_unchecked(addr.table).components()
+        }
+    }
+
     /// Borrows this entity as an [`EntityRef`].
     pub fn as_ref(&self) -> EntityRef<'w> {
         unsafe { EntityRef::new_unchecked(self.id, self.world()) }
@@ -68,11 +152,21 @@ impl<'w> EntityWorld<'w> {
         unsafe { EntityMut::new_unchecked(self.id, self.world_mut()) }
     }
 
+    /// Returns an iterator over the info of the components of this entity.
+    pub fn components(&self) -> impl Iterator<Item = ComponentInfo> + 'w {
+        self.archetype().iter()
+    }
+
     /// Returns `true` if this entity contains the component.
     pub fn contains<C: Component>(&self) -> bool {
         self.as_ref().contains::<C>()
     }
 
+    /// Returns `true` if this entity contains the component with the id.
+    pub fn contains_id(&self, component: ComponentId) -> bool {
+        self.archetype().contains(component)
+    }
+
     /// Returns a reference to a component of this entity.
     ///
     /// Returns an error if the component doesn't exist.
@@ -92,11 +186,104 @@ impl<'w> EntityWorld<'w> {
     /// Inserts a component into this entity.
     ///
     /// Returns the previous value if there was one.
+    #[track_caller]
     pub fn insert<C: Component>(&mut self, component: C) -> Option<C> {
-        let world = self.world_mut();
-        let info = ComponentInfo::of::<C>();
+        let caller = self.caller();
+        let mut prev = MaybeUninit::<C>::uninit();
+        let replaced = OwningPtr::make(component, |value| {
+            // SAFETY: the pointers are to values of `C`
+            unsafe {
+                self.insert_ptr(
+                    ComponentInfo::of::<C>(),
+                    value,
+                    caller,
+                    Some(NonNull::from(&mut prev).cast()),
+                )
+            }
+        });
+
+        // SAFETY: the previous value was moved into `prev` if it was replaced
+        replaced.then(|| unsafe { prev.assume_init() })
+    }
+
+    /// Inserts a component into this entity by its id, moving it out of a
+    /// type-erased pointer.
+    ///
+    /// Allows inserting components whose type isn't known at compile time,
+    /// such as in scripting layers and deserializers. The previous value is
+    /// dropped if there was one. Otherwise equivalent to
+    /// [`EntityWorld::insert`].
+    ///
+    /// Returns `true` if a previous value was replaced.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// # use worldlines::component::OwningPtr;
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// let mut world = World::new();
+    /// let mut entity = world.spawn(());
+    /// let id = ComponentId::of::<Health>();
+    ///
+    /// // SAFETY: the pointer is to a `Health`
+    /// OwningPtr::make(Health(5), |value| unsafe {
+    ///     entity.insert_by_id(id, value)
+    /// });
+    ///
+    /// assert_eq!(entity.get::<Health>().unwrap().0, 5);
+    ///
+    /// entity.remove_by_id(id).unwrap();
+    ///
+    /// assert!(!entity.contains::<Health>());
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to a value of the component with the id.
+    #[track_caller]
+    pub unsafe fn insert_by_id(
+        &mut self,
+        component: ComponentId,
+        value: OwningPtr<'_>,
+    ) -> bool {
+        let caller = self.caller();
+        let info = ComponentInfo::of_id(component);
+
+        // SAFETY: the caller ensures that the pointer is to a value of the
+        // component
+        unsafe { self.insert_ptr(info, value, caller, None) }
+    }
+
+    /// Inserts a component, moving the previous value into `prev` or dropping
+    /// it if `prev` is `None`.
+    ///
+    /// Returns `true` if a previous value was replaced.
+    ///
+    /// # Safety
+    ///
+    /// The pointers must be to values of the component, and `prev` must be
+    /// valid for writes.
+    #[inline]
+    unsafe fn insert_ptr(
+        &mut self,
+        info: ComponentInfo,
+        value: OwningPtr<'_>,
+        caller: &'static Location<'static>,
+        prev: Option<NonNull<u8>>,
+    ) -> bool {
         let id = info.id();
 
+        // SAFETY: the caller ensures that the pointer is to a value of the
+        // component
+        unsafe { self.world().check_indexes(self.id, id, value.as_ptr()) };
+
+        if self.contains_id(id) {
+            self.run_hook(info.on_replace(), info, true, caller);
+        }
+
+        let world = self.world_mut();
+
         let old_addr =
             unsafe { world.entities.get(self.id).unwrap_unchecked() };
 
@@ -110,70 +297,123 @@ impl<'w> EntityWorld<'w> {
         } {
             // replace
 
+            // SAFETY: the previous value is initialized, and the caller ensures
+            // that the pointers are to values of the component
             unsafe {
                 let old_table =
                     world.components.get_unchecked_mut(old_addr.table);
 
-                Some(old_table.replace(old_addr.row, id, component))
+                if let Some(prev) = prev {
+                    old_table.move_component(old_addr.row, id, prev);
+                    old_table.init_component(old_addr.row, id, value.as_ptr());
+                } else {
+                    old_table.replace_component(
+                        old_addr.row,
+                        id,
+                        value.as_ptr(),
+                    );
+                }
             }
+
+            self.run_insert_hook(info.after_insert(), info, true, caller);
+
+            true
         } else {
             // insert new
 
-            unsafe {
-                let new_components = world
-                    .components
-                    .get_unchecked(old_addr.table)
-                    .components()
-                    .clone()
-                    .and_insert(info);
+            let required = unsafe {
+                let old_components =
+                    world.components.get_unchecked(old_addr.table).components();
+                let required = Requirements::missing(
+                    &ComponentSet::new().and_insert(info),
+                    old_components,
+                );
+                let mut new_components =
+                    old_components.clone().and_insert(info);
+
+                for requirement in &required {
+                    new_components.insert(requirement.info);
+                }
+
+                let section = CriticalSection::new("inserting a component");
                 let new_addr =
                     world.components.realloc(self.id, old_addr, new_components);
+                let new_table =
+                    world.components.get_unchecked_mut(new_addr.table);
 
                 world.entities.set(self.id, new_addr);
-                world.components.get_unchecked_mut(new_addr.table).write(
-                    new_addr.row,
-                    id,
-                    component,
+                new_table.init_component(new_addr.row, id, value.as_ptr());
+
+                for requirement in &required {
+                    (requirement.init)(new_table, new_addr.row);
+                }
+
+                section.end();
+
+                // both tables exist
+                world.listeners.archetype_changed(
+                    &world.components,
+                    self.id,
+                    Some(old_addr.table),
+                    Some(new_addr.table),
                 );
-            }
 
-            C::after_insert(self.as_mut());
+                required
+            };
+
+            self.run_insert_hook(info.after_insert(), info, false, caller);
 
-            None
+            for requirement in required {
+                let hook = requirement.info.after_insert();
+
+                self.run_insert_hook(hook, requirement.info, false, caller);
+            }
+
+            false
         }
     }
 
-    /// Removes a component from this entity.
+    /// Inserts a bundle of components into this entity.
     ///
-    /// Returns an error if this entity doesn't contain the component.
-    pub fn remove<C: Component>(&mut self) -> Result<C, ComponentNotFound> {
-        if self.contains::<C>() {
-            C::before_remove(self.as_mut());
+    /// Components that this entity already contains are replaced, running
+    /// their [`Component::on_replace`] hooks.
+    #[track_caller]
+    pub fn insert_bundle<B: Bundle>(&mut self, bundle: B) {
+        let caller = self.caller();
+        let mut inserted = ComponentSet::new();
 
-            let world = self.world_mut();
-            let info = ComponentInfo::of::<C>();
-            let id = info.id();
+        B::components(&mut inserted);
 
-            let old_addr =
-            // SAFETY: this entity exists
-                unsafe { world.entities.get(self.id).unwrap_unchecked() };
-            let (prev, new_components) = {
-                let old_table =
-                    unsafe { world.components.get_unchecked(old_addr.table) };
-                // SAFETY: the component exists because of the above
-                // `.contains::<C>()`
-                let prev = unsafe {
-                    old_table
-                        .get_unchecked(old_addr.row, id)
-                        .as_ptr()
-                        .cast::<C>()
-                        .read()
-                };
-                let new_components =
-                    old_table.components().clone().and_remove(id);
-
-                (prev, new_components)
-            };
+        let replacing = self.archetype().intersection(&inserted);
+
+        for component in &replacing {
+            self.run_hook(component.on_replace(), component, true, caller);
+        }
+
+        let world = self.world_mut();
+        // SAFETY: this entity is alive, so its address is valid
+        let old_addr =
+            unsafe { world.entities.get(self.id).unwrap_unchecked() };
+        let old_components = unsafe {
+            world.components.get_unchecked(old_addr.table).components().clone()
+        };
+        // components removed by the hooks above are initialized instead
+        let replaced = old_components.intersection(&inserted);
+        let required = Requirements::missing(&inserted, &old_components);
+        let mut new_components = old_components.clone();
+
+        for component in &inserted {
+            new_components.insert(component);
+        }
+
+        for requirement in &required {
+            new_components.insert(requirement.info);
+        }
+
+        let section = CriticalSection::new("inserting a bundle");
+        let addr = if new_components == old_components {
+            old_addr
+        } else {
             // SAFETY: this entity exists in the table at `old_addr`
             let new_addr = unsafe {
                 world.components.realloc(self.id, old_addr, new_components)
@@ -181,14 +421,290 @@ impl<'w> EntityWorld<'w> {
 
             world.entities.set(self.id, new_addr);
 
-            Ok(prev)
+            new_addr
+        };
+        let mut writer = ComponentWriter::new(
+            EntityQueue::new(self.id, &mut world.commands),
+            &mut world.components,
+            addr,
+            replaced,
+            caller,
+        );
+
+        bundle.write(&mut writer);
+        writer.write_requirements(&required);
+        section.end();
+
+        // SAFETY: both tables exist
+        unsafe {
+            world.listeners.archetype_changed(
+                &world.components,
+                self.id,
+                Some(old_addr.table),
+                Some(addr.table),
+            );
+        }
+
+        world.flush();
+    }
+
+    /// Inserts a component into this entity if it doesn't already contain it.
+    ///
+    /// Returns `true` if the component was inserted.
+    #[track_caller]
+    pub fn insert_if_new<C: Component>(&mut self, component: C) -> bool {
+        let new = !self.contains::<C>();
+
+        if new {
+            self.insert(component);
+        }
+
+        new
+    }
+
+    /// Returns an entry for a component of this entity, for in-place
+    /// manipulation.
+    pub fn entry<C: Component>(&mut self) -> ComponentEntry<'_, 'w, C> {
+        ComponentEntry::new(self)
+    }
+
+    /// Removes a component from this entity.
+    ///
+    /// Returns an error if this entity doesn't contain the component.
+    #[track_caller]
+    pub fn remove<C: Component>(&mut self) -> Result<C, ComponentNotFound> {
+        if self.contains::<C>() {
+            let caller = self.caller();
+            let mut prev = None;
+
+            // SAFETY: the entity contains the component, which is read as `C`
+            unsafe {
+                self.remove_ptr(ComponentInfo::of::<C>(), caller, |ptr| {
+                    prev = Some(ptr.cast::<C>().read());
+                });
+
+                Ok(prev.unwrap_unchecked())
+            }
         } else {
             Err(ComponentNotFound::new::<C>(self.id))
         }
     }
 
+    /// Removes and drops a component from this entity by its id.
+    ///
+    /// See [`EntityWorld::insert_by_id`].
+    ///
+    /// Returns an error if this entity doesn't contain the component.
+    #[track_caller]
+    pub fn remove_by_id(
+        &mut self,
+        component: ComponentId,
+    ) -> Result<(), ComponentNotFound> {
+        if self.contains_id(component) {
+            let caller = self.caller();
+            let info = ComponentInfo::of_id(component);
+
+            // SAFETY: the entity contains the component, which is dropped
+            unsafe {
+                self.remove_ptr(info, caller, |ptr| info.drop()(ptr.as_ptr()));
+            }
+
+            Ok(())
+        } else {
+            Err(ComponentNotFound::of_id(self.id, component))
+        }
+    }
+
+    /// Removes a component, passing its value to `take` after the entity was
+    /// moved to its new table.
+    ///
+    /// The value is left in the old table, where it isn't dropped, so `take`
+    /// may panic without corrupting storage.
+    ///
+    /// # Safety
+    ///
+    /// This entity must contain the component. `take` must move out of or
+    /// drop the value.
+    #[inline]
+    unsafe fn remove_ptr(
+        &mut self,
+        info: ComponentInfo,
+        caller: &'static Location<'static>,
+        take: impl FnOnce(NonNull<u8>),
+    ) {
+        let id = info.id();
+
+        self.run_hook(info.before_remove(), info, false, caller);
+
+        let world = self.world_mut();
+
+        let old_addr =
+            // SAFETY: this entity exists
+            unsafe { world.entities.get(self.id).unwrap_unchecked() };
+        let new_components = unsafe {
+            world.components.get_unchecked(old_addr.table).components().clone()
+        }
+        .and_remove(id);
+        // SAFETY: this entity exists in the table at `old_addr`
+        let new_addr = unsafe {
+            world.components.realloc(self.id, old_addr, new_components)
+        };
+
+        world.entities.set(self.id, new_addr);
+
+        // SAFETY: the caller ensures that the component exists, and it wasn't
+        // moved by `realloc`, as it isn't in the new table
+        take(unsafe {
+            world
+                .components
+                .get_unchecked_mut(old_addr.table)
+                .get_unchecked_mut(old_addr.row, id)
+        });
+
+        // SAFETY: both tables exist
+        unsafe {
+            world.listeners.archetype_changed(
+                &world.components,
+                self.id,
+                Some(old_addr.table),
+                Some(new_addr.table),
+            );
+        }
+        self.run_hook(info.after_remove(), info, false, caller);
+    }
+
+    /// Removes all components of this entity that aren't in `B`.
+    #[track_caller]
+    pub fn retain<B: Bundle>(&mut self) {
+        let caller = self.caller();
+        let mut kept = ComponentSet::new();
+
+        B::components(&mut kept);
+
+        for component in self.archetype().clone().iter() {
+            if !kept.contains(component.id()) {
+                let hook = component.before_remove();
+
+                self.run_hook(hook, component, false, caller);
+            }
+        }
+
+        let world = self.world_mut();
+        // SAFETY: this entity is alive, so its address is valid
+        let old_addr =
+            unsafe { world.entities.get(self.id).unwrap_unchecked() };
+        let old_components = unsafe {
+            world.components.get_unchecked(old_addr.table).components().clone()
+        };
+        let new_components = old_components.intersection(&kept);
+
+        if new_components.len() == old_components.len() {
+            return;
+        }
+
+        // SAFETY: this entity exists in the table at `old_addr`
+        let new_addr = unsafe {
+            world.components.realloc(self.id, old_addr, new_components)
+        };
+
+        world.entities.set(self.id, new_addr);
+
+        // dropped after the entity is moved, so that a panic leaks the
+        // remaining components instead of dropping them twice
+        let old_table =
+            unsafe { world.components.get_unchecked_mut(old_addr.table) };
+
+        for component in &old_components {
+            if !kept.contains(component.id()) {
+                // SAFETY: the component is initialized and wasn't moved by
+                // `realloc`, as it isn't in the new table
+                unsafe {
+                    let ptr = old_table
+                        .get_unchecked_mut(old_addr.row, component.id());
+
+                    component.drop()(ptr.as_ptr());
+                }
+            }
+        }
+
+        // SAFETY: both tables exist
+        unsafe {
+            world.listeners.archetype_changed(
+                &world.components,
+                self.id,
+                Some(old_addr.table),
+                Some(new_addr.table),
+            );
+        }
+
+        for component in &old_components {
+            if !kept.contains(component.id()) {
+                let hook = component.after_remove();
+
+                self.run_hook(hook, component, false, caller);
+            }
+        }
+    }
+
+    /// Removes all components of this entity without despawning it.
+    #[track_caller]
+    pub fn clear(&mut self) {
+        self.retain::<()>();
+    }
+
     /// Despawns this entity.
+    #[track_caller]
     pub fn despawn(mut self) {
+        let caller = self.caller();
+        let addr = self.detach(DespawnReason::Explicit, caller);
+        let world = self.world_mut();
+
+        // SAFETY: the entity existed at the address until it was freed
+        unsafe {
+            world.free_row(addr.table, addr.row);
+            world.listeners.archetype_changed(
+                &world.components,
+                self.id,
+                Some(addr.table),
+                None,
+            );
+        }
+    }
+
+    /// Moves this entity and its components out of the world.
+    ///
+    /// See [`World::take_entity`].
+    #[track_caller]
+    pub fn take(mut self) -> EntityRecord {
+        let caller = self.caller();
+        let addr = self.detach(DespawnReason::Taken, caller);
+        let world = self.world_mut();
+
+        // SAFETY: the entity existed at the address until it was freed
+        unsafe {
+            let record =
+                EntityRecord::take(self.id, &mut world.components, addr);
+
+            world.listeners.archetype_changed(
+                &world.components,
+                self.id,
+                Some(addr.table),
+                None,
+            );
+
+            record
+        }
+    }
+
+    /// Runs the removal hooks of this entity, notifies listeners and frees its
+    /// id.
+    ///
+    /// Returns the address of its components, which are left in their table.
+    fn detach(
+        &mut self,
+        reason: DespawnReason,
+        caller: &'static Location<'static>,
+    ) -> EntityAddr {
         let world = self.world_mut();
         let (addr, components) = unsafe {
             // SAFETY: for this `EntityWorld` to exist, it must be a valid
@@ -204,15 +720,16 @@ impl<'w> EntityWorld<'w> {
         for component in &components {
             let hook = component.before_remove();
 
-            hook(self.as_mut());
+            self.run_hook(hook, component, false, caller);
         }
 
-        // SAFETY: same as above, the address is valid
-        let table = unsafe { world.components.get_unchecked_mut(addr.table) };
+        // SAFETY: the entity is alive until it is freed below
+        unsafe { world.notify_despawn(self.id, reason) };
+        world.unrelate_target(self.id);
 
         _ = world.entities.free(self.id);
-        // SAFETY: same as above, the entity exists
-        unsafe { table.free(addr.row) };
+
+        addr
     }
 }
 
@@ -237,6 +754,63 @@ mod tests {
         assert_eq!(entity.get::<B>().unwrap().0, 321);
     }
 
+    #[test]
+    fn insert_bundle() {
+        let mut world = World::new();
+        let mut entity = world.spawn(A(123));
+
+        entity.insert_bundle((A(1), B(2)));
+
+        assert_eq!(entity.get::<A>().unwrap().0, 1);
+        assert_eq!(entity.get::<B>().unwrap().0, 2);
+
+        entity.insert_bundle(B(3));
+
+        assert_eq!(entity.get::<A>().unwrap().0, 1);
+        assert_eq!(entity.get::<B>().unwrap().0, 3);
+    }
+
+    #[test]
+    fn insert_if_new() {
+        let mut world = World::new();
+        let mut entity = world.spawn(A(123));
+
+        assert!(!entity.insert_if_new(A(0)));
+        assert!(entity.insert_if_new(B(321)));
+        assert_eq!(entity.get::<A>().unwrap().0, 123);
+        assert_eq!(entity.get::<B>().unwrap().0, 321);
+    }
+
+    #[test]
+    fn entry() {
+        let mut world = World::new();
+        let mut entity = world.spawn(A(1));
+
+        entity.entry::<A>().and_modify(|a| a.0 += 1).or_insert(A(0));
+        entity.entry::<B>().and_modify(|b| b.0 += 1).or_insert(B(0));
+
+        assert_eq!(entity.get::<A>().unwrap().0, 2);
+        assert_eq!(entity.get::<B>().unwrap().0, 0);
+    }
+
+    #[test]
+    fn retain_and_clear() {
+        let mut world = World::new();
+        let mut entity = world.spawn((A(123), B(321)));
+
+        entity.retain::<A>();
+
+        assert_eq!(entity.get::<A>().unwrap().0, 123);
+        assert!(!entity.contains::<B>());
+
+        entity.clear();
+
+        let id = entity.id();
+
+        assert!(!entity.contains::<A>());
+        assert!(world.contains(id));
+    }
+
     #[test]
     fn remove() {
         let mut world = World::new();
@@ -247,4 +821,100 @@ mod tests {
         assert_eq!(entity.get::<A>().unwrap().0, 123);
         assert!(entity.get::<B>().is_err());
     }
+
+    #[test]
+    fn insert_and_remove_by_id() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Component)]
+        struct Tracked(Arc<AtomicUsize>);
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let id = ComponentId::of::<Tracked>();
+        let mut world = World::new();
+        let mut entity = world.spawn(A(123));
+
+        for replaced in [false, true] {
+            let value = Tracked(drops.clone());
+
+            // SAFETY: the pointer is to a `Tracked`
+            OwningPtr::make(value, |value| unsafe {
+                assert_eq!(entity.insert_by_id(id, value), replaced);
+            });
+        }
+
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert!(entity.contains::<Tracked>());
+
+        entity.remove_by_id(id).unwrap();
+
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+        assert!(entity.remove_by_id(id).is_err());
+        assert_eq!(entity.get::<A>().unwrap().0, 123);
+    }
+
+    #[test]
+    fn panicking_drop_is_not_repeated() {
+        use std::panic::{self, AssertUnwindSafe};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Component)]
+        struct Fragile {
+            drops: Arc<AtomicUsize>,
+            panics: bool,
+        }
+
+        impl Drop for Fragile {
+            fn drop(&mut self) {
+                self.drops.fetch_add(1, Ordering::Relaxed);
+
+                if self.panics {
+                    panic!("dropped a fragile component");
+                }
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let fragile = |panics| Fragile { drops: drops.clone(), panics };
+        let id = ComponentId::of::<Fragile>();
+        let mut world = World::new();
+        let entity = world.spawn(fragile(true)).id();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut entity = world.entity_mut(entity).unwrap();
+
+            // SAFETY: the pointer is to a `Fragile`
+            OwningPtr::make(fragile(false), |value| unsafe {
+                entity.insert_by_id(id, value)
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert!(
+            !world.entity(entity).unwrap().get::<Fragile>().unwrap().panics
+        );
+
+        world.entity_mut(entity).unwrap().insert(fragile(true)).unwrap();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            world.entity_mut(entity).unwrap().clear();
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+        assert!(!world.entity(entity).unwrap().contains::<Fragile>());
+
+        drop(world);
+
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
 }
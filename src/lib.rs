@@ -6,22 +6,34 @@
 extern crate self as worldlines;
 
 pub mod access;
+pub mod assets;
 pub mod commands;
 pub mod component;
 pub mod entity;
+pub mod marker;
 pub mod query;
+#[cfg(feature = "reflect")]
+pub mod reflect;
 pub mod resource;
+#[cfg(feature = "spatial")]
+pub mod spatial;
 mod storage;
 pub mod system;
 pub mod world;
 /// Re-export of all items in this crate.
 pub mod prelude {
     pub use crate::access::*;
+    pub use crate::assets::*;
     pub use crate::commands::*;
     pub use crate::component::*;
     pub use crate::entity::*;
+    pub use crate::marker::*;
     pub use crate::query::*;
+    #[cfg(feature = "reflect")]
+    pub use crate::reflect::*;
     pub use crate::resource::*;
+    #[cfg(feature = "spatial")]
+    pub use crate::spatial::*;
     pub use crate::system::*;
     pub use crate::world::*;
 }
@@ -0,0 +1,87 @@
+//! Thread-safety bounds that are relaxed by the `single_threaded` feature.
+//!
+//! With the feature enabled, [`MaybeSend`] and [`MaybeSendSync`] are
+//! implemented for all types, so components, resources and commands can
+//! contain types like [`Rc`](std::rc::Rc), such as for JS interop on
+//! `wasm32-unknown-unknown`. Without it, they are aliases of [`Send`] and
+//! `Send + Sync`.
+
+/// [`Send`], unless the `single_threaded` feature is enabled.
+#[cfg(not(feature = "single_threaded"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(feature = "single_threaded"))]
+impl<T: ?Sized + Send> MaybeSend for T {}
+
+/// [`Send`], unless the `single_threaded` feature is enabled.
+#[cfg(feature = "single_threaded")]
+pub trait MaybeSend {}
+
+#[cfg(feature = "single_threaded")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// [`Send`] and [`Sync`], unless the `single_threaded` feature is enabled.
+#[cfg(not(feature = "single_threaded"))]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(not(feature = "single_threaded"))]
+impl<T: ?Sized + Send + Sync> MaybeSendSync for T {}
+
+/// [`Send`] and [`Sync`], unless the `single_threaded` feature is enabled.
+#[cfg(feature = "single_threaded")]
+pub trait MaybeSendSync {}
+
+#[cfg(feature = "single_threaded")]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+/// A marker field for types that own components, resources or commands.
+///
+/// Makes the containing type neither [`Send`] nor [`Sync`] when the
+/// `single_threaded` feature is enabled.
+#[cfg(not(feature = "single_threaded"))]
+pub(crate) type ThreadMarker = std::marker::PhantomData<()>;
+
+/// A marker field for types that own components, resources or commands.
+///
+/// Makes the containing type neither [`Send`] nor [`Sync`] when the
+/// `single_threaded` feature is enabled.
+#[cfg(feature = "single_threaded")]
+pub(crate) type ThreadMarker = std::marker::PhantomData<*const ()>;
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "single_threaded")]
+    #[test]
+    fn single_threaded_rc() {
+        use std::rc::Rc;
+
+        use crate::prelude::*;
+
+        #[derive(Component)]
+        struct Shared(Rc<u32>);
+
+        #[derive(Resource)]
+        struct SharedRes(Rc<u32>);
+
+        let value = Rc::new(3);
+        let mut world = World::new();
+        let entity = world.spawn(Shared(value.clone())).id();
+
+        world.create(SharedRes(value.clone()));
+
+        let captured = value.clone();
+        let mut commands = Commands::new();
+
+        commands.push(move |world: &mut World| {
+            world.create(SharedRes(captured));
+        });
+        commands.apply(&mut world);
+
+        assert_eq!(
+            *world.entity(entity).unwrap().get::<Shared>().unwrap().0,
+            3
+        );
+        assert_eq!(*world.resource::<SharedRes>().unwrap().0, 3);
+        assert_eq!(Rc::strong_count(&value), 3);
+    }
+}
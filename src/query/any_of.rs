@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+
+use super::{QueryData, ReadOnlyQueryData};
+use crate::access::WorldAccess;
+use crate::component::ComponentSet;
+use crate::entity::EntityPtr;
+
+/// Query data that matches entities containing at least one of the components
+/// borrowed by a tuple of query data, like `AnyOf<(&Sprite, &Mesh)>`.
+///
+/// Yields a tuple of options, one for each query data, which are `Some` for
+/// the components the entity contains. Each query data must be a component
+/// borrow, such as `&C` or `&mut C`.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Sprite;
+///
+/// #[derive(Component)]
+/// struct Mesh;
+///
+/// let mut world = World::new();
+///
+/// world.spawn(Sprite);
+/// world.spawn(Mesh);
+/// world.spawn((Sprite, Mesh));
+/// world.spawn(());
+///
+/// let query = world.query::<AnyOf<(&Sprite, &Mesh)>>().unwrap();
+///
+/// assert_eq!(query.iter().count(), 3);
+/// assert_eq!(
+///     query
+///         .iter()
+///         .filter(|(sprite, mesh)| sprite.is_some() && mesh.is_some())
+///         .count(),
+///     1,
+/// );
+/// ```
+pub struct AnyOf<T> {
+    _marker: PhantomData<T>,
+}
+
+macro_rules! any_of_impl {
+    ($($d:ident),*) => {
+        any_of_impl!([] [$($d)*]);
+    };
+
+    ([$($d:ident)+] []) => {
+        /// # Safety
+        ///
+        /// The access is that of an optional borrow of each query data, which
+        /// is how they are accessed.
+        unsafe impl<$($d),+> QueryData for AnyOf<($($d,)+)>
+        where
+            $($d: QueryData, Option<$d>: QueryData,)+
+        {
+            type Output<'w> = ($(<Option<$d> as QueryData>::Output<'w>,)+);
+
+            fn world_access(access: &mut WorldAccess) {
+                let mut any_of = ComponentSet::new();
+
+                $(
+                    let mut required = WorldAccess::new();
+
+                    $d::world_access(&mut required);
+                    any_of = any_of.union(required.required_components());
+                    <Option<$d>>::world_access(access);
+                )+
+
+                access.requires_any_of(any_of);
+            }
+
+            unsafe fn get(entity: EntityPtr<'_>) -> Self::Output<'_> {
+                // SAFETY: the caller ensures that the entity is valid for the
+                // access of each optional query data
+                ($(unsafe { <Option<$d>>::get(entity) },)+)
+            }
+        }
+
+        /// # Safety
+        ///
+        /// Each optional query data only performs immutable access.
+        unsafe impl<$($d),+> ReadOnlyQueryData for AnyOf<($($d,)+)>
+        where
+            $($d: QueryData, Option<$d>: ReadOnlyQueryData,)+
+        {
+        }
+    };
+
+    ([$($rest:ident)*] []) => {};
+
+    ([$($rest:ident)*] [$head:ident $($tail:ident)*]) => {
+        any_of_impl!([$($rest)*] []);
+        any_of_impl!([$($rest)* $head] [$($tail)*]);
+    };
+}
+
+any_of_impl!(D0, D1, D2, D3, D4, D5, D6, D7);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Sprite(u32);
+
+    #[derive(Component)]
+    struct Mesh(u32);
+
+    #[derive(Component)]
+    struct Hidden;
+
+    #[test]
+    fn any_of() {
+        let mut world = World::new();
+
+        world.spawn(Sprite(1));
+        world.spawn(Mesh(2));
+        world.spawn((Sprite(3), Mesh(4)));
+        world.spawn((Sprite(5), Hidden));
+        world.spawn(Hidden);
+
+        let mut query = world
+            .query_mut::<(AnyOf<(&mut Sprite, &Mesh)>, Without<Hidden>)>()
+            .unwrap();
+        let mut ids: Vec<_> = query
+            .iter_mut()
+            .map(|((sprite, mesh), ())| {
+                let sprite = sprite.map(|sprite| {
+                    sprite.0 *= 10;
+                    sprite.0
+                });
+
+                (sprite, mesh.map(|mesh| mesh.0))
+            })
+            .collect();
+
+        ids.sort();
+
+        assert_eq!(
+            ids,
+            [(None, Some(2)), (Some(10), None), (Some(30), Some(4))]
+        );
+
+        drop(query);
+
+        assert!(world.query_mut::<AnyOf<(&Sprite, &mut Sprite)>>().is_err());
+    }
+}
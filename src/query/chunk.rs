@@ -0,0 +1,496 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::slice;
+
+use super::{
+    IncludeDisabled,
+    Query,
+    QueryData,
+    ReadOnlyQueryData,
+    With,
+    Without,
+};
+use crate::access::{Level, WorldAccess};
+use crate::component::Component;
+use crate::prelude::TableId;
+use crate::storage::{SparseIter, Table, TableRow};
+use crate::world::WorldPtr;
+
+/// Trait for query data that can be retrieved as contiguous slices.
+///
+/// Used by [`Query::iter_chunks`] for processing components a table at a time,
+/// such as with SIMD. Implemented for `&C`, `&mut C`, filters and tuples of
+/// these.
+///
+/// # Safety
+///
+/// [`ChunkData::get_chunk`] must only access data set in
+/// [`QueryData::world_access`].
+pub unsafe trait ChunkData: QueryData {
+    /// The type of the output chunk.
+    type Chunk<'w>;
+
+    /// Returns the query output for a chunk of entities.
+    ///
+    /// # Safety
+    ///
+    /// The access of this query data must have been validated. The chunk must
+    /// be valid for the described access. All components that are required by
+    /// [`QueryData::world_access`] must be present in the table of the chunk.
+    unsafe fn get_chunk(chunk: TableChunk<'_>) -> Self::Chunk<'_>;
+}
+
+/// A run of consecutive occupied rows in a table.
+///
+/// Passed to [`ChunkData::get_chunk`].
+#[derive(Debug, Clone, Copy)]
+pub struct TableChunk<'w> {
+    world: WorldPtr<'w>,
+    table: TableId,
+    start: TableRow,
+    len: usize,
+}
+
+/// An iterator over chunks of query data.
+///
+/// Created by [`Query::iter_chunks`] and [`Query::iter_chunks_mut`].
+pub struct ChunkIter<'w, 's, D: ChunkData> {
+    world: WorldPtr<'w>,
+    tables: SparseIter<'s, TableId>,
+    /// The current table.
+    table: Option<TableId>,
+    /// The current row in the table.
+    row: TableRow,
+    _marker: PhantomData<D>,
+}
+
+impl<'w, D: ChunkData> Query<'w, D> {
+    /// Returns an iterator over contiguous chunks of query data.
+    ///
+    /// Each chunk is a run of consecutive entities in a table, so `&C` is
+    /// retrieved as `&[C]`. Tables are split at rows left empty by removed
+    /// entities until they are compacted by [`World::maintain`], after which
+    /// each table is a single chunk. Slices are borrowed from component
+    /// storage without copying, and the first chunk of each table is aligned
+    /// to [`Component::COLUMN_ALIGN`].
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    ///
+    /// [`World::maintain`]: crate::world::World::maintain
+    pub fn iter_chunks(&self) -> ChunkIter<'w, '_, D>
+    where
+        D: ReadOnlyQueryData,
+    {
+        ChunkIter::new(self.world, self.tables.iter())
+    }
+
+    /// Returns an iterator over contiguous chunks of query data.
+    ///
+    /// See [`Query::iter_chunks`].
+    pub fn iter_chunks_mut(&mut self) -> ChunkIter<'w, '_, D> {
+        ChunkIter::new(self.world, self.tables.iter())
+    }
+}
+
+impl<D: ReadOnlyQueryData> Query<'_, D> {
+    /// Appends a component of every matched entity to a vector, in the order
+    /// of [`Query::iter`].
+    ///
+    /// Components are copied a table at a time instead of an entity at a time,
+    /// such as to upload instance data to a GPU buffer each frame.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// #[derive(Component, Clone, Copy)]
+    /// struct Instance([f32; 4]);
+    ///
+    /// let mut world = World::new();
+    ///
+    /// for i in 0..3 {
+    ///     world.spawn(Instance([i as f32; 4]));
+    /// }
+    ///
+    /// let mut instances: Vec<Instance> = Vec::new();
+    ///
+    /// world.query::<&Instance>().unwrap().copy_into(&mut instances);
+    ///
+    /// assert_eq!(instances.len(), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query data doesn't borrow the component or if a matched
+    /// entity doesn't contain it.
+    pub fn copy_into<C: Component + Copy>(&self, out: &mut Vec<C>) {
+        let mut access = WorldAccess::new();
+        let mut read = WorldAccess::new();
+
+        D::world_access(&mut access);
+        read.borrows_component::<C>(Level::Read);
+
+        assert!(
+            access.signature().allows(&read.signature()),
+            "query {} doesn't borrow {}",
+            type_name::<D>(),
+            type_name::<C>(),
+        );
+
+        out.reserve(self.len());
+
+        for &table in &self.tables {
+            // SAFETY: reads to ECS metadata should always be valid
+            let table =
+                unsafe { self.world.as_ref().components.get_unchecked(table) };
+
+            assert!(
+                table.components().contains(C::id()),
+                "query {} matches entities without {}",
+                type_name::<D>(),
+                type_name::<C>(),
+            );
+
+            // SAFETY: the query borrows `C` and is read-only, so there are no
+            // mutable borrows, and the vector has capacity for every entity
+            unsafe {
+                let dst = out.as_mut_ptr().add(out.len());
+
+                table.copy_column_to(
+                    C::id(),
+                    NonNull::new_unchecked(dst).cast(),
+                );
+                out.set_len(out.len() + table.len());
+            }
+        }
+    }
+}
+
+impl<'w> TableChunk<'w> {
+    fn table(self) -> &'w Table {
+        // SAFETY: reads to ECS metadata should always be valid
+        unsafe { self.world.as_ref().components.get_unchecked(self.table) }
+    }
+
+    /// Returns the amount of entities in this chunk.
+    pub const fn len(self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this chunk contains no entities.
+    pub const fn is_empty(self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the entities in this chunk contain the component.
+    pub fn contains<C: Component>(self) -> bool {
+        self.table().components().contains(C::id())
+    }
+
+    /// Returns a pointer to the first component of this chunk.
+    ///
+    /// The components of the chunk follow it contiguously.
+    ///
+    /// # Safety
+    ///
+    /// The entities must contain the component.
+    pub unsafe fn ptr<C: Component>(self) -> NonNull<C> {
+        // SAFETY: the caller ensures that the table contains the component
+        unsafe { self.table().get_unchecked(self.start, C::id()).cast() }
+    }
+}
+
+impl<'w, 's, D: ChunkData> ChunkIter<'w, 's, D> {
+    fn new(world: WorldPtr<'w>, tables: SparseIter<'s, TableId>) -> Self {
+        let table = None;
+        let row = TableRow(0);
+
+        Self { world, tables, table, row, _marker: PhantomData }
+    }
+}
+
+impl<'w, D: ChunkData> Iterator for ChunkIter<'w, '_, D> {
+    type Item = D::Chunk<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let index = if let Some(table) = self.table {
+                table
+            } else {
+                *self.table.get_or_insert(*self.tables.next()?)
+            };
+            let table =
+                unsafe { self.world.as_ref().components.get_unchecked(index) };
+            let rows = table.rows_len();
+
+            // skips rows left empty by removed entities
+            while self.row.0 < rows && table.entity(self.row).is_none() {
+                self.row.0 += 1;
+            }
+
+            if self.row.0 >= rows {
+                self.table = None;
+                self.row = TableRow(0);
+
+                continue;
+            }
+
+            let start = self.row;
+
+            while self.row.0 < rows && table.entity(self.row).is_some() {
+                self.row.0 += 1;
+            }
+
+            let chunk = TableChunk {
+                world: self.world,
+                table: index,
+                start,
+                len: self.row.0 - start.0,
+            };
+
+            // SAFETY: the query validated the access and the table matches it
+            return Some(unsafe { D::get_chunk(chunk) });
+        }
+    }
+}
+
+/// # Safety
+///
+/// The access declares that it borrows `C`.
+unsafe impl<C: Component> ChunkData for &C {
+    type Chunk<'w> = &'w [C];
+
+    unsafe fn get_chunk(chunk: TableChunk<'_>) -> Self::Chunk<'_> {
+        // SAFETY: the caller ensures that the table contains `C` and that the
+        // chunk is valid for reads to `C`
+        unsafe { slice::from_raw_parts(chunk.ptr::<C>().as_ptr(), chunk.len()) }
+    }
+}
+
+/// # Safety
+///
+/// The access declares that it mutably borrows `C`.
+unsafe impl<C: Component> ChunkData for &mut C {
+    type Chunk<'w> = &'w mut [C];
+
+    unsafe fn get_chunk(chunk: TableChunk<'_>) -> Self::Chunk<'_> {
+        // SAFETY: the caller ensures that the table contains `C` and that the
+        // chunk is valid for reads/writes to `C`
+        unsafe {
+            slice::from_raw_parts_mut(chunk.ptr::<C>().as_ptr(), chunk.len())
+        }
+    }
+}
+
+/// # Safety
+///
+/// The access declares that it may borrow `C`.
+unsafe impl<C: Component> ChunkData for Option<&C> {
+    type Chunk<'w> = Option<&'w [C]>;
+
+    unsafe fn get_chunk(chunk: TableChunk<'_>) -> Self::Chunk<'_> {
+        // SAFETY: the table contains `C`, see above
+        chunk.contains::<C>().then(|| unsafe { <&C>::get_chunk(chunk) })
+    }
+}
+
+/// # Safety
+///
+/// The access declares that it may mutably borrow `C`.
+unsafe impl<C: Component> ChunkData for Option<&mut C> {
+    type Chunk<'w> = Option<&'w mut [C]>;
+
+    unsafe fn get_chunk(chunk: TableChunk<'_>) -> Self::Chunk<'_> {
+        // SAFETY: the table contains `C`, see above
+        chunk.contains::<C>().then(|| unsafe { <&mut C>::get_chunk(chunk) })
+    }
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> ChunkData for With<C> {
+    type Chunk<'w> = ();
+
+    unsafe fn get_chunk(_chunk: TableChunk<'_>) -> Self::Chunk<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> ChunkData for Without<C> {
+    type Chunk<'w> = ();
+
+    unsafe fn get_chunk(_chunk: TableChunk<'_>) -> Self::Chunk<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl ChunkData for IncludeDisabled {
+    type Chunk<'w> = ();
+
+    unsafe fn get_chunk(_chunk: TableChunk<'_>) -> Self::Chunk<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: ?Sized> ChunkData for PhantomData<T> {
+    type Chunk<'w> = Self;
+
+    unsafe fn get_chunk(_chunk: TableChunk<'_>) -> Self::Chunk<'_> {
+        PhantomData
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Position(f32);
+
+    #[derive(Component)]
+    struct Velocity(f32);
+
+    #[derive(Component)]
+    struct Frozen;
+
+    #[derive(Component, Debug, Clone, Copy, PartialEq)]
+    #[component(align = 64)]
+    struct Mass(f32);
+
+    #[test]
+    fn iter_chunks() {
+        let mut world = World::new();
+        let entities: Vec<_> = world
+            .spawn_iter((0..6).map(|i| (Position(i as f32), Velocity(1.0))))
+            .collect();
+
+        world.spawn((Position(0.0), Velocity(1.0), Frozen));
+        world.spawn(Position(0.0));
+        world.despawn(entities[2]).unwrap();
+
+        let mut query = world
+            .query_mut::<(&mut Position, &Velocity, Without<Frozen>)>()
+            .unwrap();
+        let mut lens = Vec::new();
+
+        for (positions, velocities, ()) in query.iter_chunks_mut() {
+            lens.push(positions.len());
+
+            for (position, velocity) in positions.iter_mut().zip(velocities) {
+                position.0 += velocity.0;
+            }
+        }
+
+        // the table is split at the despawned entity
+        assert_eq!(lens, [2, 3]);
+
+        drop(query);
+
+        let sum: f32 = world
+            .query::<&Position>()
+            .unwrap()
+            .iter_chunks()
+            .flatten()
+            .map(|position| position.0)
+            .sum();
+
+        assert_eq!(
+            sum,
+            (0..6).filter(|&i| i != 2).map(|i| i as f32 + 1.0).sum()
+        );
+    }
+
+    #[test]
+    fn aligned_columns() {
+        let mut world = World::new();
+        let entities: Vec<_> =
+            world.spawn_iter((0..5).map(|i| Mass(i as f32))).collect();
+
+        world.spawn((Mass(5.0), Frozen));
+        world.despawn(entities[1]).unwrap();
+        world.maintain(Duration::MAX);
+
+        assert_eq!(Mass::COLUMN_ALIGN, 64);
+        assert_eq!(ComponentInfo::of::<Mass>().column_align(), 64);
+        assert_eq!(ComponentInfo::of::<Frozen>().column_align(), 1);
+
+        let query = world.query::<&Mass>().unwrap();
+        let chunks: Vec<_> = query.iter_chunks().collect();
+
+        // compacted tables are a single chunk
+        assert_eq!(chunks.len(), 2);
+
+        for chunk in chunks {
+            assert_eq!(chunk.as_ptr() as usize % 64, 0);
+        }
+
+        let (_, table) = world
+            .components
+            .tables()
+            .find(|(_, table)| table.len() == 4)
+            .unwrap();
+        // SAFETY: the world isn't borrowed mutably
+        let masses = unsafe { table.as_slice::<Mass>() }.unwrap();
+
+        assert_eq!(masses.len(), 4);
+        assert!(masses.contains(&Mass(4.0)));
+        assert!(!masses.contains(&Mass(1.0)));
+    }
+
+    #[test]
+    fn copy_into() {
+        let mut world = World::new();
+        let entities: Vec<_> =
+            world.spawn_iter((0..6).map(|i| Mass(i as f32))).collect();
+
+        world.spawn((Mass(6.0), Frozen));
+        world.spawn(Frozen);
+        world.despawn(entities[2]).unwrap();
+
+        let query = world.query::<(&Mass, Without<Frozen>)>().unwrap();
+        let mut masses = vec![Mass(-1.0)];
+
+        query.copy_into(&mut masses);
+
+        assert_eq!(
+            masses,
+            [-1.0, 0.0, 1.0, 3.0, 4.0, 5.0].map(Mass),
+            "holes are skipped",
+        );
+        assert_eq!(
+            masses[1..],
+            query.iter().map(|(mass, ())| *mass).collect::<Vec<_>>(),
+        );
+
+        let (_, table) = world
+            .components
+            .tables()
+            .find(|(_, table)| table.len() == 5)
+            .unwrap();
+        let mut bytes = [0; 20];
+
+        // SAFETY: the world isn't borrowed mutably and `f32` has no padding
+        let copied = unsafe { table.copy_column_into(Mass::id(), &mut bytes) };
+
+        assert_eq!(copied, Some(20));
+        assert_eq!(bytes[12..16], 4.0f32.to_ne_bytes());
+        // SAFETY: see above
+        assert_eq!(
+            unsafe { table.copy_column_into(Frozen::id(), &mut []) },
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic = "doesn't borrow"]
+    fn copy_into_requires_borrow() {
+        let world = World::new();
+
+        world.query::<With<Mass>>().unwrap().copy_into(&mut Vec::<Mass>::new());
+    }
+}
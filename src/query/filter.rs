@@ -0,0 +1,275 @@
+use std::marker::PhantomData;
+
+use super::{QueryData, ReadOnlyQueryData};
+use crate::access::WorldAccess;
+use crate::component::Component;
+use crate::entity::EntityPtr;
+
+/// Query data that only matches entities containing `C`.
+///
+/// Doesn't borrow the component, so it can be combined with queries that
+/// access every component, such as `(EntityMut, With<Enemy>)`.
+pub struct With<C: Component> {
+    _marker: PhantomData<C>,
+}
+
+/// Query data that only matches entities that don't contain `C`.
+pub struct Without<C: Component> {
+    _marker: PhantomData<C>,
+}
+
+/// Query data that matches entities matched by at least one query data of a
+/// tuple, like `Or<(With<Sprite>, With<Mesh>)>`.
+///
+/// Only the components that each query data requires of matched entities are
+/// used, nothing is borrowed. Combine with [`And`] and [`Not`] for more
+/// complex predicates.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Sprite;
+///
+/// #[derive(Component)]
+/// struct Mesh;
+///
+/// #[derive(Component)]
+/// struct Hidden;
+///
+/// let mut world = World::new();
+///
+/// world.spawn(Sprite);
+/// world.spawn((Sprite, Hidden));
+/// world.spawn((Mesh, Hidden));
+/// world.spawn(Hidden);
+///
+/// let query = world
+///     .query::<Or<(And<(With<Sprite>, Not<With<Hidden>>)>, With<Mesh>)>>()
+///     .unwrap();
+///
+/// assert_eq!(query.len(), 2);
+/// ```
+pub struct Or<T> {
+    _marker: PhantomData<T>,
+}
+
+/// Query data that matches entities matched by all query data of a tuple.
+///
+/// Equivalent to the tuple itself, but doesn't borrow anything, so it can be
+/// used to group filters within an [`Or`] or [`Not`].
+pub struct And<T> {
+    _marker: PhantomData<T>,
+}
+
+/// Query data that matches entities that aren't matched by a query data, like
+/// `Not<Or<(With<A>, With<B>)>>`.
+///
+/// Nothing is borrowed.
+pub struct Not<T> {
+    _marker: PhantomData<T>,
+}
+
+/// Query data that also matches [`Disabled`](crate::entity::Disabled)
+/// entities.
+///
+/// Queries skip disabled entities by default. Combine with other query data to
+/// opt in, like `(&Hp, IncludeDisabled)`.
+pub struct IncludeDisabled;
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> QueryData for With<C> {
+    type Output<'w> = ();
+
+    fn world_access(access: &mut WorldAccess) {
+        access.requires_component::<C>();
+    }
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> ReadOnlyQueryData for With<C> {}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> QueryData for Without<C> {
+    type Output<'w> = ();
+
+    fn world_access(access: &mut WorldAccess) {
+        access.excludes_component::<C>();
+    }
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<C: Component> ReadOnlyQueryData for Without<C> {}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl QueryData for IncludeDisabled {
+    type Output<'w> = ();
+
+    fn world_access(access: &mut WorldAccess) {
+        access.includes_disabled();
+    }
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl ReadOnlyQueryData for IncludeDisabled {}
+
+macro_rules! or_impl {
+    ($($d:ident),*) => {
+        or_impl!([] [$($d)*]);
+    };
+
+    ([$($d:ident)+] []) => {
+        /// # Safety
+        ///
+        /// Nothing is accessed.
+        unsafe impl<$($d: QueryData),+> QueryData for Or<($($d,)+)> {
+            type Output<'w> = ();
+
+            fn world_access(access: &mut WorldAccess) {
+                let filters = [$({
+                    let mut filter = WorldAccess::new();
+
+                    $d::world_access(&mut filter);
+
+                    filter
+                }),+];
+
+                access.requires_any_match(&filters);
+            }
+
+            unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+        }
+
+        /// # Safety
+        ///
+        /// Nothing is accessed.
+        unsafe impl<$($d: QueryData),+> ReadOnlyQueryData for Or<($($d,)+)> {}
+    };
+
+    ([$($rest:ident)*] []) => {};
+
+    ([$($rest:ident)*] [$head:ident $($tail:ident)*]) => {
+        or_impl!([$($rest)*] []);
+        or_impl!([$($rest)* $head] [$($tail)*]);
+    };
+}
+
+or_impl!(D0, D1, D2, D3, D4, D5, D6, D7);
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<D: QueryData> QueryData for And<D> {
+    type Output<'w> = ();
+
+    fn world_access(access: &mut WorldAccess) {
+        let mut filter = WorldAccess::new();
+
+        D::world_access(&mut filter);
+        access.requires_match(&filter);
+    }
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<D: QueryData> ReadOnlyQueryData for And<D> {}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<D: QueryData> QueryData for Not<D> {
+    type Output<'w> = ();
+
+    fn world_access(access: &mut WorldAccess) {
+        let mut filter = WorldAccess::new();
+
+        D::world_access(&mut filter);
+        access.excludes_match(&filter);
+    }
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {}
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<D: QueryData> ReadOnlyQueryData for Not<D> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Component)]
+    struct C(u32);
+
+    fn matched<F: ReadOnlyQueryData>(world: &World) -> Vec<EntityId> {
+        let mut entities: Vec<_> = world
+            .query::<(EntityId, F)>()
+            .unwrap()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect();
+
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn filter_combinators() {
+        let mut world = World::new();
+
+        let a = world.spawn((A, C(0))).id();
+        let b = world.spawn((B, C(1))).id();
+        let ab = world.spawn((A, B, C(2))).id();
+        let c = world.spawn(C(3)).id();
+
+        assert_eq!(matched::<Or<(With<A>, With<B>)>>(&world), [a, b, ab]);
+        assert_eq!(
+            matched::<
+                Or<(And<(With<A>, Without<B>)>, And<(With<B>, Without<A>)>)>,
+            >(&world),
+            [a, b],
+        );
+        assert_eq!(matched::<Not<Or<(With<A>, With<B>)>>>(&world), [c]);
+        assert_eq!(matched::<Not<And<(With<A>, With<B>)>>>(&world), [a, b, c]);
+
+        // the combined query data isn't borrowed
+        let mut query =
+            world.query_mut::<(&mut C, Not<And<(&A, &mut B)>>)>().unwrap();
+
+        for (c, ()) in query.iter_mut() {
+            c.0 += 10;
+        }
+
+        drop(query);
+
+        assert_eq!(world.entity(ab).unwrap().get::<C>().unwrap().0, 2);
+        assert_eq!(world.entity(c).unwrap().get::<C>().unwrap().0, 13);
+    }
+}
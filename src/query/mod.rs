@@ -4,14 +4,36 @@ use std::any::type_name;
 use std::marker::PhantomData;
 
 use thiserror::Error;
-
+pub use worldlines_macros::QueryData;
+
+pub use self::any_of::*;
+pub use self::chunk::*;
+pub use self::filter::*;
+pub use self::single::*;
+pub use self::sort::*;
+pub use self::state::*;
 use crate::access::{AccessError, Level, WorldAccess};
-use crate::entity::{EntityAddr, EntityId, EntityMut, EntityPtr, EntityRef};
+use crate::component::{Bundle, ComponentSet};
+use crate::entity::{
+    EntityAddr,
+    EntityId,
+    EntityMut,
+    EntityPtr,
+    EntityRef,
+    FilteredEntityMut,
+    FilteredEntityRef,
+};
 use crate::prelude::{Component, TableId};
 use crate::storage::{SparseIter, SparseSet, TableRow};
 use crate::system::{ReadOnlySystemInput, SystemInput};
 use crate::world::{World, WorldPtr};
 
+mod any_of;
+mod chunk;
+mod filter;
+mod single;
+mod sort;
+mod state;
 mod tuple_impl;
 
 /// A query of components of a world.
@@ -19,6 +41,9 @@ pub struct Query<'w, D: QueryData> {
     world: WorldPtr<'w>,
     /// Tables that this query matches.
     tables: SparseSet<TableId>,
+    /// Released when the query is dropped.
+    #[cfg(feature = "borrow_tracker")]
+    _borrow: crate::world::WorldBorrow<'w>,
     _marker: PhantomData<D>,
 }
 
@@ -32,11 +57,38 @@ pub struct QueryIter<'w, 's, D: QueryData> {
     table: Option<TableId>,
     /// The current row in the table.
     row: TableRow,
+    /// The structure epoch of the world when iteration started.
+    #[cfg(debug_assertions)]
+    epoch: u64,
     _marker: PhantomData<D>,
 }
 
 /// Trait for the data that can be retreived from an entity.
 ///
+/// # Deriving
+///
+/// `QueryData` can be derived for structs whose fields all implement
+/// `QueryData`, as a readable alternative to tuples. The struct can have at
+/// most one lifetime, which is that of the world borrow:
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// # #[derive(Component)]
+/// # struct Health(u32);
+/// # #[derive(Component)]
+/// # struct Name(&'static str);
+/// #[derive(QueryData)]
+/// struct Player<'w> {
+///     entity: EntityId,
+///     health: &'w Health,
+///     name: Option<&'w Name>,
+/// }
+/// ```
+///
+/// Each field is the output of its own query data, so filters like [`With`]
+/// can't be fields. [`ReadOnlyQueryData`] is also implemented if all fields
+/// implement it.
+///
 /// # Safety
 ///
 /// [`QueryData::get`] must only access data set in [`QueryData::world_access`].
@@ -85,6 +137,17 @@ pub enum QueryGetError {
     },
 }
 
+/// Error when a query expected to match exactly one entity doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum QuerySingleError {
+    /// Error when no entities match the query.
+    #[error("no entities match the query {0}")]
+    NoEntities(&'static str),
+    /// Error when multiple entities match the query.
+    #[error("multiple entities match the query {0}")]
+    MultipleEntities(&'static str),
+}
+
 impl<'w, D: QueryData> Query<'w, D> {
     /// Creates a new query.
     ///
@@ -93,26 +156,31 @@ impl<'w, D: QueryData> Query<'w, D> {
     /// # Safety
     ///
     /// The world pointer must be valid for this query's access.
+    #[cfg_attr(feature = "borrow_tracker", track_caller)]
     pub unsafe fn new(world: WorldPtr<'w>) -> Result<Self, AccessError> {
         // SAFETY: access to world metadata is always valid
         let mut access = WorldAccess::new();
 
         D::world_access(&mut access);
+        access.result()?;
 
-        access.result().map(|_| {
-            // TODO: optimize
+        // TODO: optimize
 
-            let mut tables = SparseSet::new();
+        let mut tables = SparseSet::new();
 
-            // SAFETY: access to world metadata is always valid
-            for (index, table) in unsafe { world.as_ref().components.tables() }
-            {
-                if access.matches(table.components()) {
-                    tables.insert(index);
-                }
+        // SAFETY: access to world metadata is always valid
+        for (index, table) in unsafe { world.as_ref().components.tables() } {
+            if access.matches(table.components()) {
+                tables.insert(index);
             }
+        }
 
-            Self { world, tables, _marker: PhantomData }
+        Ok(Self {
+            world,
+            tables,
+            #[cfg(feature = "borrow_tracker")]
+            _borrow: world.track(access),
+            _marker: PhantomData,
         })
     }
 
@@ -164,6 +232,49 @@ impl<'w, D: QueryData> Query<'w, D> {
         self.tables.contains(&addr.table)
     }
 
+    /// Gets the query data of the only entity matched by this query.
+    ///
+    /// Returns an error if no entities or multiple entities are matched. The
+    /// query data must implement [`ReadOnlyQueryData`].
+    pub fn single(&self) -> Result<D::Output<'_>, QuerySingleError>
+    where
+        D: ReadOnlyQueryData,
+    {
+        let entity = self.single_entity()?;
+
+        // SAFETY: the entity matches the query
+        Ok(unsafe { D::get(self.world.entity(entity)) })
+    }
+
+    /// Gets the query data of the only entity matched by this query.
+    ///
+    /// Returns an error if no entities or multiple entities are matched.
+    pub fn single_mut(&mut self) -> Result<D::Output<'_>, QuerySingleError> {
+        let entity = self.single_entity()?;
+
+        // SAFETY: the entity matches the query
+        Ok(unsafe { D::get(self.world.entity(entity)) })
+    }
+
+    /// Returns the only entity matched by this query.
+    pub(crate) fn single_entity(&self) -> Result<EntityId, QuerySingleError> {
+        match self.len() {
+            0 => Err(QuerySingleError::NoEntities(type_name::<D>())),
+            1 => Ok(self
+                .tables
+                .iter()
+                // SAFETY: reads to ECS metadata should always be valid
+                .find_map(|&table| unsafe {
+                    let table =
+                        self.world.as_ref().components.get_unchecked(table);
+
+                    table.rows().next().map(|(_, entity)| entity)
+                })
+                .expect("a matched table contains the entity")),
+            _ => Err(QuerySingleError::MultipleEntities(type_name::<D>())),
+        }
+    }
+
     /// Gets the query data for a particular entity.
     ///
     /// The query data must implement [`ReadOnlyQueryData`].
@@ -200,6 +311,90 @@ impl<'w, D: QueryData> Query<'w, D> {
         }
     }
 
+    /// Gets the query data for a particular entity, or `None` if the entity
+    /// was despawned.
+    ///
+    /// Unlike [`Query::get`], stale ids aren't an error. Returns an error if
+    /// the entity is alive but doesn't match the query.
+    pub fn get_or_despawned(
+        &self,
+        entity: EntityId,
+    ) -> Result<Option<D::Output<'_>>, QueryGetError>
+    where
+        D: ReadOnlyQueryData,
+    {
+        match self.get(entity) {
+            Ok(output) => Ok(Some(output)),
+            Err(QueryGetError::EntityNotFound(_)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Gets the query data for a particular entity, or `None` if the entity
+    /// was despawned.
+    ///
+    /// See [`Query::get_or_despawned`].
+    pub fn get_or_despawned_mut(
+        &mut self,
+        entity: EntityId,
+    ) -> Result<Option<D::Output<'_>>, QueryGetError> {
+        match self.get_mut(entity) {
+            Ok(output) => Ok(Some(output)),
+            Err(QueryGetError::EntityNotFound(_)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Gets the query data for several entities, appending it to a vector in
+    /// the order of the entities.
+    ///
+    /// The entities are visited in storage order, which is more cache-friendly
+    /// than calling [`Query::get`] for each entity when they are scattered.
+    /// Returns an error without appending anything if an entity doesn't exist
+    /// or doesn't match the query.
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    pub fn get_batch<'q>(
+        &'q self,
+        entities: &[EntityId],
+        out: &mut Vec<D::Output<'q>>,
+    ) -> Result<(), QueryGetError>
+    where
+        D: ReadOnlyQueryData,
+    {
+        let mut addrs = Vec::with_capacity(entities.len());
+
+        for (index, &entity) in entities.iter().enumerate() {
+            let addr = self
+                .addr_of(entity)
+                .ok_or(QueryGetError::EntityNotFound(entity))?;
+
+            if !self.tables.contains(&addr.table) {
+                return Err(QueryGetError::Mismatch {
+                    entity,
+                    data: type_name::<D>(),
+                });
+            }
+
+            addrs.push((addr.table, addr.row.0, index));
+        }
+
+        addrs.sort_unstable();
+
+        let mut outputs: Vec<_> = addrs
+            .into_iter()
+            .map(|(_, _, index)| {
+                // SAFETY: the entity matches the query, which is read-only
+                (index, unsafe { D::get(self.world.entity(entities[index])) })
+            })
+            .collect();
+
+        outputs.sort_unstable_by_key(|&(index, _)| index);
+        out.extend(outputs.into_iter().map(|(_, output)| output));
+
+        Ok(())
+    }
+
     fn addr_of(&self, entity: EntityId) -> Option<EntityAddr> {
         unsafe { self.world.as_ref().entities.get(entity) }
     }
@@ -211,26 +406,12 @@ impl<'w, D: QueryData> Query<'w, D> {
     where
         D: ReadOnlyQueryData,
     {
-        QueryIter {
-            world: self.world,
-            len: self.len(),
-            tables: self.tables.iter(),
-            table: None,
-            row: TableRow(0),
-            _marker: PhantomData,
-        }
+        QueryIter::new(self.world, self.tables.iter(), self.len())
     }
 
     /// Returns an iterator over query data.
     pub fn iter_mut(&mut self) -> QueryIter<'w, '_, D> {
-        QueryIter {
-            world: self.world,
-            tables: self.tables.iter(),
-            len: self.len(),
-            table: None,
-            row: TableRow(0),
-            _marker: PhantomData,
-        }
+        QueryIter::new(self.world, self.tables.iter(), self.len())
     }
 }
 
@@ -282,33 +463,66 @@ impl<'w, 's, D: QueryData> IntoIterator for &'s mut Query<'w, D> {
     }
 }
 
+impl<'w, 's, D: QueryData> QueryIter<'w, 's, D> {
+    pub(crate) fn new(
+        world: WorldPtr<'w>,
+        tables: SparseIter<'s, TableId>,
+        len: usize,
+    ) -> Self {
+        Self {
+            world,
+            tables,
+            len,
+            table: None,
+            row: TableRow(0),
+            // SAFETY: reads to ECS metadata should always be valid
+            #[cfg(debug_assertions)]
+            epoch: unsafe { world.as_ref() }.entities.epoch(),
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<'w, D: QueryData> Iterator for QueryIter<'w, '_, D> {
     type Item = D::Output<'w>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let table = if let Some(table) = self.table {
-            table
-        } else {
-            *self.table.get_or_insert(*self.tables.next()?)
-        };
-        let entity = unsafe {
-            let table = self.world.as_ref().components.get_unchecked(table);
+        // entities spawned, despawned or moved through unchecked access would
+        // otherwise invalidate the rows being iterated
+        // SAFETY: reads to ECS metadata should always be valid
+        #[cfg(debug_assertions)]
+        assert_eq!(
+            unsafe { self.world.as_ref() }.entities.epoch(),
+            self.epoch,
+            "the world was structurally changed during query iteration",
+        );
+
+        loop {
+            let table = if let Some(table) = self.table {
+                table
+            } else {
+                *self.table.get_or_insert(*self.tables.next()?)
+            };
+            let table =
+                unsafe { self.world.as_ref().components.get_unchecked(table) };
+
+            if self.row.0 >= table.rows_len() {
+                self.table = None;
+                self.row = TableRow(0);
+
+                continue;
+            }
 
-            table.entity(self.row)
-        };
+            let row = self.row;
 
-        if let Some(entity) = entity {
-            self.len -= 1;
             self.row.0 += 1;
 
-            Some(unsafe { D::get(self.world.entity(entity)) })
-        } else if entity.is_none() && self.tables.len() != 0 {
-            self.table = None;
-            self.row = TableRow(0);
+            // skips rows left empty by removed entities
+            if let Some(entity) = table.entity(row) {
+                self.len -= 1;
 
-            self.next()
-        } else {
-            None
+                return Some(unsafe { D::get(self.world.entity(entity)) });
+            }
         }
     }
 
@@ -411,6 +625,24 @@ unsafe impl QueryData for EntityId {
 /// Nothing is accessed.
 unsafe impl ReadOnlyQueryData for EntityId {}
 
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: ?Sized> QueryData for PhantomData<T> {
+    type Output<'w> = Self;
+
+    fn world_access(_access: &mut WorldAccess) {}
+
+    unsafe fn get(_entity: EntityPtr<'_>) -> Self::Output<'_> {
+        PhantomData
+    }
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: ?Sized> ReadOnlyQueryData for PhantomData<T> {}
+
 /// # Safety
 ///
 /// The access declares that it immutable borrows all components.
@@ -438,7 +670,7 @@ unsafe impl QueryData for EntityMut<'_> {
     type Output<'w> = EntityMut<'w>;
 
     fn world_access(access: &mut WorldAccess) {
-        access.borrows_all_entities(Level::Read);
+        access.borrows_all_entities(Level::Write);
     }
 
     unsafe fn get(entity: EntityPtr<'_>) -> Self::Output<'_> {
@@ -446,6 +678,49 @@ unsafe impl QueryData for EntityMut<'_> {
     }
 }
 
+/// # Safety
+///
+/// The access declares that it immutably borrows the components of `B`.
+unsafe impl<B: Bundle> QueryData for FilteredEntityRef<'_, B> {
+    type Output<'w> = FilteredEntityRef<'w, B>;
+
+    fn world_access(access: &mut WorldAccess) {
+        let mut components = ComponentSet::new();
+
+        B::components(&mut components);
+        access.maybe_borrows_components(&components, Level::Read);
+    }
+
+    unsafe fn get(entity: EntityPtr<'_>) -> Self::Output<'_> {
+        // SAFETY: the returned reference can only access the components of `B`
+        FilteredEntityRef::new(unsafe { entity.as_ref() })
+    }
+}
+
+/// # Safety
+///
+/// The access declares that it immutably borrows the components of `B`.
+unsafe impl<B: Bundle> ReadOnlyQueryData for FilteredEntityRef<'_, B> {}
+
+/// # Safety
+///
+/// The access declares that it mutably borrows the components of `B`.
+unsafe impl<B: Bundle> QueryData for FilteredEntityMut<'_, B> {
+    type Output<'w> = FilteredEntityMut<'w, B>;
+
+    fn world_access(access: &mut WorldAccess) {
+        let mut components = ComponentSet::new();
+
+        B::components(&mut components);
+        access.maybe_borrows_components(&components, Level::Write);
+    }
+
+    unsafe fn get(entity: EntityPtr<'_>) -> Self::Output<'_> {
+        // SAFETY: the returned reference can only access the components of `B`
+        FilteredEntityMut::new(unsafe { entity.as_mut() })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -491,6 +766,194 @@ mod tests {
         assert_eq!(iter.next().unwrap().0, butterfly);
     }
 
+    #[test]
+    fn get_batch() {
+        let mut world = World::new();
+
+        let hps: Vec<_> = (0..6)
+            .map(|hp| {
+                if hp % 2 == 0 {
+                    world.spawn((Hp(hp), Human)).id()
+                } else {
+                    world.spawn(Hp(hp)).id()
+                }
+            })
+            .collect();
+        let human = world.spawn(Human).id();
+
+        let query = world.query::<&Hp>().unwrap();
+        let entities = [hps[5], hps[0], hps[3], hps[0], hps[2]];
+        let mut out = Vec::new();
+
+        query.get_batch(&entities, &mut out).unwrap();
+
+        assert_eq!(
+            out.iter().map(|hp| hp.0).collect::<Vec<_>>(),
+            [5, 0, 3, 0, 2]
+        );
+        assert!(matches!(
+            query.get_batch(&[hps[1], human], &mut out),
+            Err(QueryGetError::Mismatch { entity, .. }) if entity == human,
+        ));
+        assert_eq!(out.len(), 5);
+    }
+
+    #[test]
+    fn query_iter_skips_removed_rows() {
+        let mut world = World::new();
+
+        let first = world.spawn(Hp(1)).id();
+        let second = world.spawn(Hp(2)).id();
+
+        world.despawn(first).unwrap();
+
+        let query = world.query::<(EntityId, &Hp)>().unwrap();
+        let entities: Vec<_> = query.iter().map(|(entity, _)| entity).collect();
+
+        assert_eq!(entities, [second]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "structurally changed during query iteration"]
+    fn structural_change_during_iteration_panics() {
+        let mut world = World::new();
+
+        world.spawn(Hp(1));
+        world.spawn(Hp(2));
+
+        let world = WorldPtr::from_mut(&mut world);
+        // SAFETY: `EntityId` doesn't access components
+        let query = unsafe { Query::<(EntityId, With<Hp>)>::new(world) };
+
+        for _ in query.unwrap().iter() {
+            // misuse: spawning invalidates the rows being iterated
+            unsafe { world.as_mut() }.spawn(Hp(3));
+        }
+    }
+
+    #[test]
+    fn disabled_entities_are_skipped() {
+        let mut world = World::new();
+
+        let enabled = world.spawn(Hp(1)).id();
+        let disabled = world.spawn((Hp(2), Disabled)).id();
+
+        {
+            let query = world.query::<EntityId>().unwrap();
+
+            assert_eq!(query.iter().collect::<Vec<_>>(), [enabled]);
+
+            let query = world.query::<(EntityId, IncludeDisabled)>().unwrap();
+
+            assert_eq!(query.len(), 2);
+
+            let query = world.query::<(EntityId, With<Disabled>)>().unwrap();
+
+            assert_eq!(
+                query.iter().map(|(entity, _)| entity).collect::<Vec<_>>(),
+                [disabled]
+            );
+
+            let query = world.query::<Option<&Disabled>>().unwrap();
+
+            assert_eq!(query.iter().filter(Option::is_some).count(), 1);
+        }
+
+        world.entity_mut(disabled).unwrap().remove::<Disabled>().unwrap();
+
+        assert_eq!(world.query::<&Hp>().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn entity_mut_conflicts_with_components() {
+        let mut world = World::new();
+
+        assert!(world.query_mut::<(EntityMut, &Hp)>().is_err());
+        assert!(world.query_mut::<(EntityMut, With<Hp>)>().is_ok());
+    }
+
+    #[test]
+    fn with_and_without() {
+        let mut world = World::new();
+
+        let human = world.spawn((Human, Hp(24))).id();
+        let butterfly = world.spawn((Butterfly, Hp(3))).id();
+
+        let mut query = world.query_mut::<(EntityMut, With<Human>)>().unwrap();
+        let (mut entity, ()) = query.iter_mut().next().unwrap();
+
+        assert_eq!(query.len(), 1);
+        assert_eq!(entity.id(), human);
+
+        entity.get_mut::<Hp>().unwrap().0 += 1;
+
+        drop(query);
+
+        let query = world.query::<(EntityId, Without<Human>)>().unwrap();
+
+        assert_eq!(query.len(), 1);
+        assert_eq!(query.iter().next().unwrap().0, butterfly);
+        assert_eq!(world.entity(human).unwrap().get::<Hp>().unwrap().0, 25);
+    }
+
+    #[test]
+    fn filtered_entity_access() {
+        let mut world = World::new();
+
+        let human = world.spawn((Human, Hp(24))).id();
+
+        let mut query =
+            world.query_mut::<(FilteredEntityMut<Hp>, &Human)>().unwrap();
+        let (mut entity, _) = query.get_mut(human).unwrap();
+
+        entity.get_mut::<Hp>().unwrap().0 = 12;
+
+        assert!(entity.contains::<Human>());
+        assert!(entity.get::<Human>().is_err());
+
+        drop(query);
+
+        assert_eq!(world.entity(human).unwrap().get::<Hp>().unwrap().0, 12);
+    }
+
+    #[test]
+    fn derived_query_data() {
+        #[derive(QueryData)]
+        struct Named<'w> {
+            entity: EntityId,
+            hp: &'w Hp,
+        }
+
+        #[derive(QueryData)]
+        struct MaybeHuman<'w>(Named<'w>, Option<&'w Human>);
+
+        #[derive(QueryData)]
+        struct Healable<'w> {
+            hp: &'w mut Hp,
+            human: Option<&'w Human>,
+        }
+
+        let mut world = World::new();
+
+        let human = world.spawn((Human, Hp(24))).id();
+        let la_creatura = world.spawn((LaCreatura, Hp(128))).id();
+
+        for healable in &mut world.query_mut::<Healable>().unwrap() {
+            if healable.human.is_some() {
+                healable.hp.0 += 1;
+            }
+        }
+
+        let query = world.query::<MaybeHuman>().unwrap();
+        let MaybeHuman(named, is_human) = query.get(human).unwrap();
+
+        assert_eq!(named.entity, human);
+        assert_eq!(named.hp.0, 25);
+        assert!(is_human.is_some());
+        assert!(query.get(la_creatura).unwrap().1.is_none());
+    }
+
     #[test]
     fn query_get() {
         let mut world = World::new();
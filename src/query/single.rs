@@ -0,0 +1,257 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use super::{Query, QueryData, QuerySingleError, ReadOnlyQueryData};
+use crate::access::WorldAccess;
+use crate::system::{ReadOnlySystemInput, SkipReason, SystemInput};
+use crate::world::{World, WorldPtr};
+
+/// A system input of the query data of the only entity matched by a query.
+///
+/// The system is skipped if no entities or multiple entities match, and
+/// [`World::run_system`] returns [`RunSystemError::Skipped`]. Use
+/// `Option<Single<D>>` or `Result<Single<D>, QuerySingleError>` to run the
+/// system anyways.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Player(u32);
+///
+/// fn score(player: Single<&Player>) -> u32 {
+///     player.0
+/// }
+///
+/// fn maybe_score(player: Option<Single<&Player>>) -> Option<u32> {
+///     player.map(|player| player.0)
+/// }
+///
+/// let mut world = World::new();
+///
+/// assert!(world.run_system(score).is_err());
+/// assert_eq!(world.run_system(maybe_score).unwrap(), None);
+///
+/// world.spawn(Player(3));
+///
+/// assert_eq!(world.run_system(score).unwrap(), 3);
+/// ```
+///
+/// [`RunSystemError::Skipped`]: crate::system::RunSystemError::Skipped
+pub struct Single<'w, D: QueryData> {
+    item: D::Output<'w>,
+    /// Kept for the borrow of the world.
+    _query: Query<'w, D>,
+}
+
+impl<'w, D: QueryData> Single<'w, D> {
+    /// Returns the query data of the entity.
+    pub fn into_inner(self) -> D::Output<'w> {
+        self.item
+    }
+
+    /// Creates a query and gets the query data of its only entity.
+    ///
+    /// # Safety
+    ///
+    /// The world pointer must be valid for the access of the query data.
+    unsafe fn new(world: WorldPtr<'w>) -> Result<Self, QuerySingleError> {
+        // SAFETY: the caller ensures that the access is valid
+        let query = unsafe { Query::<D>::new(world).unwrap_unchecked() };
+        let entity = query.single_entity()?;
+        // SAFETY: the entity matches the query, and the item is the only one
+        // created from the query
+        let item = unsafe { D::get(world.entity(entity)) };
+
+        Ok(Self { item, _query: query })
+    }
+
+    /// Returns an error unless exactly one entity matches the query data.
+    fn matches(world: &World) -> Result<(), QuerySingleError> {
+        // SAFETY: only metadata is read
+        unsafe { Query::<D>::new(world.as_ptr()) }
+            .map_or(Ok(()), |query| query.single_entity().map(|_| ()))
+    }
+}
+
+/// # Safety
+///
+/// The access is that of the query data.
+unsafe impl<D: QueryData> SystemInput for Single<'_, D> {
+    type Output<'w, 's> = Single<'w, D>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        D::world_access(access);
+    }
+
+    fn validate(_state: &Self::State, world: &World) -> Result<(), SkipReason> {
+        Self::matches(world).map_err(|error| {
+            SkipReason::new::<Self>(match error {
+                QuerySingleError::NoEntities(_) => "no entities match",
+                QuerySingleError::MultipleEntities(_) => {
+                    "multiple entities match"
+                },
+            })
+        })
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the access is valid
+        unsafe { Single::new(world) }.unwrap_or_else(|error| {
+            panic!("`Single` input used without validation: {error}")
+        })
+    }
+}
+
+/// # Safety
+///
+/// The query data performs only immutable access.
+unsafe impl<D: ReadOnlyQueryData> ReadOnlySystemInput for Single<'_, D> {}
+
+/// # Safety
+///
+/// The access is that of the query data.
+unsafe impl<D: QueryData> SystemInput for Option<Single<'_, D>> {
+    type Output<'w, 's> = Option<Single<'w, D>>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        D::world_access(access);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the access is valid
+        unsafe { Single::new(world) }.ok()
+    }
+}
+
+/// # Safety
+///
+/// The query data performs only immutable access.
+unsafe impl<D: ReadOnlyQueryData> ReadOnlySystemInput
+    for Option<Single<'_, D>>
+{
+}
+
+/// # Safety
+///
+/// The access is that of the query data.
+unsafe impl<D: QueryData> SystemInput
+    for Result<Single<'_, D>, QuerySingleError>
+{
+    type Output<'w, 's> = Result<Single<'w, D>, QuerySingleError>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        D::world_access(access);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the access is valid
+        unsafe { Single::new(world) }
+    }
+}
+
+/// # Safety
+///
+/// The query data performs only immutable access.
+unsafe impl<D: ReadOnlyQueryData> ReadOnlySystemInput
+    for Result<Single<'_, D>, QuerySingleError>
+{
+}
+
+// ---
+
+impl<'w, D: QueryData> Deref for Single<'w, D> {
+    type Target = D::Output<'w>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.item
+    }
+}
+
+impl<D: QueryData> DerefMut for Single<'_, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.item
+    }
+}
+
+impl<'w, D: QueryData> fmt::Debug for Single<'w, D>
+where
+    D::Output<'w>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.item.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Player(u32);
+
+    #[derive(Component)]
+    struct Enemy;
+
+    #[test]
+    fn single_skips_system() {
+        fn heal(mut player: Single<&mut Player>) {
+            player.0 += 1;
+        }
+
+        fn count(player: Result<Single<&Player>, QuerySingleError>) -> bool {
+            player.is_ok()
+        }
+
+        let mut world = World::new();
+
+        assert!(matches!(
+            world.run_system(heal),
+            Err(RunSystemError::Skipped { .. }),
+        ));
+        assert!(!world.run_system(count).unwrap());
+
+        let player = world.spawn((Player(1), Enemy)).id();
+
+        world.run_system(heal).unwrap();
+
+        assert_eq!(world.entity(player).unwrap().get::<Player>().unwrap().0, 2);
+        assert!(world.run_system(count).unwrap());
+
+        world.spawn(Player(5));
+
+        let Err(RunSystemError::Skipped { reason, .. }) =
+            world.run_system(heal)
+        else {
+            panic!("expected the system to be skipped");
+        };
+
+        assert!(reason.input().contains("Single"));
+        assert!(matches!(
+            world.query::<&Player>().unwrap().single(),
+            Err(QuerySingleError::MultipleEntities(_)),
+        ));
+
+        let query = world.query::<(&Player, With<Enemy>)>().unwrap();
+        let (enemy, ()) = query.single().unwrap();
+
+        assert_eq!(enemy.0, 2);
+    }
+}
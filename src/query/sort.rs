@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::{slice, vec};
+
+use super::{Query, QueryData, QueryIter, ReadOnlyQueryData};
+use crate::entity::EntityId;
+use crate::world::WorldPtr;
+
+/// An iterator over query data ordered by a key.
+///
+/// Created by [`Query::iter_sorted_by_key`] and
+/// [`Query::iter_sorted_by_key_mut`].
+pub struct QuerySortedIter<'w, 's, D: QueryData, K> {
+    world: WorldPtr<'w>,
+    entities: slice::Iter<'s, (K, EntityId)>,
+    _marker: PhantomData<D>,
+}
+
+impl<'w, D: QueryData> Query<'w, D> {
+    /// Returns an iterator over query data ordered by a key.
+    ///
+    /// The keys of all matched entities are collected into `buffer`, which is
+    /// cleared first. Keep the buffer around (for example in a
+    /// [`Var`](crate::system::Var)) to avoid allocating every call. Entities
+    /// with equal keys, or keys that can't be compared (like `NaN`), keep the
+    /// order of [`Query::iter`].
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    pub fn iter_sorted_by_key<'s, K: PartialOrd>(
+        &'s self,
+        buffer: &'s mut Vec<(K, EntityId)>,
+        f: impl FnMut(D::Output<'_>) -> K,
+    ) -> QuerySortedIter<'w, 's, D, K>
+    where
+        D: ReadOnlyQueryData,
+    {
+        // SAFETY: the query data is read-only
+        unsafe { self.sort_unchecked(buffer, f) }
+    }
+
+    /// Returns an iterator over query data ordered by a key.
+    ///
+    /// See [`Query::iter_sorted_by_key`].
+    pub fn iter_sorted_by_key_mut<'s, K: PartialOrd>(
+        &'s mut self,
+        buffer: &'s mut Vec<(K, EntityId)>,
+        f: impl FnMut(D::Output<'_>) -> K,
+    ) -> QuerySortedIter<'w, 's, D, K> {
+        // SAFETY: this query is borrowed mutably
+        unsafe { self.sort_unchecked(buffer, f) }
+    }
+
+    /// # Safety
+    ///
+    /// The returned iterator must not alias other outputs of this query.
+    unsafe fn sort_unchecked<'s, K: PartialOrd>(
+        &'s self,
+        buffer: &'s mut Vec<(K, EntityId)>,
+        mut f: impl FnMut(D::Output<'_>) -> K,
+    ) -> QuerySortedIter<'w, 's, D, K> {
+        buffer.clear();
+        buffer.reserve(self.len());
+
+        for &table in &self.tables {
+            // SAFETY: reads to ECS metadata should always be valid
+            let table =
+                unsafe { self.world.as_ref().components.get_unchecked(table) };
+
+            for (_, entity) in table.rows() {
+                // SAFETY: the entity matches the query and the output is
+                // dropped before the next one is created
+                let key = f(unsafe { D::get(self.world.entity(entity)) });
+
+                buffer.push((key, entity));
+            }
+        }
+
+        buffer.sort_by(|(lhs, _), (rhs, _)| {
+            lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal)
+        });
+
+        QuerySortedIter {
+            world: self.world,
+            entities: buffer.iter(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'w, D: QueryData> QueryIter<'w, '_, D> {
+    /// Collects the remaining query data and orders it by a key.
+    ///
+    /// Allocates every call. To reuse an allocation, see
+    /// [`Query::iter_sorted_by_key`].
+    pub fn sort_by_key<K: Ord>(
+        self,
+        f: impl FnMut(&D::Output<'w>) -> K,
+    ) -> vec::IntoIter<D::Output<'w>> {
+        let mut items: Vec<_> = self.collect();
+
+        items.sort_by_key(f);
+        items.into_iter()
+    }
+
+    /// Collects the remaining query data and orders it with a comparison
+    /// function.
+    ///
+    /// Allocates every call. To reuse an allocation, see
+    /// [`Query::iter_sorted_by_key`].
+    pub fn sort_by(
+        self,
+        compare: impl FnMut(&D::Output<'w>, &D::Output<'w>) -> Ordering,
+    ) -> vec::IntoIter<D::Output<'w>> {
+        let mut items: Vec<_> = self.collect();
+
+        items.sort_by(compare);
+        items.into_iter()
+    }
+}
+
+impl<'w, D: QueryData, K> Iterator for QuerySortedIter<'w, '_, D, K> {
+    type Item = D::Output<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(_, entity) = self.entities.next()?;
+
+        // SAFETY: the entity was matched by the query and each entity is
+        // yielded once
+        Some(unsafe { D::get(self.world.entity(entity)) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entities.size_hint()
+    }
+}
+
+impl<D: QueryData, K> DoubleEndedIterator for QuerySortedIter<'_, '_, D, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let &(_, entity) = self.entities.next_back()?;
+
+        // SAFETY: the entity was matched by the query and each entity is
+        // yielded once
+        Some(unsafe { D::get(self.world.entity(entity)) })
+    }
+}
+
+impl<D: QueryData, K> ExactSizeIterator for QuerySortedIter<'_, '_, D, K> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Depth(f32);
+
+    #[derive(Component)]
+    struct Layer(u32);
+
+    #[test]
+    fn iter_sorted_by_key() {
+        let mut world = World::new();
+        let mut buffer = Vec::new();
+
+        let back = world.spawn(Depth(-1.0)).id();
+        let front = world.spawn(Depth(3.0)).id();
+        let middle = world.spawn((Depth(0.5), Layer(0))).id();
+
+        let query = world.query::<(EntityId, &Depth)>().unwrap();
+        let sorted: Vec<_> = query
+            .iter_sorted_by_key(&mut buffer, |(_, depth)| depth.0)
+            .map(|(entity, _)| entity)
+            .collect();
+
+        assert_eq!(sorted, [back, middle, front]);
+
+        drop(query);
+
+        let mut query = world.query_mut::<&mut Depth>().unwrap();
+
+        for (i, depth) in query
+            .iter_sorted_by_key_mut(&mut buffer, |depth| depth.0)
+            .rev()
+            .enumerate()
+        {
+            depth.0 = i as f32;
+        }
+
+        drop(query);
+
+        assert_eq!(world.entity(front).unwrap().get::<Depth>().unwrap().0, 0.0);
+    }
+
+    #[test]
+    fn query_iter_sort_by_key() {
+        let mut world = World::new();
+
+        world.spawn(Layer(2));
+        world.spawn(Layer(0));
+        world.spawn(Layer(1));
+
+        let query = world.query::<&Layer>().unwrap();
+        let layers: Vec<_> = query
+            .iter()
+            .sort_by_key(|layer| layer.0)
+            .map(|layer| layer.0)
+            .collect();
+
+        assert_eq!(layers, [0, 1, 2]);
+    }
+}
@@ -0,0 +1,221 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use super::{QueryData, QueryGetError, QueryIter, ReadOnlyQueryData};
+use crate::access::{AccessError, WorldAccess};
+use crate::component::TableId;
+use crate::entity::EntityId;
+use crate::storage::SparseSet;
+use crate::world::{World, WorldId, WorldPtr};
+
+/// The cached state of a query.
+///
+/// Created with [`World::query_cached`]. Stores the tables matched by the
+/// query, and only checks tables created since it was last used. Can only be
+/// used with the world it was created from.
+pub struct QueryState<D: QueryData> {
+    world: WorldId,
+    access: WorldAccess,
+    /// Tables that this query matches.
+    tables: SparseSet<TableId>,
+    /// The amount of tables that have been checked.
+    checked: usize,
+    _marker: PhantomData<D>,
+}
+
+impl<D: QueryData> QueryState<D> {
+    /// Creates the state of a query.
+    ///
+    /// Returns an error if the query access is invalid.
+    pub fn new(world: &World) -> Result<Self, AccessError> {
+        let mut access = WorldAccess::new();
+
+        D::world_access(&mut access);
+        access.result()?;
+
+        let mut state = Self {
+            world: world.id(),
+            access,
+            tables: SparseSet::new(),
+            checked: 0,
+            _marker: PhantomData,
+        };
+
+        state.update(world);
+
+        Ok(state)
+    }
+
+    /// Checks tables created since this state was last updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world isn't the one this state was created from.
+    pub fn update(&mut self, world: &World) {
+        assert_eq!(
+            self.world,
+            world.id(),
+            "`QueryState<{}>` used with a world it wasn't created from",
+            type_name::<D>(),
+        );
+
+        for (index, table) in world.components.tables().skip(self.checked) {
+            if self.access.matches(table.components()) {
+                self.tables.insert(index);
+            }
+        }
+
+        self.checked = world.components.tables().len();
+    }
+
+    /// Returns the amount of entities matched by this query.
+    pub fn len(&mut self, world: &World) -> usize {
+        self.update(world);
+        self.len_unchecked(world)
+    }
+
+    /// Returns `true` if this query matched no entities.
+    pub fn is_empty(&mut self, world: &World) -> bool {
+        self.len(world) == 0
+    }
+
+    fn len_unchecked(&self, world: &World) -> usize {
+        self.tables
+            .iter()
+            // SAFETY: the tables were retrieved from this world
+            .map(|&table| unsafe {
+                world.components.get_unchecked(table).len()
+            })
+            .sum()
+    }
+
+    /// Returns an iterator over query data.
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    pub fn iter<'w, 's>(&'s mut self, world: &'w World) -> QueryIter<'w, 's, D>
+    where
+        D: ReadOnlyQueryData,
+    {
+        self.update(world);
+        // SAFETY: the query data is read-only
+        unsafe { self.iter_unchecked(world.as_ptr()) }
+    }
+
+    /// Returns an iterator over query data.
+    pub fn iter_mut<'w, 's>(
+        &'s mut self,
+        world: &'w mut World,
+    ) -> QueryIter<'w, 's, D> {
+        self.update(world);
+        // SAFETY: the world is borrowed mutably
+        unsafe { self.iter_unchecked(world.as_ptr_mut()) }
+    }
+
+    /// # Safety
+    ///
+    /// The world pointer must be valid for the access of this query and this
+    /// state must be updated.
+    unsafe fn iter_unchecked<'w, 's>(
+        &'s self,
+        world: WorldPtr<'w>,
+    ) -> QueryIter<'w, 's, D> {
+        // SAFETY: reads to ECS metadata should always be valid
+        let len = self.len_unchecked(unsafe { world.as_ref() });
+
+        QueryIter::new(world, self.tables.iter(), len)
+    }
+
+    /// Gets the query data for a particular entity.
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    pub fn get<'w>(
+        &mut self,
+        world: &'w World,
+        entity: EntityId,
+    ) -> Result<D::Output<'w>, QueryGetError>
+    where
+        D: ReadOnlyQueryData,
+    {
+        self.update(world);
+        // SAFETY: the query data is read-only
+        unsafe { self.get_unchecked(world.as_ptr(), entity) }
+    }
+
+    /// Gets the query data for a particular entity.
+    pub fn get_mut<'w>(
+        &mut self,
+        world: &'w mut World,
+        entity: EntityId,
+    ) -> Result<D::Output<'w>, QueryGetError> {
+        self.update(world);
+        // SAFETY: the world is borrowed mutably
+        unsafe { self.get_unchecked(world.as_ptr_mut(), entity) }
+    }
+
+    /// # Safety
+    ///
+    /// The world pointer must be valid for the access of this query and this
+    /// state must be updated.
+    unsafe fn get_unchecked<'w>(
+        &self,
+        world: WorldPtr<'w>,
+        entity: EntityId,
+    ) -> Result<D::Output<'w>, QueryGetError> {
+        // SAFETY: reads to ECS metadata should always be valid
+        let world_ref = unsafe { world.as_ref() };
+
+        if !world_ref.contains(entity) {
+            return Err(QueryGetError::EntityNotFound(entity));
+        }
+
+        match world_ref.entities.get(entity) {
+            Some(addr) if self.tables.contains(&addr.table) => {
+                // SAFETY: the entity matches the query
+                Ok(unsafe { D::get(world.entity(entity)) })
+            },
+            _ => {
+                Err(QueryGetError::Mismatch { entity, data: type_name::<D>() })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A(u32);
+
+    #[derive(Component)]
+    struct B;
+
+    #[test]
+    fn query_cached() {
+        let mut world = World::new();
+        let mut state = world.query_cached::<&mut A>().unwrap();
+
+        world.spawn(A(0));
+
+        assert_eq!(state.len(&world), 1);
+
+        // creates a new table after the state was created
+        let entity = world.spawn((A(1), B)).id();
+
+        for a in state.iter_mut(&mut world) {
+            a.0 += 10;
+        }
+
+        assert_eq!(state.len(&world), 2);
+        assert_eq!(state.get_mut(&mut world, entity).unwrap().0, 11);
+    }
+
+    #[test]
+    #[should_panic = "wasn't created from"]
+    fn query_state_other_world() {
+        let world = World::new();
+        let mut state = world.query_cached::<&A>().unwrap();
+
+        state.update(&World::new());
+    }
+}
@@ -24,6 +24,16 @@ macro_rules! tuple_impl {
             $($d: crate::query::ReadOnlyQueryData,)*
         {
         }
+
+        unsafe impl<$($d: crate::query::ChunkData),*> crate::query::ChunkData for ($($d,)*) {
+            type Chunk<'w> = ($($d::Chunk<'w>,)*);
+
+            #[allow(unused)]
+            unsafe fn get_chunk(chunk: crate::query::TableChunk<'_>) -> Self::Chunk<'_> {
+                #[allow(clippy::unused_unit)]
+                ($(unsafe { $d::get_chunk(chunk) },)*)
+            }
+        }
     };
 
     ([$($rest:ident)*]  [$head:ident $($dail:ident)*]) => {
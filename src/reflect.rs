@@ -0,0 +1,173 @@
+//! Runtime reflection of component fields.
+//!
+//! Requires the `reflect` feature.
+
+use std::any::{type_name, Any};
+
+pub use worldlines_macros::Reflect;
+
+/// Trait for types whose fields can be accessed at runtime.
+///
+/// # Deriving
+///
+/// `Reflect` can be derived for structs whose fields all implement `Reflect`.
+/// Fields of tuple structs are named by their index (`"0"`, `"1"`, ...).
+///
+/// To reflect a [`Component`](crate::component::Component) through an entity,
+/// also add `#[component(reflect)]` to its derive.
+pub trait Reflect: Any + Send + Sync {
+    /// Returns the [type name](std::any::type_name) of this value.
+    fn type_name(&self) -> &'static str;
+
+    /// Returns the names of the fields of this value, in declaration order.
+    fn field_names(&self) -> &'static [&'static str];
+
+    /// Returns a reference to a field of this value.
+    fn field(&self, name: &str) -> Option<&dyn Reflect>;
+
+    /// Returns a mutable reference to a field of this value.
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect>;
+
+    /// Returns this value as [`Any`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns this value as [`Any`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl dyn Reflect {
+    /// Returns `true` if this value is a `T`.
+    pub fn is<T: Reflect>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Returns a reference to this value if it is a `T`.
+    pub fn downcast_ref<T: Reflect>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to this value if it is a `T`.
+    pub fn downcast_mut<T: Reflect>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+
+    /// Returns an iterator over the names and values of the fields of this
+    /// value.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, &dyn Reflect)> {
+        self.field_names()
+            .iter()
+            .filter_map(|&name| self.field(name).map(|field| (name, field)))
+    }
+}
+
+macro_rules! impl_reflect_value {
+    ($($t:ty),*) => {
+        $(
+            impl Reflect for $t {
+                fn type_name(&self) -> &'static str {
+                    type_name::<Self>()
+                }
+
+                fn field_names(&self) -> &'static [&'static str] {
+                    &[]
+                }
+
+                fn field(&self, _name: &str) -> Option<&dyn Reflect> {
+                    None
+                }
+
+                fn field_mut(&mut self, _name: &str) -> Option<&mut dyn Reflect> {
+                    None
+                }
+
+                fn as_any(&self) -> &dyn Any {
+                    self
+                }
+
+                fn as_any_mut(&mut self) -> &mut dyn Any {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_reflect_value!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    String,
+    &'static str
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Reflect)]
+    #[component(reflect)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Component, Reflect)]
+    #[component(reflect)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Hidden;
+
+    #[test]
+    fn reflect_fields() {
+        let mut position = Position { x: 1.0, y: 2.0 };
+        let reflect: &mut dyn Reflect = &mut position;
+
+        assert_eq!(reflect.field_names(), &["x", "y"]);
+        assert_eq!(reflect.field("y").unwrap().downcast_ref(), Some(&2.0f32));
+
+        *reflect.field_mut("x").unwrap().downcast_mut::<f32>().unwrap() = 3.0;
+
+        assert_eq!(position.x, 3.0);
+    }
+
+    #[test]
+    fn reflect_entity_components() {
+        let mut world = World::new();
+        let entity =
+            world.spawn((Position { x: 1.0, y: 2.0 }, Health(3), Hidden));
+        let id = entity.id();
+
+        let names: Vec<_> = entity
+            .as_ref()
+            .reflect_components()
+            .map(|component| component.type_name())
+            .collect();
+
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().any(|name| name.ends_with("Health")));
+
+        let mut entity = world.entity_mut(id).unwrap();
+
+        for component in entity.as_mut().reflect_components_mut() {
+            if let Some(health) = component.downcast_mut::<Health>() {
+                health.0 += 1;
+            }
+        }
+
+        assert_eq!(entity.get::<Health>().unwrap().0, 4);
+    }
+}
@@ -1,4 +1,5 @@
-use std::any::type_name;
+use std::alloc::Layout;
+use std::any::{type_name, TypeId};
 use std::fmt;
 use std::marker::PhantomData;
 use std::sync::atomic::{self, AtomicUsize};
@@ -7,7 +8,7 @@ use std::sync::{LazyLock, OnceLock};
 use dashmap::DashMap;
 
 use super::Resource;
-use crate::storage::{SparseIndex, UsizeHasher};
+use crate::storage::{SparseIndex, TypeIdHasher, UsizeHasher};
 
 /// A unique identifier for a [`Resource`].
 #[repr(transparent)]
@@ -29,13 +30,16 @@ pub trait ResourceVTable: Send + Sync + 'static {
     /// Returns the [type name](std::any::type_name) of the resource.
     fn type_name(&self) -> &'static str;
 
+    /// Returns the layout of the resource in memory.
+    fn layout(&self) -> Layout;
+
     // may expand to include resource hooks
 }
 
 /// A static container for allocating [`ResourceId`]'s.
 pub struct ResourceIdCell<R: Resource> {
     inner: OnceLock<ResourceId>,
-    _marker: PhantomData<R>,
+    _marker: PhantomData<fn() -> R>,
 }
 
 /// Registry of [`ResourceInfo`] by their [id](ResourceId).
@@ -51,6 +55,23 @@ impl ResourceId {
         R::id()
     }
 
+    /// Returns the id of a resource type, initializing it if necessary.
+    ///
+    /// Used to implement [`Resource::id`] for generic types, which can't use a
+    /// static [`ResourceIdCell`]. Slower, as the id is looked up in a map.
+    pub fn of_type<R: Resource>() -> Self {
+        static IDS: LazyLock<DashMap<TypeId, ResourceId, TypeIdHasher>> =
+            LazyLock::new(Default::default);
+
+        *IDS.entry(TypeId::of::<R>()).or_insert_with(|| {
+            let id = ResourceId::next();
+
+            REGISTRY.insert(id, ResourceInfo::of::<R>());
+
+            id
+        })
+    }
+
     /// Used internally by [`Resource::id`].
     pub(super) fn next() -> Self {
         static COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -62,7 +83,7 @@ impl ResourceId {
 impl ResourceInfo {
     /// Returns the info of the given resource.
     pub const fn of<R: Resource>() -> Self {
-        Self { inner: &PhantomData::<R> }
+        Self { inner: &PhantomData::<fn() -> R> }
     }
 
     /// Returns the info of the resource with the given id.
@@ -73,6 +94,20 @@ impl ResourceInfo {
         // this invariant.
         unsafe { *REGISTRY.get(&id).unwrap_unchecked() }
     }
+
+    /// Returns the info of all registered resources, in order of their id.
+    ///
+    /// Resources are registered when their id is first requested. See
+    /// [`ComponentInfo::iter_registered`].
+    ///
+    /// [`ComponentInfo::iter_registered`]: crate::component::ComponentInfo::iter_registered
+    pub fn iter_registered() -> impl Iterator<Item = Self> {
+        let mut infos: Vec<_> =
+            REGISTRY.iter().map(|entry| *entry.value()).collect();
+
+        infos.sort_unstable_by_key(|info| info.id());
+        infos.into_iter()
+    }
 }
 
 impl<R: Resource> ResourceIdCell<R> {
@@ -97,6 +132,12 @@ impl<R: Resource> ResourceIdCell<R> {
 
 // ---
 
+impl<R: Resource> Default for ResourceIdCell<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ResourceVTable for ResourceInfo {
     fn id(&self) -> ResourceId {
         self.inner.id()
@@ -105,6 +146,10 @@ impl ResourceVTable for ResourceInfo {
     fn type_name(&self) -> &'static str {
         self.inner.type_name()
     }
+
+    fn layout(&self) -> Layout {
+        self.inner.layout()
+    }
 }
 
 impl SparseIndex for ResourceInfo {
@@ -146,7 +191,7 @@ impl Eq for ResourceInfo {}
 
 // ---
 
-impl<R: Resource> ResourceVTable for PhantomData<R> {
+impl<R: Resource> ResourceVTable for PhantomData<fn() -> R> {
     fn id(&self) -> ResourceId {
         R::id()
     }
@@ -154,6 +199,10 @@ impl<R: Resource> ResourceVTable for PhantomData<R> {
     fn type_name(&self) -> &'static str {
         type_name::<R>()
     }
+
+    fn layout(&self) -> Layout {
+        Layout::new::<R>()
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +223,17 @@ mod tests {
         assert_ne!(ResourceInfo::of::<A>().id(), ResourceInfo::of::<B>().id(),);
     }
 
+    #[test]
+    fn iter_registered() {
+        let [a, b] = [ResourceId::of::<A>(), ResourceId::of::<B>()];
+        let registered: Vec<_> =
+            ResourceInfo::iter_registered().map(|info| info.id()).collect();
+
+        assert!(registered.contains(&a) && registered.contains(&b));
+        assert!(registered.is_sorted());
+        assert_eq!(ResourceInfo::of_id(a).layout(), Layout::new::<A>());
+    }
+
     #[test]
     fn id_eq() {
         assert_eq!(ResourceInfo::of::<A>(), ResourceInfo::of::<A>());
@@ -0,0 +1,143 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Res, ResMut, Resource};
+use crate::access::{Level, WorldAccess};
+use crate::system::{ReadOnlySystemInput, SystemInput};
+use crate::world::{FromWorld, World, WorldPtr};
+
+/// A system input that creates its resource with [`FromWorld`] if it doesn't
+/// exist, such as `Init<Res<R>>` or `Init<ResMut<R>>`.
+///
+/// Otherwise running a system whose resource doesn't exist returns
+/// [`RunSystemError::ResourceNotFound`](crate::system::RunSystemError).
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Resource, Default)]
+/// struct Score(u32);
+///
+/// fn score(mut score: Init<ResMut<Score>>) {
+///     score.0 += 1;
+/// }
+///
+/// let mut world = World::new();
+///
+/// world.run_system(score).unwrap();
+/// world.run_system(score).unwrap();
+///
+/// assert_eq!(world.resource::<Score>().unwrap().0, 2);
+/// ```
+pub struct Init<T> {
+    inner: T,
+}
+
+impl<T> Init<T> {
+    /// Returns the wrapped system input.
+    pub fn into_inner(this: Self) -> T {
+        this.inner
+    }
+}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`].
+unsafe impl<R: Resource + FromWorld> SystemInput for Init<Res<'_, R>> {
+    type Output<'w, 's> = Init<Res<'w, R>>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource_or_init::<R>(Level::Read);
+    }
+
+    unsafe fn get<'w, 's>(
+        state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world is valid for this access
+        Init { inner: unsafe { Res::get(state, world) } }
+    }
+}
+
+/// # Safety
+///
+/// [`Res`] performs only immutable access.
+unsafe impl<R: Resource + FromWorld> ReadOnlySystemInput for Init<Res<'_, R>> {}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`].
+unsafe impl<R: Resource + FromWorld> SystemInput for Init<ResMut<'_, R>> {
+    type Output<'w, 's> = Init<ResMut<'w, R>>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource_or_init::<R>(Level::Write);
+    }
+
+    unsafe fn get<'w, 's>(
+        state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world is valid for this access
+        Init { inner: unsafe { ResMut::get(state, world) } }
+    }
+}
+
+impl<T> Deref for Init<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for Init<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Resource)]
+    struct Gravity(f32);
+
+    #[derive(Resource)]
+    struct Weight(f32);
+
+    impl FromWorld for Weight {
+        fn from_world(world: &mut World) -> Self {
+            Weight(world.resource::<Gravity>().unwrap().0 * 2.0)
+        }
+    }
+
+    fn weigh(weight: Init<Res<Weight>>, gravity: Res<Gravity>) -> f32 {
+        weight.0 + gravity.0
+    }
+
+    #[test]
+    fn init_missing_resource() {
+        let mut world = World::new();
+
+        let Err(RunSystemError::ResourceNotFound { resource, system }) =
+            world.run_system(weigh)
+        else {
+            panic!("the system shouldn't run without `Gravity`");
+        };
+
+        assert!(resource.ends_with("Gravity"));
+        assert!(system.contains("weigh"));
+        assert!(!world.has::<Weight>());
+
+        world.create(Gravity(1.5));
+
+        assert_eq!(world.run_system(weigh).unwrap(), 4.5);
+        assert!(world.has::<Weight>());
+    }
+}
@@ -7,12 +7,17 @@ use thiserror::Error;
 pub use worldlines_macros::Resource;
 
 pub use self::info::*;
+pub use self::init::*;
+pub use self::shared::*;
 pub(crate) use self::storage::*;
 use crate::access::{Level, WorldAccess};
+use crate::marker::MaybeSendSync;
 use crate::prelude::{World, WorldPtr};
 use crate::system::{ReadOnlySystemInput, SystemInput};
 
 mod info;
+mod init;
+mod shared;
 mod storage;
 
 /// Trait for unique ECS values.
@@ -22,8 +27,9 @@ mod storage;
 /// # Safety
 ///
 /// The implementation of [`Resource::id`] must use a static
-/// [`ResourceIdCell`] to store the id. The implementation must only create a
-/// [`ResourceIdCell`] for `Self`.
+/// [`ResourceIdCell`] to store the id, or call [`ResourceId::of_type`] for
+/// generic types. The implementation must only create a [`ResourceIdCell`]
+/// for `Self`.
 ///
 /// ```
 /// # use worldlines::prelude::*;
@@ -38,11 +44,24 @@ mod storage;
 ///     }
 /// }
 /// ```
-pub unsafe trait Resource: Send + Sync + 'static {
+pub unsafe trait Resource: MaybeSendSync + 'static {
     /// Returns the id of this resource.
     fn id() -> ResourceId;
 }
 
+/// Boxed values are resources, so a resource can be stored as a trait object
+/// and borrowed through the trait with `Res<Box<dyn Trait>>`. See
+/// [`World::create_as`].
+///
+/// # Safety
+///
+/// The id is stored with [`ResourceId::of_type`].
+unsafe impl<T: ?Sized + MaybeSendSync + 'static> Resource for Box<T> {
+    fn id() -> ResourceId {
+        ResourceId::of_type::<Self>()
+    }
+}
+
 /// A reference to a [resource](Resource) in a world.
 pub struct Res<'w, R: Resource> {
     inner: AtomicRef<'w, R>,
@@ -0,0 +1,295 @@
+use std::any::type_name;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+
+use super::{Res, Resource, ResourceError, ResourceId};
+use crate::access::{Level, WorldAccess};
+use crate::marker::MaybeSendSync;
+use crate::system::{ReadOnlySystemInput, SystemInput};
+use crate::world::{World, WorldPtr};
+
+/// A resource whose value can be shared between worlds.
+///
+/// Clones of a shared resource refer to the same value, so a clone can be
+/// created in another world, such as a render world that uses the asset cache
+/// of the main world. Borrows of the value are checked across all worlds that
+/// share it, which is in addition to the access validation of each world.
+///
+/// The value is borrowed with [`SharedRef`] and [`SharedMut`], which can also
+/// be used as system inputs. Within a world, their access is validated like
+/// [`Res`] and [`ResMut`](super::ResMut). Across worlds, borrowing a value that
+/// is already borrowed mutably elsewhere panics.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// struct AssetCache(Vec<&'static str>);
+///
+/// fn load(mut cache: SharedMut<AssetCache>) {
+///     cache.0.push("player.png");
+/// }
+///
+/// let cache = SharedResource::new(AssetCache(Vec::new()));
+/// let mut main = World::new();
+/// let mut render = World::new();
+///
+/// main.create(cache.clone());
+/// render.create(cache);
+/// main.run_system(load).unwrap();
+///
+/// let cache = render.resource::<SharedResource<AssetCache>>().unwrap();
+///
+/// assert_eq!(cache.borrow().unwrap().0, ["player.png"]);
+/// ```
+pub struct SharedResource<T> {
+    inner: Arc<AtomicRefCell<T>>,
+}
+
+/// A reference to the value of a [`SharedResource`].
+pub struct SharedRef<'a, T: MaybeSendSync + 'static> {
+    inner: AtomicRef<'a, T>,
+    /// Keeps the resource from being replaced when borrowed from a world.
+    _resource: Option<Res<'a, SharedResource<T>>>,
+}
+
+/// A mutable reference to the value of a [`SharedResource`].
+pub struct SharedMut<'a, T: MaybeSendSync + 'static> {
+    inner: AtomicRefMut<'a, T>,
+    /// See [`SharedRef`].
+    _resource: Option<Res<'a, SharedResource<T>>>,
+}
+
+impl<T> SharedResource<T> {
+    /// Creates a new shared resource.
+    pub fn new(value: T) -> Self {
+        Self { inner: Arc::new(AtomicRefCell::new(value)) }
+    }
+
+    /// Returns the amount of clones of this resource, including itself.
+    pub fn shares(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// Returns `true` if both resources share the same value.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the value if this is the only clone of this resource.
+    pub fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(AtomicRefCell::into_inner)
+            .map_err(|inner| Self { inner })
+    }
+}
+
+impl<T: MaybeSendSync + 'static> SharedResource<T> {
+    /// Immutably borrows the value.
+    ///
+    /// Returns an error if the value is mutably borrowed, possibly by another
+    /// world.
+    pub fn borrow(&self) -> Result<SharedRef<'_, T>, ResourceError> {
+        self.inner
+            .try_borrow()
+            .map(|inner| SharedRef { inner, _resource: None })
+            .map_err(|_| ResourceError::AlreadyBorrowed(type_name::<Self>()))
+    }
+
+    /// Mutably borrows the value.
+    ///
+    /// Returns an error if the value is borrowed, possibly by another world.
+    pub fn borrow_mut(&self) -> Result<SharedMut<'_, T>, ResourceError> {
+        self.inner
+            .try_borrow_mut()
+            .map(|inner| SharedMut { inner, _resource: None })
+            .map_err(|_| ResourceError::AlreadyBorrowed(type_name::<Self>()))
+    }
+
+    /// Borrows the cell of the resource in a world for the lifetime of the
+    /// world.
+    ///
+    /// # Safety
+    ///
+    /// The world must be valid for reads of this resource.
+    unsafe fn get(world: WorldPtr<'_>) -> (Res<'_, Self>, &AtomicRefCell<T>) {
+        // SAFETY: the caller ensures that the world is valid for this access
+        let resource = unsafe { world.as_ref().resource::<Self>() }
+            .unwrap_or_else(|error| panic!("{error}"));
+        // SAFETY: the value is kept alive by the resource, which can't be
+        // replaced while it is borrowed
+        let cell = unsafe { &*Arc::as_ptr(&(*resource).inner) };
+
+        (resource, cell)
+    }
+}
+
+// ---
+
+impl<T> Clone for SharedResource<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+/// # Safety
+///
+/// The id is stored with [`ResourceId::of_type`].
+unsafe impl<T: MaybeSendSync + 'static> Resource for SharedResource<T> {
+    fn id() -> ResourceId {
+        ResourceId::of_type::<Self>()
+    }
+}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`].
+unsafe impl<T: MaybeSendSync + 'static> SystemInput for SharedRef<'_, T> {
+    type Output<'w, 's> = SharedRef<'w, T>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource::<SharedResource<T>>(Level::Read);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world is valid for this access
+        let (resource, cell) = unsafe { SharedResource::get(world) };
+        let inner = cell.try_borrow().unwrap_or_else(|_| {
+            panic!("{} is mutably borrowed by another world", type_name::<T>())
+        });
+
+        SharedRef { inner, _resource: Some(resource) }
+    }
+}
+
+/// # Safety
+///
+/// [`SharedRef`] performs only immutable access.
+unsafe impl<T: MaybeSendSync + 'static> ReadOnlySystemInput
+    for SharedRef<'_, T>
+{
+}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`]. The resource
+/// itself is only read, but mutable access is declared so that borrows of the
+/// value within a world are validated.
+unsafe impl<T: MaybeSendSync + 'static> SystemInput for SharedMut<'_, T> {
+    type Output<'w, 's> = SharedMut<'w, T>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource::<SharedResource<T>>(Level::Write);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world is valid for this access
+        let (resource, cell) = unsafe { SharedResource::get(world) };
+        let inner = cell.try_borrow_mut().unwrap_or_else(|_| {
+            panic!("{} is borrowed by another world", type_name::<T>())
+        });
+
+        SharedMut { inner, _resource: Some(resource) }
+    }
+}
+
+impl<T: MaybeSendSync + 'static> Deref for SharedRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: MaybeSendSync + 'static> Deref for SharedMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: MaybeSendSync + 'static> DerefMut for SharedMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<T> fmt::Debug for SharedResource<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(&format!("SharedResource<{}>", type_name::<T>()))
+            .field("shares", &self.shares())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    struct Cache(Vec<u32>);
+
+    fn push(mut cache: SharedMut<Cache>) {
+        let len = cache.0.len() as u32;
+
+        cache.0.push(len);
+    }
+
+    #[test]
+    fn shared_between_worlds() {
+        let cache = SharedResource::new(Cache(Vec::new()));
+        let mut main = World::new();
+        let mut render = World::new();
+
+        main.create(cache.clone());
+        render.create(cache.clone());
+
+        assert_eq!(cache.shares(), 3);
+
+        main.run_system(push).unwrap();
+        render.run_system(push).unwrap();
+
+        let shared = main.resource::<SharedResource<Cache>>().unwrap();
+
+        assert!(shared.ptr_eq(&cache));
+        assert_eq!(shared.borrow().unwrap().0, [0, 1]);
+
+        {
+            let _borrow = cache.borrow_mut().unwrap();
+
+            assert!(shared.borrow().is_err());
+        }
+
+        drop(shared);
+        main.destroy::<SharedResource<Cache>>().unwrap();
+        render.destroy::<SharedResource<Cache>>().unwrap();
+
+        assert_eq!(cache.try_unwrap().ok().unwrap().0, [0, 1]);
+    }
+
+    #[test]
+    #[should_panic = "borrowed by another world"]
+    fn conflicting_borrow_across_worlds() {
+        let cache = SharedResource::new(Cache(Vec::new()));
+        let mut render = World::new();
+
+        render.create(cache.clone());
+
+        let _borrow = cache.borrow().unwrap();
+
+        render.run_system(push).unwrap();
+    }
+}
@@ -3,7 +3,7 @@ use std::any::{type_name, Any};
 
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
 
-use super::{Res, ResMut, Resource, ResourceError, ResourceId};
+use super::{Res, ResMut, Resource, ResourceError, ResourceId, ResourceInfo};
 use crate::storage::SparseMap;
 
 /// Storage for all resources.
@@ -15,9 +15,15 @@ pub struct Resources {
 /// Storage for a single resource.
 #[repr(transparent)]
 struct ResourceBox {
-    inner: AtomicRefCell<Box<dyn Any>>,
+    inner: AtomicRefCell<Box<AnyResource>>,
 }
 
+#[cfg(not(feature = "single_threaded"))]
+type AnyResource = dyn Any + Send + Sync;
+
+#[cfg(feature = "single_threaded")]
+type AnyResource = dyn Any;
+
 impl Resources {
     pub fn new() -> Self {
         let inner = SparseMap::new();
@@ -29,6 +35,10 @@ impl Resources {
         self.inner.contains(&ResourceId::of::<R>())
     }
 
+    pub fn contains_id(&self, id: ResourceId) -> bool {
+        self.inner.contains(&id)
+    }
+
     pub fn get<R: Resource>(&self) -> Result<Res<'_, R>, ResourceError> {
         self.inner
             .get(&ResourceId::of::<R>())
@@ -60,13 +70,54 @@ impl Resources {
             .map(|boxed| unsafe { boxed.into_inner() })
     }
 
+    /// Returns an iterator over the resources in storage.
+    pub fn iter(&self) -> impl Iterator<Item = ResourceInfo> + use<'_> {
+        self.inner.slots().enumerate().filter_map(|(index, resource)| {
+            resource.as_ref().map(|_| ResourceInfo::of_id(ResourceId(index)))
+        })
+    }
+
+    /// Removes all resources for which the predicate returns `false`.
+    ///
+    /// Returns the amount of removed resources.
+    pub fn retain(&mut self, mut f: impl FnMut(ResourceInfo) -> bool) -> usize {
+        let len = self.inner.len();
+
+        self.inner.retain(|index, _| f(ResourceInfo::of_id(ResourceId(index))));
+
+        len - self.inner.len()
+    }
+
     pub fn clear(&mut self) {
         self.inner.clear();
     }
+
+    /// Moves all resources out of another storage into this one.
+    ///
+    /// Resources contained by both are kept if `replace` is `false`.
+    pub fn append(&mut self, other: &mut Self, replace: bool) {
+        let ids: Vec<_> = other
+            .inner
+            .slots()
+            .enumerate()
+            .filter(|(_, resource)| resource.is_some())
+            .map(|(index, _)| ResourceId(index))
+            .collect();
+
+        for id in ids {
+            // SAFETY: the id was just retrieved from the storage
+            let resource =
+                unsafe { other.inner.remove(&id).unwrap_unchecked() };
+
+            if replace || !self.inner.contains(&id) {
+                self.inner.insert(id, resource);
+            }
+        }
+    }
 }
 
 impl ResourceBox {
-    fn new<R: Any>(resource: R) -> Self {
+    fn new<R: Resource>(resource: R) -> Self {
         let inner = AtomicRefCell::new(Box::new(resource) as _);
 
         Self { inner }
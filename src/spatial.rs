@@ -0,0 +1,363 @@
+//! Broad-phase spatial queries over entity positions.
+//!
+//! Requires the `spatial` feature.
+
+use std::any::{type_name, Any};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::{fmt, mem};
+
+use crate::component::Component;
+use crate::entity::{EntityHashMap, EntityId, EntityRef};
+use crate::world::{ErasedIndex, World};
+
+/// Trait for components that place an entity in space.
+///
+/// 2D positions can use `0.0` as their third coordinate.
+pub trait Position: Component {
+    /// Returns the position of the entity.
+    fn position(&self) -> [f32; 3];
+}
+
+/// A uniform grid of the entities with a [`Position`] component.
+///
+/// Created with [`World::add_spatial_index`] and retrieved with
+/// [`World::spatial_index`]. Entities are added and removed when the component
+/// is inserted, replaced or removed. Changing the value through a mutable
+/// reference isn't tracked, so call [`World::update_spatial_index`] after
+/// moving entities.
+pub struct SpatialIndex<P> {
+    cell_size: f32,
+    cells: HashMap<[i32; 3], Vec<EntityId>>,
+    positions: EntityHashMap<[f32; 3]>,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl World {
+    /// Adds a [`SpatialIndex`] of the entities with a [`Position`] component.
+    ///
+    /// The cell size should be around the radius of common queries. Entities
+    /// that already contain the component are indexed immediately. Replaces an
+    /// existing index of the component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell size isn't positive.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// #[derive(Component)]
+    /// struct Pos([f32; 3]);
+    ///
+    /// impl Position for Pos {
+    ///     fn position(&self) -> [f32; 3] {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::new();
+    ///
+    /// world.add_spatial_index::<Pos>(4.0);
+    ///
+    /// let near = world.spawn(Pos([1.0, 0.0, 0.0])).id();
+    ///
+    /// world.spawn(Pos([10.0, 0.0, 0.0]));
+    ///
+    /// let index = world.spatial_index::<Pos>().unwrap();
+    ///
+    /// assert_eq!(index.query_radius([0.0; 3], 2.0).collect::<Vec<_>>(), [near]);
+    /// assert_eq!(index.nearest([0.0; 3]), Some(near));
+    /// ```
+    pub fn add_spatial_index<P: Position>(&mut self, cell_size: f32) {
+        assert!(cell_size > 0.0, "spatial index cell size must be positive");
+
+        let mut index = SpatialIndex::<P>::new(cell_size);
+        let entities: Vec<_> = self
+            .components
+            .tables()
+            .filter(|(_, table)| table.components().contains(P::id()))
+            .flat_map(|(_, table)| table.entities().copied())
+            .collect();
+
+        for entity in entities {
+            // SAFETY: the entity is contained in a table, so it is alive
+            _ = index.insert(unsafe { EntityRef::new_unchecked(entity, self) });
+        }
+
+        self.listeners
+            .indexes
+            .retain(|(_, other)| !other.as_any().is::<SpatialIndex<P>>());
+        self.listeners.indexes.push((P::id(), Box::new(index)));
+    }
+
+    /// Returns the [`SpatialIndex`] of a component, if it was added.
+    pub fn spatial_index<P: Position>(&self) -> Option<&SpatialIndex<P>> {
+        self.listeners
+            .indexes
+            .iter()
+            .find_map(|(_, index)| index.as_any().downcast_ref())
+    }
+
+    /// Moves the entities in the [`SpatialIndex`] of a component to their
+    /// current positions.
+    ///
+    /// Returns the amount of entities that moved to another cell.
+    pub fn update_spatial_index<P: Position>(&mut self) -> usize {
+        let mut indexes = mem::take(&mut self.listeners.indexes);
+        let moved = indexes
+            .iter_mut()
+            .find_map(|(_, index)| {
+                index.as_any_mut().downcast_mut::<SpatialIndex<P>>()
+            })
+            .map_or(0, |index| index.update(self));
+
+        self.listeners.indexes = indexes;
+
+        moved
+    }
+}
+
+impl<P: Position> SpatialIndex<P> {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            positions: EntityHashMap::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the size of the cells of this grid.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Returns the amount of indexed entities.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if no entities are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the indexed position of an entity.
+    pub fn position(&self, entity: EntityId) -> Option<[f32; 3]> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Returns an iterator over the entities within a radius of a point.
+    pub fn query_radius(
+        &self,
+        center: [f32; 3],
+        radius: f32,
+    ) -> impl Iterator<Item = EntityId> + '_ {
+        let min = center.map(|c| c - radius);
+        let max = center.map(|c| c + radius);
+
+        self.query_aabb(min, max).filter(move |entity| {
+            distance_squared(self.positions[entity], center) <= radius * radius
+        })
+    }
+
+    /// Returns an iterator over the entities within an axis-aligned bounding
+    /// box.
+    pub fn query_aabb(
+        &self,
+        min: [f32; 3],
+        max: [f32; 3],
+    ) -> impl Iterator<Item = EntityId> + '_ {
+        let [min_cell, max_cell] = [min, max].map(|point| self.cell(point));
+
+        (min_cell[0]..=max_cell[0])
+            .flat_map(move |x| {
+                (min_cell[1]..=max_cell[1]).flat_map(move |y| {
+                    (min_cell[2]..=max_cell[2]).map(move |z| [x, y, z])
+                })
+            })
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |entity| {
+                let position = self.positions[entity];
+
+                (0..3).all(|i| min[i] <= position[i] && position[i] <= max[i])
+            })
+    }
+
+    /// Returns the entity closest to a point.
+    ///
+    /// Returns `None` if the index is empty.
+    pub fn nearest(&self, point: [f32; 3]) -> Option<EntityId> {
+        let center = self.cell(point);
+        // the farthest ring of cells that contains any entities
+        let max_ring =
+            self.cells.keys().map(|cell| ring(*cell, center)).max()?;
+        let mut nearest: Option<(EntityId, f32)> = None;
+
+        for r in 0..=max_ring {
+            // entities in this ring and beyond are at least this far away
+            let bound = r.saturating_sub(1) as f32 * self.cell_size;
+
+            if nearest.is_some_and(|(_, d)| d <= bound * bound) {
+                break;
+            }
+
+            for (_, entities) in
+                self.cells.iter().filter(|(cell, _)| ring(**cell, center) == r)
+            {
+                for &entity in entities {
+                    let d = distance_squared(self.positions[&entity], point);
+
+                    if nearest.is_none_or(|(_, nearest)| d < nearest) {
+                        nearest = Some((entity, d));
+                    }
+                }
+            }
+        }
+
+        nearest.map(|(entity, _)| entity)
+    }
+
+    fn cell(&self, point: [f32; 3]) -> [i32; 3] {
+        point.map(|c| (c / self.cell_size).floor() as i32)
+    }
+
+    fn add(&mut self, entity: EntityId, position: [f32; 3]) {
+        self.cells.entry(self.cell(position)).or_default().push(entity);
+        self.positions.insert(entity, position);
+    }
+
+    /// Re-reads the positions of all indexed entities.
+    fn update(&mut self, world: &World) -> usize {
+        let mut moved = 0;
+        let entities: Vec<_> = self.positions.keys().copied().collect();
+
+        for entity in entities {
+            let Some(position) = world
+                .entity(entity)
+                .ok()
+                .and_then(|entity| entity.get::<P>().ok())
+                .map(P::position)
+            else {
+                continue;
+            };
+            let old = self.positions[&entity];
+
+            if self.cell(old) != self.cell(position) {
+                self.remove(entity);
+                self.add(entity, position);
+
+                moved += 1;
+            } else {
+                self.positions.insert(entity, position);
+            }
+        }
+
+        moved
+    }
+}
+
+impl<P: Position> ErasedIndex for SpatialIndex<P> {
+    fn insert(&mut self, entity: EntityRef<'_>) -> Result<(), EntityId> {
+        if let Ok(component) = entity.get::<P>() {
+            self.remove(entity.id());
+            self.add(entity.id(), component.position());
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, entity: EntityId) {
+        let Some(position) = self.positions.remove(&entity) else {
+            return;
+        };
+        let cell = self.cell(position);
+
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            entities.retain(|&other| other != entity);
+
+            if entities.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The Chebyshev distance between two cells.
+fn ring(a: [i32; 3], b: [i32; 3]) -> u32 {
+    (0..3).map(|i| a[i].abs_diff(b[i])).max().unwrap_or_default()
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+impl<P> fmt::Debug for SpatialIndex<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(&format!("SpatialIndex<{}>", type_name::<P>()))
+            .field("cell_size", &self.cell_size)
+            .field("cells", &self.cells.len())
+            .field("entities", &self.positions.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Pos([f32; 3]);
+
+    impl Position for Pos {
+        fn position(&self) -> [f32; 3] {
+            self.0
+        }
+    }
+
+    #[test]
+    fn spatial_queries() {
+        let mut world = World::new();
+        let origin = world.spawn(Pos([0.0, 0.0, 0.0])).id();
+
+        world.add_spatial_index::<Pos>(2.0);
+
+        let near = world.spawn(Pos([1.5, 1.5, 0.0])).id();
+        let far = world.spawn(Pos([-9.0, 4.0, 0.0])).id();
+        let index = world.spatial_index::<Pos>().unwrap();
+
+        let mut within: Vec<_> = index.query_radius([0.0; 3], 2.5).collect();
+
+        within.sort();
+
+        assert_eq!(within, [origin, near]);
+        assert_eq!(
+            index
+                .query_aabb([-10.0, 0.0, 0.0], [-8.0, 5.0, 0.0])
+                .collect::<Vec<_>>(),
+            [far],
+        );
+        assert_eq!(index.nearest([-6.0, 3.0, 0.0]), Some(far));
+
+        world.despawn(origin).unwrap();
+        world.entity_mut(far).unwrap().get_mut::<Pos>().unwrap().0 =
+            [0.5, 0.0, 0.0];
+
+        assert_eq!(world.update_spatial_index::<Pos>(), 1);
+
+        let index = world.spatial_index::<Pos>().unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.nearest([0.0; 3]), Some(far));
+    }
+}
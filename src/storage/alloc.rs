@@ -0,0 +1,141 @@
+use std::alloc::{alloc, dealloc, realloc, Layout};
+use std::fmt;
+use std::ptr::NonNull;
+
+/// An allocator for the component storage of a world.
+///
+/// Set with [`World::new_in`]. All columns of a world's tables allocate through
+/// it, which covers the memory of every component.
+///
+/// [`World::new_in`]: crate::world::World::new_in
+///
+/// # Safety
+///
+/// Memory returned by [`WorldAllocator::allocate`] and
+/// [`WorldAllocator::grow`] must be valid for reads and writes of the
+/// requested layout until it is passed to [`WorldAllocator::deallocate`] or
+/// [`WorldAllocator::grow`].
+pub unsafe trait WorldAllocator: Send + Sync + 'static {
+    /// Allocates memory for a layout of non-zero size.
+    ///
+    /// Returns `None` if the allocation failed.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Deallocates memory.
+    ///
+    /// # Safety
+    ///
+    /// The memory must have been allocated by this allocator with the layout.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Grows an allocation to a larger layout, preserving its contents.
+    ///
+    /// Returns `None` if the allocation failed, in which case the old memory
+    /// is still valid.
+    ///
+    /// # Safety
+    ///
+    /// The memory must have been allocated by this allocator with the old
+    /// layout. The new layout must be at least as large and have the same
+    /// alignment.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        let new = self.allocate(new_layout)?;
+
+        // SAFETY: the caller ensures that the old memory is valid for the old
+        // layout, which is smaller than the new one
+        unsafe {
+            new.copy_from_nonoverlapping(ptr, old_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+
+        Some(new)
+    }
+}
+
+/// The default [`WorldAllocator`], which uses the global allocator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalAllocator;
+
+/// # Safety
+///
+/// Forwards to the global allocator.
+unsafe impl WorldAllocator for GlobalAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        // SAFETY: the layout is of non-zero size
+        NonNull::new(unsafe { alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // SAFETY: the caller ensures that the memory was allocated above
+        unsafe { dealloc(ptr.as_ptr(), layout) };
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        // SAFETY: the caller ensures that the memory was allocated above and
+        // that the alignment is the same
+        NonNull::new(unsafe {
+            realloc(ptr.as_ptr(), old_layout, new_layout.size())
+        })
+    }
+}
+
+impl fmt::Debug for dyn WorldAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorldAllocator").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::prelude::*;
+
+    /// Counts the bytes currently allocated through it.
+    #[derive(Default)]
+    struct Counting(Arc<AtomicUsize>);
+
+    unsafe impl WorldAllocator for Counting {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            self.0.fetch_add(layout.size(), Ordering::Relaxed);
+
+            GlobalAllocator.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.fetch_sub(layout.size(), Ordering::Relaxed);
+
+            unsafe { GlobalAllocator.deallocate(ptr, layout) };
+        }
+    }
+
+    #[derive(Component)]
+    struct Position(#[allow(unused)] [f32; 3]);
+
+    #[test]
+    fn world_allocator() {
+        let counting = Counting::default();
+        let allocated = counting.0.clone();
+        let mut world = World::new_in(counting);
+
+        world.spawn_iter((0..100).map(|_| Position([0.0; 3])));
+
+        assert!(allocated.load(Ordering::Relaxed) >= 100 * 12);
+
+        drop(world);
+
+        assert_eq!(allocated.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,186 @@
+use std::iter;
+
+/// A growable set of `usize`'s stored as bits.
+///
+/// Trailing empty words are always removed, so equal sets have equal words.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+const BITS: usize = u64::BITS as usize;
+
+impl BitSet {
+    /// Creates a new empty bitset.
+    pub const fn new() -> Self {
+        let words = Vec::new();
+
+        Self { words }
+    }
+
+    /// Returns `true` if the set contains no indices.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns `true` if the set contains the index.
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / BITS)
+            .is_some_and(|word| word & (1 << (index % BITS)) != 0)
+    }
+
+    /// Inserts an index into the set.
+    pub fn insert(&mut self, index: usize) {
+        let word = index / BITS;
+
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        self.words[word] |= 1 << (index % BITS);
+    }
+
+    /// Removes an index from the set.
+    pub fn remove(&mut self, index: usize) {
+        if let Some(word) = self.words.get_mut(index / BITS) {
+            *word &= !(1 << (index % BITS));
+        }
+
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    /// Returns an iterator over the indices in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + use<'_> {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+
+            iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+
+                    word &= word - 1;
+
+                    i * BITS + bit
+                })
+            })
+        })
+    }
+
+    /// Returns a new set of the indices in either `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        let (longer, shorter) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = longer.words.clone();
+
+        for (word, other) in words.iter_mut().zip(&shorter.words) {
+            *word |= other;
+        }
+
+        Self { words }
+    }
+
+    /// Returns a new set of the indices in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut words: Vec<_> = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(lhs, rhs)| lhs & rhs)
+            .collect();
+
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+
+        Self { words }
+    }
+
+    /// Returns `true` if every index in `self` is also in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.len() <= other.words.len()
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(lhs, rhs)| lhs & !rhs == 0)
+    }
+
+    /// Returns `true` if `self` and `other` share no indices.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(lhs, rhs)| lhs & rhs == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove() {
+        let mut set = BitSet::new();
+
+        set.insert(3);
+        set.insert(130);
+
+        assert!(set.contains(3));
+        assert!(set.contains(130));
+        assert!(!set.contains(4));
+
+        set.remove(130);
+
+        assert!(!set.contains(130));
+        assert_eq!(set, {
+            let mut set = BitSet::new();
+
+            set.insert(3);
+            set
+        });
+    }
+
+    #[test]
+    fn iter_intersection() {
+        let mut lhs = BitSet::new();
+        let mut rhs = BitSet::new();
+
+        for index in [0, 5, 64, 130] {
+            lhs.insert(index);
+        }
+
+        for index in [5, 130, 200] {
+            rhs.insert(index);
+        }
+
+        assert_eq!(lhs.iter().collect::<Vec<_>>(), [0, 5, 64, 130]);
+        assert_eq!(lhs.intersection(&rhs).iter().collect::<Vec<_>>(), [5, 130]);
+
+        lhs.remove(5);
+        lhs.remove(130);
+
+        assert!(lhs.intersection(&rhs).is_empty());
+    }
+
+    #[test]
+    fn subset_disjoint() {
+        let mut a = BitSet::new();
+        let mut ab = BitSet::new();
+        let mut c = BitSet::new();
+
+        a.insert(1);
+        ab.insert(1);
+        ab.insert(70);
+        c.insert(200);
+
+        assert!(a.is_subset(&ab));
+        assert!(!ab.is_subset(&a));
+        assert!(BitSet::new().is_subset(&a));
+
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&ab));
+    }
+}
@@ -1,41 +1,108 @@
-use std::alloc::{alloc, dealloc, realloc, Layout};
-use std::fmt;
+use std::alloc::Layout;
 use std::ptr::NonNull;
+use std::sync::Arc;
+use std::{fmt, slice};
 
-use super::{SparseIndex, TableRow};
-use crate::component::ComponentInfo;
+use super::{SparseIndex, TableRow, WorldAllocator};
+use crate::component::{Component, ComponentInfo};
 use crate::prelude::ComponentVTable;
 
 /// Storage for a single component type.
+///
+/// Components are stored contiguously, without padding between them,
+/// starting at an address aligned to [`ComponentVTable::column_align`].
 pub struct Column {
     component: ComponentInfo,
     capacity: usize,
     ptr: NonNull<u8>,
+    alloc: Arc<dyn WorldAllocator>,
 }
 
+// SAFETY: the column owns its components, which are `Send` and `Sync` as
+// required by `Component`
+#[cfg(not(feature = "single_threaded"))]
+unsafe impl Send for Column {}
+
+// SAFETY: see above
+#[cfg(not(feature = "single_threaded"))]
+unsafe impl Sync for Column {}
+
 impl Column {
     /// Creates an empty column without allocating.
-    pub fn new(component: ComponentInfo) -> Self {
+    pub fn new(
+        component: ComponentInfo,
+        alloc: Arc<dyn WorldAllocator>,
+    ) -> Self {
         let capacity =
             if component.layout().size() == 0 { usize::MAX } else { 0 };
         let ptr = NonNull::dangling();
 
-        Self { component, capacity, ptr }
+        Self { component, capacity, ptr, alloc }
     }
 
     /// Creates a new column with at least the specified capacity.
-    pub fn with_capacity(component: ComponentInfo, capacity: usize) -> Self {
-        let mut new = Self::new(component);
+    pub fn with_capacity(
+        component: ComponentInfo,
+        capacity: usize,
+        alloc: Arc<dyn WorldAllocator>,
+    ) -> Self {
+        let mut new = Self::new(component, alloc);
 
         new.grow(capacity);
 
         new
     }
 
+    /// Returns the component stored in this column.
+    pub fn component(&self) -> ComponentInfo {
+        self.component
+    }
+
+    /// Returns the capacity of this column.
+    ///
+    /// Is [`usize::MAX`] for zero-sized components.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     fn is_allocated(&self) -> bool {
         self.ptr != NonNull::dangling()
     }
 
+    /// Returns the components of the first `len` rows as a slice.
+    ///
+    /// The slice is aligned to [`ComponentVTable::column_align`].
+    ///
+    /// # Safety
+    ///
+    /// The first `len` rows must be initialized and not be mutably borrowed
+    /// for the returned lifetime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this column doesn't store `C`.
+    pub unsafe fn as_slice<C: Component>(&self, len: usize) -> &[C] {
+        assert_eq!(
+            self.component,
+            C::id(),
+            "column of {} accessed as {}",
+            self.component,
+            std::any::type_name::<C>(),
+        );
+        debug_assert!(len <= self.capacity);
+
+        let ptr = if self.is_allocated() {
+            self.ptr.cast()
+        } else {
+            // zero-sized or empty, but the pointer must still be aligned
+            NonNull::<C>::dangling()
+        };
+
+        // SAFETY: the caller ensures that the rows are initialized and that
+        // there are no mutable borrows
+        unsafe { slice::from_raw_parts(ptr.as_ptr(), len) }
+    }
+
     /// Returns a pointer to the component for a row.
     ///
     /// Returns `None` if the entity is not within bounds.
@@ -85,18 +152,93 @@ impl Column {
         })
     }
 
-    /// Writes a component to a row from a component pointer.
+    /// Initializes the component at a row by moving it out of a pointer.
     ///
-    /// Will reallocate if the row is out of bounds.
+    /// The previous bytes of the row aren't read or dropped. Will reallocate if
+    /// the row is out of bounds.
     ///
     /// # Safety
     ///
     /// The pointer must refer to a valid instance of the component this column
-    /// was created for, and must not overlap.
-    pub unsafe fn write(&mut self, row: TableRow, ptr: NonNull<u8>) {
+    /// was created for, and must not overlap. The value is moved, so it must
+    /// not be used or dropped afterwards.
+    pub unsafe fn init_component(&mut self, row: TableRow, src: NonNull<u8>) {
         unsafe {
             self.get_or_alloc(row)
-                .copy_from_nonoverlapping(ptr, self.component.layout().size());
+                .copy_from_nonoverlapping(src, self.component.layout().size());
+        }
+    }
+
+    /// Replaces the component at a row with one moved out of a pointer,
+    /// dropping the previous value.
+    ///
+    /// The new value is written even if dropping the previous value panics, so
+    /// the row is never left uninitialized.
+    ///
+    /// # Safety
+    ///
+    /// The row must be within bounds and initialized. See
+    /// [`Column::init_component`].
+    pub unsafe fn replace_component(
+        &mut self,
+        row: TableRow,
+        src: NonNull<u8>,
+    ) {
+        /// Writes a component when dropped, including during unwinding.
+        struct WriteOnDrop {
+            dst: NonNull<u8>,
+            src: NonNull<u8>,
+            size: usize,
+        }
+
+        impl Drop for WriteOnDrop {
+            fn drop(&mut self) {
+                // SAFETY: see `Column::replace_component`
+                unsafe {
+                    self.dst.copy_from_nonoverlapping(self.src, self.size)
+                }
+            }
+        }
+
+        // SAFETY: the caller ensures that the row is within bounds
+        let dst = unsafe { self.get_unchecked_mut(row) };
+        let _write =
+            WriteOnDrop { dst, src, size: self.component.layout().size() };
+
+        // SAFETY: the caller ensures that the previous value is initialized,
+        // and it is overwritten without being read afterwards
+        unsafe { self.component.drop()(dst.as_ptr()) };
+    }
+
+    /// Moves the component at a row into a pointer. The row is uninitialized
+    /// afterwards.
+    ///
+    /// # Safety
+    ///
+    /// The row must be within bounds and initialized. The pointer must be valid
+    /// for writes of the component and must not overlap.
+    pub unsafe fn move_component(&mut self, row: TableRow, dst: NonNull<u8>) {
+        unsafe {
+            dst.copy_from_nonoverlapping(
+                self.get_unchecked_mut(row),
+                self.component.layout().size(),
+            );
+        }
+    }
+
+    /// Moves a component from one row to another. The destination is
+    /// overwritten without being dropped.
+    ///
+    /// # Safety
+    ///
+    /// Both rows must be within bounds and the component at `from` must be
+    /// initialized. The component at `from` is uninitialized afterwards.
+    pub unsafe fn move_row(&mut self, from: TableRow, to: TableRow) {
+        unsafe {
+            let src = self.get_unchecked(from);
+            let dst = self.get_unchecked_mut(to);
+
+            dst.copy_from_nonoverlapping(src, self.component.layout().size());
         }
     }
 
@@ -127,32 +269,77 @@ impl Column {
         // TODO: optimize allocation strategy
         let new_capacity = (self.capacity + additional)
             .max(self.capacity.checked_mul(2).unwrap_or_default());
-        let new_layout = array(self.component.layout(), new_capacity);
+        let new_layout = array(self.component, new_capacity);
 
         if self.is_allocated() {
-            let old_layout = array(self.component.layout(), self.capacity);
+            let old_layout = array(self.component, self.capacity);
 
-            self.ptr = NonNull::new(unsafe {
-                realloc(self.ptr.as_ptr(), old_layout, new_layout.size())
-            })
-            .expect("global allocation failure");
+            self.ptr =
+                unsafe { self.alloc.grow(self.ptr, old_layout, new_layout) }
+                    .expect("world allocation failure");
         } else {
-            self.ptr = NonNull::new(unsafe { alloc(new_layout) })
-                .expect("global allocation failure");
+            self.ptr = self
+                .alloc
+                .allocate(new_layout)
+                .expect("world allocation failure");
         }
 
         self.capacity = new_capacity;
     }
+
+    /// Shrinks storage to a capacity, returning the amount of freed bytes.
+    ///
+    /// Does nothing if the capacity isn't smaller than the current one, or
+    /// for zero-sized components.
+    ///
+    /// # Safety
+    ///
+    /// The rows at and after the capacity must not be initialized.
+    pub unsafe fn shrink_to(&mut self, capacity: usize) -> usize {
+        // if ZST
+        if self.capacity == usize::MAX || capacity >= self.capacity {
+            return 0;
+        }
+
+        let old_layout = array(self.component, self.capacity);
+        let new_layout = array(self.component, capacity);
+
+        if self.is_allocated() {
+            let ptr = if capacity == 0 {
+                NonNull::dangling()
+            } else {
+                let ptr = self
+                    .alloc
+                    .allocate(new_layout)
+                    .expect("world allocation failure");
+
+                // SAFETY: the initialized rows are within the new layout
+                unsafe {
+                    ptr.copy_from_nonoverlapping(self.ptr, new_layout.size())
+                };
+
+                ptr
+            };
+
+            // SAFETY: the memory was allocated with the old layout
+            unsafe { self.alloc.deallocate(self.ptr, old_layout) };
+            self.ptr = ptr;
+        }
+
+        self.capacity = capacity;
+
+        old_layout.size() - new_layout.size()
+    }
 }
 
-/// The layout of an array of items size `n`.
-fn array(layout: Layout, n: usize) -> Layout {
+/// The layout of a column of `n` components.
+fn array(component: ComponentInfo, n: usize) -> Layout {
     // from [Bevy](https://github.com/bevyengine/bevy/blob/dcb191bb1837027156584260c3999558dd6368c0/crates/bevy_ecs/src/storage/blob_vec.rs#L457).
 
-    let align = layout.align();
-    let size = (layout.size() + padding_needed_for(layout, align)) * n;
+    let layout = component.layout();
+    let size = (layout.size() + padding_needed_for(layout, layout.align())) * n;
 
-    Layout::from_size_align(size, align).unwrap()
+    Layout::from_size_align(size, component.column_align()).unwrap()
 }
 
 fn padding_needed_for(layout: Layout, align: usize) -> usize {
@@ -167,10 +354,8 @@ impl Drop for Column {
     fn drop(&mut self) {
         if self.is_allocated() {
             unsafe {
-                dealloc(
-                    self.ptr.as_ptr(),
-                    array(self.component.layout(), self.capacity),
-                )
+                self.alloc
+                    .deallocate(self.ptr, array(self.component, self.capacity))
             };
         }
     }
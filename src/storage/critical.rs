@@ -0,0 +1,37 @@
+use std::process;
+
+/// A guard that aborts the process if a panic unwinds through it.
+///
+/// Structural changes run user code, such as [`Default`] implementations of
+/// required components and [`Drop`] implementations of replaced components,
+/// while rows are only partially initialized. Unwinding from there would
+/// leave uninitialized components behind that are later dropped, so such
+/// sections are guarded by this instead. Hooks are only run once storage is
+/// consistent, and never inside a critical section.
+#[must_use = "the section ends when the guard is dropped"]
+pub(crate) struct CriticalSection {
+    /// What storage was doing, reported when aborting.
+    operation: &'static str,
+}
+
+impl CriticalSection {
+    pub const fn new(operation: &'static str) -> Self {
+        Self { operation }
+    }
+
+    /// Ends the section without aborting.
+    pub fn end(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for CriticalSection {
+    fn drop(&mut self) {
+        eprintln!(
+            "panicked while {}, which would leave storage corrupted; aborting",
+            self.operation,
+        );
+
+        process::abort();
+    }
+}
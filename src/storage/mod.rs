@@ -1,10 +1,16 @@
+pub use self::alloc::*;
+pub use self::bitset::*;
 pub use self::column::*;
+pub(crate) use self::critical::*;
 pub use self::sparse::*;
 pub use self::table::*;
 pub use self::type_id_hasher::*;
 pub use self::usize_hasher::*;
 
+mod alloc;
+mod bitset;
 mod column;
+mod critical;
 mod sparse;
 mod table;
 mod type_id_hasher;
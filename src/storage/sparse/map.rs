@@ -120,6 +120,30 @@ impl<K: SparseIndex, V> SparseMap<K, V> {
             .inspect(|_| self.len -= 1)
     }
 
+    /// Removes empty slots from the end of the map.
+    pub fn trim(&mut self) {
+        while self.inner.last().is_some_and(Option::is_none) {
+            self.inner.pop();
+        }
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Removes all values for which the predicate returns `false`.
+    ///
+    /// The predicate is passed the sparse index of each value.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut V) -> bool) {
+        for (i, slot) in self.inner.iter_mut().enumerate() {
+            if slot.as_mut().is_some_and(|value| !f(i, value)) {
+                *slot = None;
+                self.len -= 1;
+            }
+        }
+    }
+
     /// Removes all values from the map.
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -78,7 +78,7 @@ mod tests {
         map.insert(1, 1);
         map.insert(3, 3);
 
-        fn iter_asserts<'a>(mut iter: impl ExactSizeIterator<Item = usize>) {
+        fn iter_asserts(mut iter: impl ExactSizeIterator<Item = usize>) {
             assert_eq!(iter.len(), 3);
             assert_eq!(iter.next(), Some(0));
 
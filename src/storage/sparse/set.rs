@@ -53,11 +53,15 @@ impl<I: SparseIndex> SparseSet<I> {
     {
         self.inner
             .get(index.sparse_index())
-            .map(Option::as_ref)
-            .flatten()
+            .and_then(Option::as_ref)
             .is_some_and(|i| index == i)
     }
 
+    /// Returns the index stored at a sparse index.
+    pub fn get(&self, sparse: usize) -> Option<&I> {
+        self.inner.get(sparse).and_then(Option::as_ref)
+    }
+
     /// Inserts an index into the set.
     ///
     /// Returns the previous value if it exists.
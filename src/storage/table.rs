@@ -1,8 +1,9 @@
-use std::mem;
+use std::mem::ManuallyDrop;
 use std::ptr::NonNull;
+use std::sync::Arc;
 
-use super::{Column, SparseIndex, SparseIter, SparseMap};
-use crate::component::{Component, ComponentId, ComponentSet};
+use super::{Column, SparseIndex, SparseIter, SparseMap, WorldAllocator};
+use crate::component::{Component, ComponentId, ComponentSet, ComponentVTable};
 use crate::entity::EntityId;
 
 /// Storage for entities with the same components.
@@ -11,6 +12,11 @@ pub struct Table {
     components: ComponentSet,
     entities: SparseMap<TableRow, EntityId>,
     columns: SparseMap<ComponentId, Column>,
+    /// Rows of removed entities whose components haven't been dropped yet.
+    buried: Vec<TableRow>,
+    /// The row after the last buried row, which can't be reused until the
+    /// buried components are dropped.
+    buried_end: usize,
 }
 
 /// The row in [`Table.entities`](Table) of an entity.
@@ -21,18 +27,27 @@ pub struct TableRow(pub usize);
 impl Table {
     const DEFAULT_CAPACITY: usize = 16;
 
-    /// Creates a new table with at least the specified capacity.
-    pub fn with_capacity(components: ComponentSet, capacity: usize) -> Self {
+    /// Creates a new table with at least the specified capacity, allocating
+    /// its columns with the allocator.
+    pub fn with_capacity(
+        components: ComponentSet,
+        capacity: usize,
+        alloc: &Arc<dyn WorldAllocator>,
+    ) -> Self {
         let capacity = capacity.max(Self::DEFAULT_CAPACITY);
         let columns = components
             .slots()
             .map(|slot| {
-                slot.map(|component| Column::with_capacity(component, capacity))
+                slot.map(|component| {
+                    Column::with_capacity(component, capacity, alloc.clone())
+                })
             })
             .collect();
         let entities = SparseMap::new();
+        let buried = Vec::new();
+        let buried_end = 0;
 
-        Self { components, entities, columns }
+        Self { components, entities, columns, buried, buried_end }
     }
 
     /// Returns a reference to the component set of this table.
@@ -45,11 +60,159 @@ impl Table {
         self.entities.iter()
     }
 
+    /// Returns the rows and entities in this table.
+    pub fn rows(&self) -> impl Iterator<Item = (TableRow, EntityId)> + '_ {
+        self.entities.slots().enumerate().filter_map(|(i, entity)| {
+            entity.map(|entity| (TableRow(i), entity))
+        })
+    }
+
     /// Returns the amount of entities in this table.
     pub fn len(&self) -> usize {
         self.entities.len()
     }
 
+    /// Returns the amount of rows in this table, including empty rows.
+    pub fn rows_len(&self) -> usize {
+        self.entities.slots().len()
+    }
+
+    /// Returns the amount of empty rows left by removed entities.
+    pub fn holes(&self) -> usize {
+        self.rows_len() - self.len()
+    }
+
+    /// Returns the row the next pushed entity will be placed at.
+    ///
+    /// Rows are only ever appended, so removing entities leaves holes until
+    /// the table is [compacted](Table::compact_one). Rows are also appended
+    /// after [buried](Table::bury) rows.
+    pub fn next_row(&self) -> TableRow {
+        TableRow(self.rows_len().max(self.buried_end))
+    }
+
+    /// Returns the amount of rows with components that haven't been dropped.
+    pub fn buried(&self) -> usize {
+        self.buried.len()
+    }
+
+    /// Returns the columns of this table.
+    pub fn columns(&self) -> SparseIter<'_, Column> {
+        self.columns.iter()
+    }
+
+    /// Returns the components of a column as a slice, in the order of
+    /// [`Table::entities`].
+    ///
+    /// Returns `None` if this table doesn't contain the component or has
+    /// [holes](Table::holes), as the slice must be contiguous.
+    ///
+    /// # Safety
+    ///
+    /// The column must not be mutably borrowed for the returned lifetime.
+    pub unsafe fn as_slice<C: Component>(&self) -> Option<&[C]> {
+        if self.holes() > 0 {
+            return None;
+        }
+
+        let column = self.columns.get(&C::id())?;
+
+        // SAFETY: there are no holes, so every row is initialized, and the
+        // caller ensures that there are no mutable borrows
+        Some(unsafe { column.as_slice(self.rows_len()) })
+    }
+
+    /// Copies the bytes of the components of a column into a buffer, in the
+    /// order of [`Table::entities`].
+    ///
+    /// Returns the amount of copied bytes, or `None` if this table doesn't
+    /// contain the component.
+    ///
+    /// # Safety
+    ///
+    /// The column must not be mutably borrowed. The component must not
+    /// contain padding or other uninitialized bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is smaller than the components of this table.
+    pub unsafe fn copy_column_into(
+        &self,
+        component: ComponentId,
+        out: &mut [u8],
+    ) -> Option<usize> {
+        let info = self.columns.get(&component)?.component();
+        let bytes = info.layout().size() * self.len();
+
+        assert!(
+            out.len() >= bytes,
+            "buffer of {} bytes is too small for {bytes} bytes of {info}",
+            out.len(),
+        );
+
+        // SAFETY: the table contains the component and the buffer is large
+        // enough, and the caller ensures that the bytes are initialized and
+        // that there are no mutable borrows
+        unsafe { self.copy_column_to(component, NonNull::from(out).cast()) };
+
+        Some(bytes)
+    }
+
+    /// Copies the components of a column to a pointer, in the order of
+    /// [`Table::entities`].
+    ///
+    /// Each run of consecutive rows is copied at once, skipping
+    /// [holes](Table::holes).
+    ///
+    /// # Safety
+    ///
+    /// The table must contain the component and the column must not be
+    /// mutably borrowed. The pointer must be valid for writes of
+    /// [`Table::len`] components.
+    pub unsafe fn copy_column_to(
+        &self,
+        component: ComponentId,
+        dst: NonNull<u8>,
+    ) {
+        // SAFETY: the caller ensures that the table contains the component
+        let column = unsafe { self.columns.get(&component).unwrap_unchecked() };
+        let size = column.component().layout().size();
+        let rows = self.rows_len();
+        let mut row = 0;
+        let mut written = 0;
+
+        if size == 0 {
+            return;
+        }
+
+        while row < rows {
+            if self.entity(TableRow(row)).is_none() {
+                row += 1;
+
+                continue;
+            }
+
+            let start = row;
+
+            while row < rows && self.entity(TableRow(row)).is_some() {
+                row += 1;
+            }
+
+            let bytes = (row - start) * size;
+
+            // SAFETY: the rows are initialized and the caller ensures that the
+            // pointer is valid for all of the components of this table
+            unsafe {
+                dst.byte_add(written).copy_from_nonoverlapping(
+                    column.get_unchecked(TableRow(start)),
+                    bytes,
+                );
+            }
+
+            written += bytes;
+        }
+    }
+
     /// Get the entity at the row.
     pub fn entity(&self, row: TableRow) -> Option<EntityId> {
         self.entities.get(&row).copied()
@@ -68,7 +231,19 @@ impl Table {
              table causes undefined behavior",
         );
 
-        let row = TableRow(self.entities.len());
+        unsafe { self.push_new(entity) }
+    }
+
+    /// Pushes a newly allocated entity to this table.
+    ///
+    /// Unlike [`Table::push`], doesn't check that the table doesn't contain
+    /// the entity in debug builds, which takes linear time.
+    ///
+    /// # Safety
+    ///
+    /// The entity must not exist in the table.
+    pub unsafe fn push_new(&mut self, entity: EntityId) -> TableRow {
+        let row = self.next_row();
 
         self.entities.insert(row, entity);
 
@@ -79,7 +254,43 @@ impl Table {
     ///
     /// Does not drop components.
     pub fn remove(&mut self, row: TableRow) -> Option<EntityId> {
-        self.entities.remove(&row)
+        let entity = self.entities.remove(&row);
+
+        self.entities.trim();
+
+        entity
+    }
+
+    /// Moves the last entity of this table into the first hole.
+    ///
+    /// Returns the moved entity and its new row, or `None` if there are no
+    /// holes to fill.
+    ///
+    /// Drops [buried](Table::bury) components first, as their rows are holes.
+    pub fn compact_one(&mut self) -> Option<(EntityId, TableRow)> {
+        self.drop_buried();
+
+        let hole = self.entities.slots().position(Option::is_none)?;
+        let last = self.rows_len() - 1;
+
+        // trailing holes are always trimmed, so the last row is filled and
+        // comes after the hole
+        debug_assert!(hole < last);
+
+        let (from, to) = (TableRow(last), TableRow(hole));
+        // SAFETY: the last row is filled
+        let entity = unsafe { self.entities.remove(&from).unwrap_unchecked() };
+
+        for column in &mut self.columns {
+            // SAFETY: both rows are within bounds of every column and the
+            // entity at `from` is initialized
+            unsafe { column.move_row(from, to) };
+        }
+
+        self.entities.insert(to, entity);
+        self.entities.trim();
+
+        Some((entity, to))
     }
 
     /// Returns a pointer to a component of an entity.
@@ -119,34 +330,38 @@ impl Table {
         }
     }
 
-    /// Writes a component value to an entity. The previous value is not read,
-    /// so this can be used to initialize the component.
+    /// Initializes a component of an entity with a value. The previous value
+    /// is not read or dropped.
+    ///
+    /// Returns `Some` if this table contains the component.
     ///
-    /// Returns `Some` if the entity exists and contains the component.
-    pub unsafe fn write<C: Component>(
+    /// # Safety
+    ///
+    /// The entity must exist in this table at the row.
+    pub unsafe fn init<C: Component>(
         &mut self,
         row: TableRow,
-        component: ComponentId,
-        mut value: C,
+        value: C,
     ) -> Option<()> {
+        let mut value = ManuallyDrop::new(value);
+
+        // SAFETY: the value is moved into the table, and `ManuallyDrop`
+        // ensures that it doesn't get dropped here
         unsafe {
-            self.write_ptr(row, component, NonNull::from(&mut value).cast())
-                // this write has move semantics, so call `forget` to ensure
-                // that `component` does not get dropped
-                .inspect(|_| mem::forget(value))
+            self.init_component(row, C::id(), NonNull::from(&mut *value).cast())
         }
     }
 
-    /// Copies the bytes of a component pointer to an entity. The previous value
-    /// is not read, so this can be used to initialize the component.
+    /// Initializes a component of an entity by moving it out of a pointer. The
+    /// previous value is not read or dropped.
     ///
     /// Returns `Some` if this table contains the component.
     ///
     /// # Safety
     ///
-    /// The pointer must be a valid instance of the component the index refers
-    /// to.
-    pub unsafe fn write_ptr(
+    /// The pointer must be to a valid instance of the component, which must
+    /// not be used or dropped afterwards.
+    pub unsafe fn init_component(
         &mut self,
         row: TableRow,
         component: ComponentId,
@@ -154,28 +369,47 @@ impl Table {
     ) -> Option<()> {
         self.columns
             .get_mut(&component)
-            .map(|column| unsafe { column.write(row, value) })
+            .map(|column| unsafe { column.init_component(row, value) })
     }
 
-    /// Replaces the previous component with a new value.
+    /// Replaces a component of an entity by moving it out of a pointer,
+    /// dropping the previous value.
+    ///
+    /// Returns `Some` if this table contains the component. See
+    /// [`Column::replace_component`].
     ///
     /// # Safety
     ///
-    /// The previous value must be initialized. The provided component index
-    /// must refer to `C`.
-    pub unsafe fn replace<C: Component>(
+    /// The previous value must be initialized. See [`Table::init_component`].
+    pub unsafe fn replace_component(
         &mut self,
         row: TableRow,
         component: ComponentId,
-        value: C,
-    ) -> C {
-        unsafe {
-            let prev = self.get_unchecked_mut(row, component).cast().read();
-
-            self.write(row, component, value);
+        value: NonNull<u8>,
+    ) -> Option<()> {
+        self.columns
+            .get_mut(&component)
+            .map(|column| unsafe { column.replace_component(row, value) })
+    }
 
-            prev
-        }
+    /// Moves a component of an entity into a pointer, leaving it
+    /// uninitialized.
+    ///
+    /// Returns `Some` if this table contains the component.
+    ///
+    /// # Safety
+    ///
+    /// The component must be initialized. The pointer must be valid for writes
+    /// of the component and must not overlap.
+    pub unsafe fn move_component(
+        &mut self,
+        row: TableRow,
+        component: ComponentId,
+        dst: NonNull<u8>,
+    ) -> Option<()> {
+        self.columns
+            .get_mut(&component)
+            .map(|column| unsafe { column.move_component(row, dst) })
     }
 
     /// Drops all the components of an entity at the row and removes it from the
@@ -192,10 +426,63 @@ impl Table {
         for column in &mut self.columns {
             _ = unsafe { column.free(row) };
         }
+
+        self.entities.trim();
+    }
+
+    /// Removes the entity at the row like [`Table::free`], but drops its
+    /// components later in [`Table::drop_buried`].
+    ///
+    /// The row isn't reused until then.
+    ///
+    /// # Safety
+    ///
+    /// The table must contain the entity at the row.
+    pub unsafe fn bury(&mut self, row: TableRow) {
+        // SAFETY: the caller ensures that this table contains the entity at the
+        // provided row
+        unsafe { self.entities.remove(&row).unwrap_unchecked() };
+
+        self.entities.trim();
+        self.buried.push(row);
+        self.buried_end = self.buried_end.max(row.0 + 1);
+    }
+
+    /// Drops the components of all [buried](Table::bury) rows.
+    pub fn drop_buried(&mut self) {
+        for row in self.buried.drain(..) {
+            for column in &mut self.columns {
+                // SAFETY: the row was buried, so its components are initialized
+                // and the row wasn't reused
+                _ = unsafe { column.free(row) };
+            }
+        }
+
+        self.buried_end = 0;
+    }
+
+    /// Shrinks the storage of this table to fit its rows, returning the amount
+    /// of freed bytes.
+    ///
+    /// Drops [buried](Table::bury) components first. Holes aren't removed, so
+    /// the table should be compacted first.
+    pub fn shrink_to_fit(&mut self) -> usize {
+        self.drop_buried();
+        self.entities.shrink_to_fit();
+
+        let rows = self.rows_len();
+
+        self.columns
+            .iter_mut()
+            // SAFETY: there are no rows after the last row
+            .map(|column| unsafe { column.shrink_to(rows) })
+            .sum()
     }
 
     /// Clears all data in this table.
     pub fn clear(&mut self) {
+        self.drop_buried();
+
         for (i, entity) in self.entities.slots().enumerate() {
             if entity.is_none() {
                 continue;
@@ -207,6 +494,8 @@ impl Table {
                 _ = unsafe { column.free(row) };
             }
         }
+
+        self.entities.clear();
     }
 }
 
@@ -0,0 +1,263 @@
+use super::{IntoSystem, ReadOnlySystem, SkipReason, System};
+use crate::access::WorldAccess;
+use crate::component::Component;
+use crate::query::{Query, With};
+use crate::resource::{Res, Resource};
+use crate::world::{World, WorldPtr};
+
+/// Trait for read-only systems that return `bool`, used to decide whether
+/// something should run.
+///
+/// Conditions can be composed with [`Condition::and`], [`Condition::or`] and
+/// [`Condition::not`].
+pub trait Condition<I>: IntoSystem<I, bool, Output: ReadOnlySystem> {
+    /// Returns a condition that is `true` if both conditions are.
+    ///
+    /// `other` isn't run if this condition is `false`.
+    fn and<J, C: Condition<J>>(
+        self,
+        other: C,
+    ) -> AndCondition<Self::Output, C::Output> {
+        AndCondition::new(self.into_system(), other.into_system())
+    }
+
+    /// Returns a condition that is `true` if either condition is.
+    ///
+    /// `other` isn't run if this condition is `true`.
+    fn or<J, C: Condition<J>>(
+        self,
+        other: C,
+    ) -> OrCondition<Self::Output, C::Output> {
+        OrCondition::new(self.into_system(), other.into_system())
+    }
+
+    /// Returns a condition that is `true` if this condition is `false`.
+    fn not(self) -> NotCondition<Self::Output> {
+        NotCondition::new(self.into_system())
+    }
+}
+
+/// A [`Condition`] that is `true` if both conditions are.
+///
+/// Created by [`Condition::and`].
+pub struct AndCondition<A, B> {
+    lhs: A,
+    rhs: B,
+    access: Option<WorldAccess>,
+}
+
+/// A [`Condition`] that is `true` if either condition is.
+///
+/// Created by [`Condition::or`].
+pub struct OrCondition<A, B> {
+    lhs: A,
+    rhs: B,
+    access: Option<WorldAccess>,
+}
+
+/// A [`Condition`] that inverts another condition.
+///
+/// Created by [`Condition::not`].
+pub struct NotCondition<A> {
+    inner: A,
+}
+
+impl<I, C: IntoSystem<I, bool, Output: ReadOnlySystem>> Condition<I> for C {}
+
+/// A [`Condition`] that is `true` if the resource exists.
+pub fn resource_exists<R: Resource>(resource: Option<Res<R>>) -> bool {
+    resource.is_some()
+}
+
+/// A [`Condition`] that is `true` if any entity contains the component.
+pub fn any_with_component<C: Component>(query: Query<With<C>>) -> bool {
+    !query.is_empty()
+}
+
+macro_rules! binary_condition {
+    ($ty:ident, $op:tt) => {
+        impl<A, B> $ty<A, B> {
+            const fn new(lhs: A, rhs: B) -> Self {
+                let access = None;
+
+                Self { lhs, rhs, access }
+            }
+        }
+
+        /// # Safety
+        ///
+        /// The access is the union of the access of both conditions.
+        unsafe impl<A, B> System for $ty<A, B>
+        where
+            A: ReadOnlySystem<Output = bool>,
+            B: ReadOnlySystem<Output = bool>,
+        {
+            type Output = bool;
+
+            fn needs_init(&self) -> bool {
+                self.access.is_none()
+            }
+
+            fn init(&mut self, world: &World) {
+                self.lhs.init_if_needed(world);
+                self.rhs.init_if_needed(world);
+
+                let mut access = WorldAccess::new();
+
+                // SAFETY: both conditions were initialized above
+                unsafe {
+                    access.extend(self.lhs.world_access());
+                    access.extend(self.rhs.world_access());
+                }
+
+                self.access = Some(access);
+            }
+
+            unsafe fn world_access(&self) -> &WorldAccess {
+                // SAFETY: the caller ensures that the system is init
+                unsafe { self.access.as_ref().unwrap_unchecked() }
+            }
+
+            unsafe fn validate(&self, world: &World) -> Result<(), SkipReason> {
+                // SAFETY: the caller ensures that the system is init
+                unsafe {
+                    self.lhs.validate(world)?;
+                    self.rhs.validate(world)
+                }
+            }
+
+            unsafe fn run(&mut self, world: WorldPtr<'_>) -> Self::Output {
+                // SAFETY: the caller ensures that the access of both
+                // conditions is valid
+                unsafe { self.lhs.run(world) $op self.rhs.run(world) }
+            }
+
+            fn needs_sync(&self) -> bool {
+                self.lhs.needs_sync() || self.rhs.needs_sync()
+            }
+
+            unsafe fn sync(&mut self, world: &mut World) {
+                // SAFETY: the caller ensures that the system is init
+                unsafe {
+                    self.lhs.sync_if_needed(world);
+                    self.rhs.sync_if_needed(world);
+                }
+            }
+        }
+
+        /// # Safety
+        ///
+        /// Both conditions are read-only.
+        unsafe impl<A, B> ReadOnlySystem for $ty<A, B>
+        where
+            A: ReadOnlySystem<Output = bool>,
+            B: ReadOnlySystem<Output = bool>,
+        {
+        }
+    };
+}
+
+binary_condition!(AndCondition, &&);
+binary_condition!(OrCondition, ||);
+
+impl<A> NotCondition<A> {
+    const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+/// # Safety
+///
+/// The access is that of the inner condition.
+unsafe impl<A: ReadOnlySystem<Output = bool>> System for NotCondition<A> {
+    type Output = bool;
+
+    fn needs_init(&self) -> bool {
+        self.inner.needs_init()
+    }
+
+    fn init(&mut self, world: &World) {
+        self.inner.init(world);
+    }
+
+    unsafe fn world_access(&self) -> &WorldAccess {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.world_access() }
+    }
+
+    unsafe fn validate(&self, world: &World) -> Result<(), SkipReason> {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.validate(world) }
+    }
+
+    unsafe fn run(&mut self, world: WorldPtr<'_>) -> Self::Output {
+        // SAFETY: the caller ensures that the access is valid
+        !unsafe { self.inner.run(world) }
+    }
+
+    fn needs_sync(&self) -> bool {
+        self.inner.needs_sync()
+    }
+
+    unsafe fn sync(&mut self, world: &mut World) {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.sync(world) };
+    }
+}
+
+/// # Safety
+///
+/// The inner condition is read-only.
+unsafe impl<A: ReadOnlySystem<Output = bool>> ReadOnlySystem
+    for NotCondition<A>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Resource)]
+    struct Paused;
+
+    #[derive(Component)]
+    struct Player;
+
+    fn check<I>(condition: impl Condition<I>, world: &World) -> bool {
+        let mut system = condition.into_system();
+
+        system.init(world);
+        system.run_from_ref(world)
+    }
+
+    #[test]
+    fn built_in_conditions() {
+        let mut world = World::new();
+
+        assert!(!check(resource_exists::<Paused>, &world));
+        assert!(!check(any_with_component::<Player>, &world));
+
+        world.create(Paused);
+        world.spawn(Player);
+
+        assert!(check(resource_exists::<Paused>, &world));
+        assert!(check(any_with_component::<Player>, &world));
+    }
+
+    #[test]
+    fn combinators() {
+        let mut world = World::new();
+        let running = resource_exists::<Paused>.not();
+
+        world.spawn(Player);
+
+        assert!(check(running.and(any_with_component::<Player>), &world));
+
+        world.create(Paused);
+
+        let running = resource_exists::<Paused>.not();
+
+        assert!(!check(running.and(any_with_component::<Player>), &world));
+        assert!(check(resource_exists::<Paused>.not().or(|| true), &world));
+    }
+}
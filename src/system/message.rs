@@ -0,0 +1,186 @@
+//! Defines [`Messages`], a channel for sending values between systems.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+use super::SystemInput;
+use crate::access::{Level, WorldAccess};
+use crate::resource::{Res, ResMut, Resource, ResourceId};
+use crate::world::{World, WorldPtr};
+
+/// A channel resource for sending messages between systems.
+///
+/// Messages are delivered as soon as they're sent, so a [`MessageReader`]
+/// that runs after a [`MessageWriter`] receives its messages in the same tick.
+/// Messages are removed when read.
+///
+/// Must be created in the world with [`World::create`] before systems that
+/// use it run.
+pub struct Messages<M> {
+    sender: Sender<M>,
+    receiver: Mutex<Receiver<M>>,
+}
+
+/// A system input that sends [`Messages`].
+///
+/// Only requires shared access to the channel, so writers of the same message
+/// type don't conflict.
+pub struct MessageWriter<'w, M: Send + 'static> {
+    messages: Res<'w, Messages<M>>,
+}
+
+/// A system input that receives [`Messages`].
+pub struct MessageReader<'w, M: Send + 'static> {
+    messages: ResMut<'w, Messages<M>>,
+}
+
+impl<M: Send + 'static> Messages<M> {
+    /// Creates a new empty channel.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Mutex::new(receiver);
+
+        Self { sender, receiver }
+    }
+
+    /// Sends a message.
+    pub fn send(&self, message: M) {
+        // the receiver is owned by `self`, so sending can't fail
+        _ = self.sender.send(message);
+    }
+
+    /// Returns an iterator that receives all sent messages.
+    pub fn read(&mut self) -> impl Iterator<Item = M> + use<'_, M> {
+        self.receiver
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner())
+            .try_iter()
+    }
+
+    /// Removes all sent messages.
+    pub fn clear(&mut self) {
+        self.read().for_each(drop);
+    }
+}
+
+impl<M: Send + 'static> MessageWriter<'_, M> {
+    /// Sends a message.
+    pub fn send(&self, message: M) {
+        self.messages.send(message);
+    }
+}
+
+impl<M: Send + 'static> MessageReader<'_, M> {
+    /// Returns an iterator that receives all sent messages.
+    pub fn read(&mut self) -> impl Iterator<Item = M> + use<'_, M> {
+        self.messages.read()
+    }
+}
+
+// ---
+
+/// # Safety
+///
+/// The id is stored with [`ResourceId::of_type`].
+unsafe impl<M: Send + 'static> Resource for Messages<M> {
+    fn id() -> ResourceId {
+        ResourceId::of_type::<Self>()
+    }
+}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`].
+unsafe impl<M: Send + 'static> SystemInput for MessageWriter<'_, M> {
+    type Output<'w, 's> = MessageWriter<'w, M>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource::<Messages<M>>(Level::Read);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world contains this resource and
+        // that it is not already mutably borrowed
+        let messages = unsafe { world.as_ref().resource().unwrap_unchecked() };
+
+        MessageWriter { messages }
+    }
+}
+
+/// # Safety
+///
+/// [`SystemInput::get`] matches [`SystemInput::world_access`].
+unsafe impl<M: Send + 'static> SystemInput for MessageReader<'_, M> {
+    type Output<'w, 's> = MessageReader<'w, M>;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, access: &mut WorldAccess) {
+        access.borrows_resource::<Messages<M>>(Level::Write);
+    }
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        // SAFETY: the caller ensures that the world contains this resource and
+        // that it is not already borrowed
+        let messages =
+            unsafe { world.as_ref().resource_mut().unwrap_unchecked() };
+
+        MessageReader { messages }
+    }
+}
+
+impl<M: Send + 'static> Default for Messages<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{IntoSystem, System};
+
+    #[derive(Debug, PartialEq)]
+    struct Damage(u32);
+
+    #[test]
+    fn messages_are_delivered_in_the_same_tick() {
+        fn attack(writer: MessageWriter<Damage>) {
+            writer.send(Damage(1));
+            writer.send(Damage(2));
+        }
+
+        fn apply_damage(mut reader: MessageReader<Damage>) -> u32 {
+            reader.read().map(|Damage(damage)| damage).sum()
+        }
+
+        let mut world = World::new();
+
+        world.create(Messages::<Damage>::new());
+
+        let mut attack = attack.into_system();
+        let mut apply_damage = apply_damage.into_system();
+
+        attack.init(&world);
+        apply_damage.init(&world);
+
+        // SAFETY: the systems are initialized, their access is valid and the
+        // resource exists
+        unsafe {
+            attack.run(world.as_ptr());
+
+            assert_eq!(apply_damage.run(world.as_ptr()), 3);
+            assert_eq!(apply_damage.run(world.as_ptr()), 0);
+        }
+    }
+}
@@ -1,11 +1,27 @@
 //! Rich functions that perform operations on the world.
 
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use thiserror::Error;
+
+pub use self::condition::*;
 pub use self::function::*;
+pub use self::message::*;
+pub use self::pipe::*;
+pub use self::registry::*;
+pub use self::rng::*;
 pub use self::var::*;
 use crate::access::WorldAccess;
+use crate::marker::MaybeSendSync;
 use crate::world::{World, WorldPtr};
 
+mod condition;
 mod function;
+mod message;
+mod pipe;
+mod registry;
+mod rng;
 mod tuple_impl;
 mod var;
 
@@ -19,6 +35,13 @@ pub unsafe trait System {
     /// The output of this system.
     type Output;
 
+    /// Returns the name of this system, used for diagnostics.
+    ///
+    /// Defaults to the [type name](std::any::type_name) of the system.
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
     /// Returns `true` if this system needs to be initialized with
     /// [`System::init`].
     fn needs_init(&self) -> bool;
@@ -40,6 +63,17 @@ pub unsafe trait System {
     /// The system must be initialized.
     unsafe fn world_access(&self) -> &WorldAccess;
 
+    /// Returns an error if an input of this system is unavailable, in which
+    /// case the system is skipped instead of run.
+    ///
+    /// # Safety
+    ///
+    /// The system must be initialized.
+    #[expect(unused)]
+    unsafe fn validate(&self, world: &World) -> Result<(), SkipReason> {
+        Ok(())
+    }
+
     /// Runs this system.
     ///
     /// # Safety
@@ -75,6 +109,33 @@ pub unsafe trait System {
     }
 }
 
+/// The reason a system was skipped, as one of its inputs is unavailable.
+///
+/// Returned by [`SystemInput::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("input {input} is unavailable: {reason}")]
+pub struct SkipReason {
+    input: &'static str,
+    reason: &'static str,
+}
+
+impl SkipReason {
+    /// Creates a skip reason for an input.
+    pub fn new<I: SystemInput>(reason: &'static str) -> Self {
+        Self { input: type_name::<I>(), reason }
+    }
+
+    /// Returns the type name of the unavailable input.
+    pub const fn input(&self) -> &'static str {
+        self.input
+    }
+
+    /// Returns why the input is unavailable.
+    pub const fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+
 /// Trait for valid inputs to [`System`]s.
 ///
 /// # Safety
@@ -86,20 +147,41 @@ pub unsafe trait SystemInput {
     /// This system input borrowed for a lifetime.
     type Output<'w, 's>: SystemInput<State = Self::State>;
     /// The state of this input, retained between runs.
-    type State: Send + Sync + 'static;
+    type State: MaybeSendSync + 'static;
 
     /// Creates the state of this system input.
     fn init(world: &World) -> Self::State;
 
+    /// Creates the state of this system input for a system with a name.
+    ///
+    /// Defaults to [`SystemInput::init`]. Overridden by inputs whose state
+    /// depends on the system, like [`Rng`].
+    #[expect(unused)]
+    fn init_named(world: &World, system: &'static str) -> Self::State {
+        Self::init(world)
+    }
+
     /// Adds the access of this system input to the set.
     fn world_access(state: &Self::State, access: &mut WorldAccess);
 
+    /// Returns an error if this input can't be produced from the world, such
+    /// as a [`Single`](crate::query::Single) that doesn't match exactly one
+    /// entity.
+    ///
+    /// Systems with an unavailable input are skipped instead of run. Defaults
+    /// to `Ok`.
+    #[expect(unused)]
+    fn validate(state: &Self::State, world: &World) -> Result<(), SkipReason> {
+        Ok(())
+    }
+
     /// Produces this system input from the world and state.
     ///
     /// # Safety
     ///
     /// The access of the system input must be valid. THe world pointer must be
-    /// valid for the described access. All required items need to be present.
+    /// valid for the described access. All required items need to be present
+    /// and [`SystemInput::validate`] must have returned `Ok`.
     unsafe fn get<'w, 's>(
         state: &'s mut Self::State,
         world: WorldPtr<'w>,
@@ -117,12 +199,81 @@ pub unsafe trait SystemInput {
 }
 
 /// Trait for types that can be converted into a system.
+///
+/// # Generic systems
+///
+/// Generic functions are systems once their type parameters are named, so one
+/// function can be reused for several components:
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Camera;
+///
+/// fn count<C: Component>(query: Query<&C>) -> usize {
+///     query.len()
+/// }
+///
+/// let mut world = World::new();
+///
+/// world.spawn(Camera);
+///
+/// assert_eq!(world.run_system(count::<Camera>).unwrap(), 1);
+/// ```
+///
+/// Each instantiation is a distinct system, and its [name](System::name)
+/// includes its type parameters (`count<Camera>`).
 pub trait IntoSystem<I, O = ()>: Sized {
     /// The system this type can be converted into.
     type Output: System<Output = O>;
 
     /// Converts this into a system.
     fn into_system(self) -> Self::Output;
+
+    /// Returns a system that passes the output of this system to another as
+    /// [`In`].
+    ///
+    /// Deferred work of this system is only applied when the piped system is
+    /// [synced](System::sync).
+    fn pipe<J, P, Q>(self, other: Q) -> PipeSystem<Self::Output, Q::Output>
+    where
+        Q: IntoSystem<J, P, Output: InputSystem<O>>,
+    {
+        PipeSystem::new(self.into_system(), other.into_system())
+    }
+
+    /// Returns a system that maps the output of this system with a function.
+    fn map<U, F: FnMut(O) -> U>(
+        self,
+        function: F,
+    ) -> MapSystem<Self::Output, F> {
+        MapSystem::new(self.into_system(), function)
+    }
+}
+
+/// Trait for systems that return [`Result`].
+///
+/// Adds error-handling adapters to [`IntoSystem`].
+pub trait FallibleSystem<I, T, E>: IntoSystem<I, Result<T, E>> {
+    /// Returns a system that maps the error of this system with a function.
+    #[expect(clippy::type_complexity)]
+    fn map_err<F, U>(
+        self,
+        mut function: F,
+    ) -> MapSystem<Self::Output, impl FnMut(Result<T, E>) -> Result<T, U>>
+    where
+        F: FnMut(E) -> U,
+    {
+        self.map(move |result| result.map_err(&mut function))
+    }
+
+    /// Returns a system that discards the error of this system.
+    #[expect(clippy::type_complexity)]
+    fn ignore_err(
+        self,
+    ) -> MapSystem<Self::Output, fn(Result<T, E>) -> Option<T>> {
+        self.map(Result::ok)
+    }
 }
 
 /// Trait for systems that don't need mutable access.
@@ -169,15 +320,44 @@ impl<S: System> IntoSystem<ForSystem, S::Output> for S {
     }
 }
 
+impl<S, I, T, E> FallibleSystem<I, T, E> for S where
+    S: IntoSystem<I, Result<T, E>>
+{
+}
+
 impl<S, I, O> IntoReadOnlySystem<I, O> for S where
     S: IntoSystem<I, O, Output: ReadOnlySystem>
 {
 }
 
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: ?Sized> SystemInput for PhantomData<T> {
+    type Output<'w, 's> = Self;
+    type State = ();
+
+    fn init(_world: &World) -> Self::State {}
+
+    fn world_access(_state: &Self::State, _access: &mut WorldAccess) {}
+
+    unsafe fn get<'w, 's>(
+        _state: &'s mut Self::State,
+        _world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        PhantomData
+    }
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: ?Sized> ReadOnlySystemInput for PhantomData<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::prelude::WorldQueue;
+    use crate::prelude::{Component, WorldQueue};
     use crate::query::Query;
 
     fn _system_impls_into_system() {
@@ -187,6 +367,78 @@ mod tests {
         system.into_system().into_system().into_system();
     }
 
+    #[test]
+    fn function_system_name() {
+        fn update_players() {}
+
+        assert!(update_players
+            .into_system()
+            .name()
+            .ends_with("update_players"));
+    }
+
+    #[test]
+    fn generic_system_with_marker() {
+        trait Team: 'static {
+            const SIZE: usize;
+        }
+
+        #[derive(Component)]
+        struct Player;
+
+        struct Red;
+
+        impl Team for Red {
+            const SIZE: usize = 4;
+        }
+
+        fn open_slots<T: Team>(
+            _marker: PhantomData<T>,
+            query: Query<(&Player, PhantomData<T>)>,
+        ) -> usize {
+            T::SIZE - query.len()
+        }
+
+        let mut world = World::new();
+
+        world.spawn(Player);
+
+        let mut system = open_slots::<Red>.into_system();
+
+        system.init(&world);
+
+        // SAFETY: the system is initialized and only reads from the world
+        assert_eq!(unsafe { system.run(world.as_ptr()) }, 3);
+    }
+
+    #[test]
+    fn generic_system_over_component() {
+        #[derive(Component)]
+        struct Camera;
+
+        #[derive(Component)]
+        struct Light;
+
+        fn count<C: Component>(query: Query<&C>) -> usize {
+            query.len()
+        }
+
+        let mut world = World::new();
+
+        world.spawn(Camera);
+        world.spawn((Camera, Light));
+
+        assert_eq!(world.run_system(count::<Camera>).unwrap(), 2);
+        assert_eq!(world.run_system(count::<Light>).unwrap(), 1);
+
+        let camera = count::<Camera>.into_system();
+        let light = count::<Light>.into_system();
+
+        assert!(camera.name().contains("count<"));
+        assert!(camera.name().ends_with("Camera>"));
+        assert!(light.name().ends_with("Light>"));
+    }
+
     /// Ensures that the implementation of [`System`] for functions passes
     /// through [`SystemInput::needs_sync`] and calls [`SystemInput::sync`].
     #[test]
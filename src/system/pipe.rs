@@ -0,0 +1,387 @@
+use super::{
+    FunctionSystem,
+    ReadOnlySystem,
+    ReadOnlySystemInput,
+    SkipReason,
+    System,
+    SystemInput,
+};
+use crate::access::WorldAccess;
+use crate::world::{World, WorldPtr};
+
+/// A system input passed from the output of another system.
+///
+/// Must be the first parameter of a function system. See
+/// [`IntoSystem::pipe`](super::IntoSystem::pipe).
+pub struct In<T>(pub T);
+
+/// Trait for systems that take an input, produced by another system.
+///
+/// Implemented for function systems whose first parameter is [`In`].
+pub trait InputSystem<T>: System {
+    /// Runs this system with an input.
+    ///
+    /// # Safety
+    ///
+    /// See [`System::run`].
+    unsafe fn run_with(
+        &mut self,
+        input: T,
+        world: WorldPtr<'_>,
+    ) -> Self::Output;
+}
+
+/// A system that passes the output of a system to another.
+///
+/// Created by [`IntoSystem::pipe`](super::IntoSystem::pipe).
+pub struct PipeSystem<A, B> {
+    lhs: A,
+    rhs: B,
+    access: Option<WorldAccess>,
+}
+
+/// A system that maps the output of another system.
+///
+/// Created by [`IntoSystem::map`](super::IntoSystem::map).
+pub struct MapSystem<S, F> {
+    inner: S,
+    function: F,
+}
+
+impl<A, B> PipeSystem<A, B> {
+    pub(super) const fn new(lhs: A, rhs: B) -> Self {
+        let access = None;
+
+        Self { lhs, rhs, access }
+    }
+}
+
+impl<S, F> MapSystem<S, F> {
+    pub(super) const fn new(inner: S, function: F) -> Self {
+        Self { inner, function }
+    }
+}
+
+// ---
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: Send + Sync + 'static> SystemInput for In<T> {
+    type Output<'w, 's> = Self;
+    type State = Option<T>;
+
+    fn init(_world: &World) -> Self::State {
+        None
+    }
+
+    fn world_access(_state: &Self::State, _access: &mut WorldAccess) {}
+
+    unsafe fn get<'w, 's>(
+        state: &'s mut Self::State,
+        _world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        In(state.take().expect("system was run without its input"))
+    }
+}
+
+/// # Safety
+///
+/// Nothing is accessed.
+unsafe impl<T: Send + Sync + 'static> ReadOnlySystemInput for In<T> {}
+
+macro_rules! input_system_impl {
+    ($($i:ident),*) => {
+        input_system_impl!([] [$($i)*]);
+    };
+
+    ([$($i:ident)*] []) => {
+        impl<F, T, $($i,)* O> InputSystem<T>
+            for FunctionSystem<(In<T>, $($i,)*), O, F>
+        where
+            T: Send + Sync + 'static,
+            $($i: SystemInput,)*
+            Self: System<Output = O>,
+        {
+            unsafe fn run_with(
+                &mut self,
+                input: T,
+                world: WorldPtr<'_>,
+            ) -> Self::Output {
+                // SAFETY: the caller ensures that the system is init
+                let state = unsafe { self.state.as_mut().unwrap_unchecked() };
+
+                state.0 = Some(input);
+
+                // SAFETY: the caller upholds the contract of `System::run`
+                unsafe { self.run(world) }
+            }
+        }
+    };
+
+    ([$($rest:ident)*] [$head:ident $($tail:ident)*]) => {
+        input_system_impl!([$($rest)*] []);
+        input_system_impl!([$($rest)* $head] [$($tail)*]);
+    };
+}
+
+input_system_impl!(
+    I0, I1, I2, I3, I4, I5, I6, I7, I8, I9, I10, I11, I12, I13, I14
+);
+
+/// # Safety
+///
+/// The access is the [merged](WorldAccess::merge) access of both systems,
+/// which are run one after the other.
+unsafe impl<A, B> System for PipeSystem<A, B>
+where
+    A: System,
+    B: InputSystem<A::Output>,
+{
+    type Output = B::Output;
+
+    fn needs_init(&self) -> bool {
+        self.access.is_none()
+    }
+
+    fn init(&mut self, world: &World) {
+        self.lhs.init_if_needed(world);
+        self.rhs.init_if_needed(world);
+
+        let mut access = WorldAccess::new();
+
+        // SAFETY: both systems were initialized above
+        unsafe {
+            access.merge(self.lhs.world_access());
+            access.merge(self.rhs.world_access());
+        }
+
+        self.access = Some(access);
+    }
+
+    unsafe fn world_access(&self) -> &WorldAccess {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.access.as_ref().unwrap_unchecked() }
+    }
+
+    unsafe fn validate(&self, world: &World) -> Result<(), SkipReason> {
+        // SAFETY: the caller ensures that the system is init
+        unsafe {
+            self.lhs.validate(world)?;
+            self.rhs.validate(world)
+        }
+    }
+
+    unsafe fn run(&mut self, world: WorldPtr<'_>) -> Self::Output {
+        // SAFETY: the caller ensures that the access of both systems is valid.
+        // the borrows of `lhs` end before `rhs` is run
+        unsafe {
+            let input = self.lhs.run(world);
+
+            self.rhs.run_with(input, world)
+        }
+    }
+
+    fn needs_sync(&self) -> bool {
+        self.lhs.needs_sync() || self.rhs.needs_sync()
+    }
+
+    unsafe fn sync(&mut self, world: &mut World) {
+        // SAFETY: the caller ensures that the system is init
+        unsafe {
+            self.lhs.sync_if_needed(world);
+            self.rhs.sync_if_needed(world);
+        }
+    }
+}
+
+/// # Safety
+///
+/// Both systems are read-only.
+unsafe impl<A, B> ReadOnlySystem for PipeSystem<A, B>
+where
+    A: ReadOnlySystem,
+    B: ReadOnlySystem + InputSystem<A::Output>,
+{
+}
+
+impl<A, B, T> InputSystem<T> for PipeSystem<A, B>
+where
+    A: InputSystem<T>,
+    B: InputSystem<A::Output>,
+{
+    unsafe fn run_with(
+        &mut self,
+        input: T,
+        world: WorldPtr<'_>,
+    ) -> Self::Output {
+        // SAFETY: see `PipeSystem::run`
+        unsafe {
+            let input = self.lhs.run_with(input, world);
+
+            self.rhs.run_with(input, world)
+        }
+    }
+}
+
+/// # Safety
+///
+/// The access is that of the inner system.
+unsafe impl<S, F, U> System for MapSystem<S, F>
+where
+    S: System,
+    F: FnMut(S::Output) -> U,
+{
+    type Output = U;
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn needs_init(&self) -> bool {
+        self.inner.needs_init()
+    }
+
+    fn init(&mut self, world: &World) {
+        self.inner.init(world);
+    }
+
+    unsafe fn world_access(&self) -> &WorldAccess {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.world_access() }
+    }
+
+    unsafe fn validate(&self, world: &World) -> Result<(), SkipReason> {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.validate(world) }
+    }
+
+    unsafe fn run(&mut self, world: WorldPtr<'_>) -> Self::Output {
+        // SAFETY: the caller ensures that the access is valid
+        (self.function)(unsafe { self.inner.run(world) })
+    }
+
+    fn needs_sync(&self) -> bool {
+        self.inner.needs_sync()
+    }
+
+    unsafe fn sync(&mut self, world: &mut World) {
+        // SAFETY: the caller ensures that the system is init
+        unsafe { self.inner.sync(world) };
+    }
+}
+
+/// # Safety
+///
+/// The inner system is read-only.
+unsafe impl<S, F, U> ReadOnlySystem for MapSystem<S, F>
+where
+    S: ReadOnlySystem,
+    F: FnMut(S::Output) -> U,
+{
+}
+
+impl<S, F, T, U> InputSystem<T> for MapSystem<S, F>
+where
+    S: InputSystem<T>,
+    F: FnMut(S::Output) -> U,
+{
+    unsafe fn run_with(
+        &mut self,
+        input: T,
+        world: WorldPtr<'_>,
+    ) -> Self::Output {
+        // SAFETY: the caller ensures that the access is valid
+        (self.function)(unsafe { self.inner.run_with(input, world) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::ParseIntError;
+
+    use crate::prelude::*;
+
+    #[derive(Resource)]
+    struct Text(&'static str);
+
+    #[derive(Component)]
+    struct Score(u32);
+
+    fn parse(text: Res<Text>) -> Result<u32, ParseIntError> {
+        text.0.parse()
+    }
+
+    fn award(In(points): In<u32>, mut query: Query<&mut Score>) -> u32 {
+        for score in &mut query {
+            score.0 += points;
+        }
+
+        points
+    }
+
+    #[test]
+    fn pipe_outputs_into_inputs() {
+        let mut world = World::new();
+
+        world.create(Text("3"));
+        world.spawn(Score(0));
+        world.spawn(Score(1));
+
+        let mut system = parse.map(Result::unwrap).pipe(award);
+
+        system.init(&world);
+
+        // SAFETY: the system is initialized and the world pointer is valid for
+        // mutable access
+        assert_eq!(unsafe { system.run(world.as_ptr_mut()) }, 3);
+
+        let mut scores: Vec<_> =
+            world.query::<&Score>().unwrap().iter().map(|s| s.0).collect();
+
+        scores.sort();
+
+        assert_eq!(scores, [3, 4]);
+    }
+
+    #[test]
+    fn error_adapters() {
+        let mut world = World::new();
+
+        world.create(Text("three"));
+
+        let mut system = parse.map_err(|error| error.to_string());
+
+        system.init(&world);
+        assert!(system.run_from_ref(&world).is_err());
+
+        let mut system = parse.ignore_err();
+
+        system.init(&world);
+        assert_eq!(system.run_from_ref(&world), None);
+    }
+
+    #[test]
+    fn pipe_merges_access() {
+        fn count(query: Query<&Score>) -> usize {
+            query.len()
+        }
+
+        fn reset(In(_): In<usize>, mut query: Query<&mut Score>) {
+            for score in &mut query {
+                score.0 = 0;
+            }
+        }
+
+        let world = World::new();
+        let mut system = count.pipe(reset);
+
+        system.init(&world);
+
+        // SAFETY: the system is initialized
+        let access = unsafe { system.world_access() };
+
+        assert!(access.result().is_ok());
+        assert_eq!(access.level(), Some(Level::Write));
+    }
+}
@@ -0,0 +1,163 @@
+use std::any::{type_name, Any};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use thiserror::Error;
+
+use super::{SkipReason, System};
+use crate::access::AccessError;
+use crate::marker::MaybeSendSync;
+
+/// Storage for systems registered to a world.
+///
+/// Registered systems keep their state between runs. See
+/// [`World::register_system`](crate::world::World::register_system).
+#[derive(Default)]
+pub struct SystemRegistry {
+    /// Each system is a [`BoxedSystem<O>`], or `None` while it is running or
+    /// after it was unregistered.
+    systems: Vec<Option<Box<AnySystem>>>,
+}
+
+/// A type-erased [`BoxedSystem`].
+#[cfg(not(feature = "single_threaded"))]
+type AnySystem = dyn Any + Send + Sync;
+
+/// A type-erased [`BoxedSystem`].
+#[cfg(feature = "single_threaded")]
+type AnySystem = dyn Any;
+
+/// A registered system.
+#[cfg(not(feature = "single_threaded"))]
+pub(crate) type BoxedSystem<O> = Box<dyn System<Output = O> + Send + Sync>;
+
+/// A registered system.
+#[cfg(feature = "single_threaded")]
+pub(crate) type BoxedSystem<O> = Box<dyn System<Output = O>>;
+
+/// The id of a system in a [`SystemRegistry`], returning `O`.
+pub struct SystemId<O = ()> {
+    index: u32,
+    _marker: PhantomData<fn() -> O>,
+}
+
+/// An error when running a system on a world.
+#[derive(Debug, Clone, Copy, Error)]
+pub enum RunSystemError {
+    #[error("system not found")]
+    NotFound,
+    #[error("resource {resource} not found for system {system}")]
+    ResourceNotFound {
+        /// The type name of the resource.
+        resource: &'static str,
+        /// The [name](System::name) of the system that requires it.
+        system: &'static str,
+    },
+    #[error("system {system} was skipped: {reason}")]
+    Skipped {
+        /// The [name](System::name) of the skipped system.
+        system: &'static str,
+        /// Why the system was skipped.
+        reason: SkipReason,
+    },
+    #[error(transparent)]
+    Access(#[from] AccessError),
+}
+
+impl SystemRegistry {
+    /// Creates a new empty system registry.
+    pub const fn new() -> Self {
+        let systems = Vec::new();
+
+        Self { systems }
+    }
+
+    /// Adds a system to the registry.
+    pub fn register<O: 'static>(
+        &mut self,
+        system: impl System<Output = O> + MaybeSendSync + 'static,
+    ) -> SystemId<O> {
+        let index = u32::try_from(self.systems.len()).expect("system overflow");
+        let system: BoxedSystem<O> = Box::new(system);
+
+        self.systems.push(Some(Box::new(system)));
+
+        SystemId::new(index)
+    }
+
+    /// Removes a system from the registry.
+    ///
+    /// Returns `false` if the system doesn't exist or is running.
+    pub fn unregister<O>(&mut self, id: SystemId<O>) -> bool {
+        self.systems.get_mut(id.index as usize).and_then(Option::take).is_some()
+    }
+
+    /// Takes a system out of the registry to run it.
+    pub(crate) fn take<O: 'static>(
+        &mut self,
+        id: SystemId<O>,
+    ) -> Option<BoxedSystem<O>> {
+        let slot = self.systems.get_mut(id.index as usize)?;
+
+        // ids from another world may have a different output type
+        if !slot.as_ref()?.is::<BoxedSystem<O>>() {
+            return None;
+        }
+
+        slot.take()?.downcast().ok().map(|system| *system)
+    }
+
+    /// Returns a system taken with [`SystemRegistry::take`].
+    pub(crate) fn restore<O: 'static>(
+        &mut self,
+        id: SystemId<O>,
+        system: BoxedSystem<O>,
+    ) {
+        self.systems[id.index as usize] = Some(Box::new(system));
+    }
+}
+
+impl<O> SystemId<O> {
+    const fn new(index: u32) -> Self {
+        Self { index, _marker: PhantomData }
+    }
+}
+
+// ---
+
+impl fmt::Debug for SystemRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemRegistry")
+            .field("len", &self.systems.iter().flatten().count())
+            .finish()
+    }
+}
+
+impl<O> Clone for SystemId<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O> Copy for SystemId<O> {}
+
+impl<O> PartialEq for SystemId<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<O> Eq for SystemId<O> {}
+
+impl<O> Hash for SystemId<O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<O> fmt::Debug for SystemId<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SystemId<{}>({})", type_name::<O>(), self.index)
+    }
+}
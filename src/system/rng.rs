@@ -0,0 +1,261 @@
+//! Defines [`DeterministicRng`] and [`Rng`], seeded random numbers that are
+//! identical across runs.
+
+use std::ops::{Deref, DerefMut, Range};
+
+use super::SystemInput;
+use crate::access::WorldAccess;
+use crate::resource::Resource;
+use crate::world::{World, WorldPtr};
+
+/// A resource holding the seed of all random numbers in the world.
+///
+/// Each [`Rng`] system input gets its own stream, forked from the seed and
+/// the name of its system, so a system produces the same numbers regardless
+/// of which other systems exist or the order they run in. The resource itself
+/// is also a stream, for use outside of systems.
+///
+/// Must be created in the world with [`World::create`] before systems that
+/// use [`Rng`] are initialized.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct DeterministicRng {
+    seed: u64,
+    stream: RngStream,
+}
+
+/// A system input for a stream of random numbers forked from the
+/// [`DeterministicRng`] of the world.
+///
+/// The stream is created when the system is initialized and is retained
+/// between runs. Multiple `Rng` inputs of the same system share a stream.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// fn roll(mut rng: Rng) -> u64 {
+///     rng.range(1..7)
+/// }
+///
+/// let mut world = World::new();
+///
+/// world.create(DeterministicRng::new(42));
+///
+/// let first = world.run_system(roll).unwrap();
+///
+/// world.create(DeterministicRng::new(42));
+///
+/// assert_eq!(world.run_system(roll).unwrap(), first);
+/// ```
+pub struct Rng<'s> {
+    stream: &'s mut RngStream,
+}
+
+/// A stream of pseudo-random numbers.
+///
+/// Uses SplitMix64, which only relies on integer arithmetic, so the output is
+/// the same on every platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl DeterministicRng {
+    /// Creates a new generator with a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { seed, stream: RngStream::new(seed) }
+    }
+
+    /// Returns the seed of this generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a new stream for a key, like the name of a system.
+    ///
+    /// The stream only depends on the seed and the key.
+    pub fn fork(&self, key: &str) -> RngStream {
+        RngStream::new(self.seed ^ RngStream::new(fnv1a(key)).next_u64())
+    }
+}
+
+impl RngStream {
+    /// Creates a new stream from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a random number in a range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot sample an empty range");
+
+        let len = range.end - range.start;
+        // rejects the values that would make lower numbers more likely
+        let zone = u64::MAX - u64::MAX % len;
+
+        loop {
+            let value = self.next_u64();
+
+            if value < zone {
+                return range.start + value % len;
+            }
+        }
+    }
+
+    /// Returns a random fraction in `0..1`.
+    ///
+    /// The fraction is a multiple of 2<sup>-53</sup>, so it is exact and the
+    /// same on every platform.
+    pub fn fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns `true` with a probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.fraction() < probability
+    }
+}
+
+/// A stable hash of a string, unlike
+/// [`DefaultHasher`](std::hash::DefaultHasher) which may change between Rust
+/// versions.
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// # Safety
+///
+/// `Rng` declares no access and doesn't access the world.
+unsafe impl SystemInput for Rng<'_> {
+    type Output<'w, 's> = Rng<'s>;
+    type State = RngStream;
+
+    fn init(world: &World) -> Self::State {
+        Self::init_named(world, "")
+    }
+
+    fn init_named(world: &World, system: &'static str) -> Self::State {
+        world
+            .resource::<DeterministicRng>()
+            .expect("`Rng` requires the `DeterministicRng` resource")
+            .fork(system)
+    }
+
+    fn world_access(_state: &Self::State, _access: &mut WorldAccess) {}
+
+    unsafe fn get<'w, 's>(
+        state: &'s mut Self::State,
+        _world: WorldPtr<'w>,
+    ) -> Self::Output<'w, 's> {
+        Rng { stream: state }
+    }
+}
+
+impl Deref for DeterministicRng {
+    type Target = RngStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl DerefMut for DeterministicRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+impl Deref for Rng<'_> {
+    type Target = RngStream;
+
+    fn deref(&self) -> &Self::Target {
+        self.stream
+    }
+}
+
+impl DerefMut for Rng<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{IntoSystem, System};
+
+    fn draw(mut rng: Rng) -> Vec<u64> {
+        (0..4).map(|_| rng.next_u64()).collect()
+    }
+
+    fn draw_other(mut rng: Rng) -> Vec<u64> {
+        (0..4).map(|_| rng.next_u64()).collect()
+    }
+
+    fn run(
+        world: &World,
+        system: &mut impl System<Output = Vec<u64>>,
+    ) -> Vec<u64> {
+        system.init_if_needed(world);
+
+        // SAFETY: the system is initialized and `Rng` doesn't access the world
+        unsafe { system.run(world.as_ptr()) }
+    }
+
+    #[test]
+    fn streams_are_deterministic() {
+        let mut world = World::new();
+
+        world.create(DeterministicRng::new(7));
+
+        let mut a = draw.into_system();
+        let mut b = draw_other.into_system();
+        let first = run(&world, &mut a);
+
+        // the stream continues between runs
+        assert_ne!(run(&world, &mut a), first);
+        // each system has its own stream
+        assert_ne!(run(&world, &mut b), first);
+        // the same seed produces the same stream
+        assert_eq!(run(&world, &mut draw.into_system()), first);
+
+        world.create(DeterministicRng::new(8));
+
+        assert_ne!(run(&world, &mut draw.into_system()), first);
+    }
+
+    #[test]
+    fn range_and_fraction() {
+        let mut rng = DeterministicRng::new(0);
+
+        for _ in 0..1000 {
+            assert!((10..20).contains(&rng.range(10..20)));
+            assert!((0.0..1.0).contains(&rng.fraction()));
+        }
+
+        assert_eq!(rng.range(3..4), 3);
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
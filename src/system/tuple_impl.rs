@@ -13,12 +13,19 @@ macro_rules! tuple_impl {
         {
             type Output = O;
 
+            fn name(&self) -> &'static str {
+                ::std::any::type_name::<F>()
+            }
+
             fn needs_init(&self) -> bool {
                 self.state.is_none()
             }
 
             fn init(&mut self, world: &$crate::world::World) {
-                let state = <($($i,)*) as $crate::system::SystemInput>::init(world);
+                let state = <($($i,)*) as $crate::system::SystemInput>::init_named(
+                    world,
+                    $crate::system::System::name(self),
+                );
                 let mut access = $crate::access::WorldAccess::new();
 
                 <($($i,)*) as $crate::system::SystemInput>::world_access(
@@ -35,6 +42,16 @@ macro_rules! tuple_impl {
                 unsafe { self.access.as_ref().unwrap_unchecked() }
             }
 
+            unsafe fn validate(
+                &self,
+                world: &$crate::world::World,
+            ) -> ::std::result::Result<(), $crate::system::SkipReason> {
+                // SAFETY: the caller ensures that [`System::init`] has been called
+                let state = unsafe { self.state.as_ref().unwrap_unchecked() };
+
+                <($($i,)*) as $crate::system::SystemInput>::validate(state, world)
+            }
+
             #[allow(unused_variables)]
             unsafe fn run(&mut self, world: $crate::world::WorldPtr<'_>) -> Self::Output {
                 // SAFETY: the caller ensures that [`System::init`] has been called
@@ -91,6 +108,14 @@ macro_rules! tuple_impl {
                 ($($i::init(world),)*)
             }
 
+            #[allow(unused_variables, clippy::unused_unit)]
+            fn init_named(
+                world: &$crate::world::World,
+                system: &'static str,
+            ) -> Self::State {
+                ($($i::init_named(world, system),)*)
+            }
+
             fn world_access(
                 state: &Self::State,
                 #[allow(unused)]
@@ -102,6 +127,19 @@ macro_rules! tuple_impl {
                 $($i::world_access($i, access));*
             }
 
+            #[allow(unused_variables)]
+            fn validate(
+                state: &Self::State,
+                world: &$crate::world::World,
+            ) -> ::std::result::Result<(), $crate::system::SkipReason> {
+                #[allow(non_snake_case)]
+                let ($($i,)*) = state;
+
+                $($i::validate($i, world)?;)*
+
+                Ok(())
+            }
+
             #[allow(unused_variables, clippy::unused_unit)]
             unsafe fn get<'w, 's>(
                 state: &'s mut Self::State,
@@ -0,0 +1,195 @@
+use std::panic::Location;
+
+use super::World;
+use crate::component::{ComponentVTable, HookContext};
+use crate::entity::{EntityAddr, EntityHashMap, EntityId, EntityMut};
+
+/// How [`World::append`] handles resources contained by both worlds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceConflict {
+    /// Keep the resource of the world that is appended to.
+    Keep,
+    /// Replace the resource with the one from the appended world.
+    Replace,
+}
+
+impl World {
+    /// Moves all entities and resources out of another world into this one.
+    ///
+    /// Pending commands of the other world are applied first. Entities are
+    /// given new ids, and ids stored in components with
+    /// `#[component(map_entities)]` are remapped. Entities are moved a table
+    /// at a time into tables with the same components, and the
+    /// [`Component::after_insert`] hooks of their components are run.
+    ///
+    /// Returns the map from the ids in the other world to the new ids.
+    ///
+    /// [`Component::after_insert`]: crate::component::Component::after_insert
+    #[track_caller]
+    pub fn append(
+        &mut self,
+        other: &mut World,
+        resources: ResourceConflict,
+    ) -> EntityHashMap<EntityId> {
+        let caller = Location::caller();
+
+        other.flush();
+
+        let mut map = EntityHashMap::default();
+        let mut moved = Vec::with_capacity(other.len());
+        let tables: Vec<_> = other
+            .components
+            .tables()
+            .filter(|(_, table)| table.len() > 0)
+            .map(|(index, _)| index)
+            .collect();
+
+        for old_index in tables {
+            // SAFETY: the table index was just retrieved from storage
+            let old_table =
+                unsafe { other.components.get_unchecked_mut(old_index) };
+            let components = old_table.components().clone();
+            let rows: Vec<_> = old_table.rows().collect();
+            let index =
+                self.components.alloc_set(rows.len(), components.clone()).table;
+            // SAFETY: the table was allocated above
+            let table = unsafe { self.components.get_unchecked_mut(index) };
+
+            for (old_row, old_entity) in rows {
+                let entity = self.entities.alloc();
+                // SAFETY: the entity was only allocated above
+                let row = unsafe { table.push_new(entity) };
+
+                for component in &components {
+                    // SAFETY: the component is initialized and is removed from
+                    // the other table without being dropped below
+                    unsafe {
+                        let ptr = old_table
+                            .get_unchecked_mut(old_row, component.id());
+
+                        table.init_component(row, component.id(), ptr);
+                    }
+                }
+
+                old_table.remove(old_row);
+                self.entities.set(entity, EntityAddr { table: index, row });
+                map.insert(old_entity, entity);
+                moved.push((old_entity, old_index, entity, index));
+            }
+        }
+
+        other.entities.clear();
+        other.names.clear();
+        other.relations.clear();
+
+        for &(old_entity, old_index, entity, index) in &moved {
+            // SAFETY: the entity was moved from and into the tables above
+            unsafe {
+                other.listeners.archetype_changed(
+                    &other.components,
+                    old_entity,
+                    Some(old_index),
+                    None,
+                );
+                EntityMut::new_unchecked(entity, self).map_entities(&mut map);
+                self.listeners.archetype_changed(
+                    &self.components,
+                    entity,
+                    None,
+                    Some(index),
+                );
+            }
+        }
+
+        for &(_, _, entity, index) in &moved {
+            // SAFETY: the table was allocated above
+            let components = unsafe { self.components.get_unchecked(index) }
+                .components()
+                .clone();
+
+            for component in &components {
+                let hook = component.after_insert();
+                let ctx =
+                    HookContext::new(entity, component.id(), false, caller);
+
+                // SAFETY: the entity was moved into the table above
+                unsafe {
+                    hook(EntityMut::new_unchecked(entity, self), ctx);
+                    self.notify_insert(entity, component.id());
+                }
+            }
+        }
+
+        self.resources.append(
+            &mut other.resources,
+            resources == ResourceConflict::Replace,
+        );
+        self.flush();
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    #[component(map_entities)]
+    struct Target(EntityId);
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, mapper: &mut dyn EntityMapper) {
+            self.0.map_entities(mapper);
+        }
+    }
+
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Resource)]
+    struct Level(&'static str);
+
+    #[derive(Resource)]
+    struct Gravity(f32);
+
+    #[test]
+    fn append_world() {
+        let mut world = World::new();
+        let mut level = World::new();
+
+        world.spawn(Health(1));
+        world.create(Level("hub"));
+
+        let boss = level.spawn((Health(100), Name::new("boss"))).id();
+        let minion = level.spawn(Target(boss)).id();
+
+        level.create(Level("dungeon"));
+        level.create(Gravity(9.8));
+
+        let map = world.append(&mut level, ResourceConflict::Keep);
+
+        assert!(level.is_empty());
+        assert!(!level.has::<Gravity>());
+        assert_eq!(world.len(), 3);
+
+        let boss = map[&boss];
+        let minion = map[&minion];
+
+        assert_eq!(world.entity(boss).unwrap().get::<Health>().unwrap().0, 100);
+        assert_eq!(
+            world.entity(minion).unwrap().get::<Target>().unwrap().0,
+            boss
+        );
+        assert_eq!(world.find_by_name("boss"), Some(boss));
+        assert_eq!(world.resource::<Level>().unwrap().0, "hub");
+        assert_eq!(world.resource::<Gravity>().unwrap().0, 9.8);
+
+        let mut other = World::new();
+
+        other.create(Level("dungeon"));
+        world.append(&mut other, ResourceConflict::Replace);
+
+        assert_eq!(world.resource::<Level>().unwrap().0, "dungeon");
+    }
+}
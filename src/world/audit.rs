@@ -0,0 +1,302 @@
+use std::collections::vec_deque::{self, VecDeque};
+
+use super::World;
+use crate::component::{ComponentId, ComponentVTable};
+use crate::entity::EntityId;
+use crate::resource::ResourceId;
+use crate::storage::Table;
+
+/// A ring buffer of the structural operations performed on a world.
+///
+/// Enabled with [`World::enable_audit`] and retrieved with
+/// [`World::audit_log`]. Once full, the oldest entries are overwritten.
+///
+/// Requires the `audit` feature.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+    capacity: usize,
+    /// The system that is currently running.
+    system: Option<&'static str>,
+}
+
+/// An operation recorded in an [`AuditLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditEntry {
+    op: AuditOp,
+    system: Option<&'static str>,
+}
+
+/// A structural operation on a world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditOp {
+    /// An entity was spawned.
+    Spawn(EntityId),
+    /// An entity was despawned.
+    Despawn(EntityId),
+    /// A component was added to an entity.
+    ///
+    /// Not recorded when an existing component is replaced, as the entity
+    /// keeps its archetype.
+    Insert { entity: EntityId, component: ComponentId },
+    /// A component was removed from an entity.
+    Remove { entity: EntityId, component: ComponentId },
+    /// A resource was created or replaced.
+    CreateResource(ResourceId),
+    /// A resource was destroyed.
+    DestroyResource(ResourceId),
+}
+
+impl World {
+    /// Starts recording structural operations into an [`AuditLog`] that holds
+    /// at most `capacity` entries.
+    ///
+    /// Replaces the existing log.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// let mut world = World::new();
+    ///
+    /// world.enable_audit(64);
+    ///
+    /// let entity = world.spawn(()).id();
+    ///
+    /// world.despawn(entity).unwrap();
+    ///
+    /// let log = world.audit_log().unwrap();
+    ///
+    /// assert_eq!(
+    ///     log.iter().map(AuditEntry::op).collect::<Vec<_>>(),
+    ///     [AuditOp::Spawn(entity), AuditOp::Despawn(entity)],
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is `0`.
+    pub fn enable_audit(&mut self, capacity: usize) {
+        assert!(capacity > 0, "audit log capacity must be non-zero");
+
+        self.listeners.audit = Some(AuditLog {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            system: None,
+        });
+    }
+
+    /// Stops recording structural operations, returning the log.
+    pub fn disable_audit(&mut self) -> Option<AuditLog> {
+        self.listeners.audit.take()
+    }
+
+    /// Returns the [`AuditLog`] of this world, if enabled.
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.listeners.audit.as_ref()
+    }
+
+    /// Records an operation if the audit log is enabled.
+    pub(crate) fn audit(&mut self, op: AuditOp) {
+        if let Some(log) = &mut self.listeners.audit {
+            log.push(op);
+        }
+    }
+
+    /// Sets the system that operations are attributed to, returning the
+    /// previous one.
+    pub(crate) fn audit_system(
+        &mut self,
+        system: Option<&'static str>,
+    ) -> Option<&'static str> {
+        self.listeners
+            .audit
+            .as_mut()
+            .and_then(|log| std::mem::replace(&mut log.system, system))
+    }
+}
+
+impl AuditLog {
+    /// Returns the maximum amount of entries in this log.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the amount of entries in this log.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if this log has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the entries of this log, oldest first.
+    pub fn iter(&self) -> vec_deque::Iter<'_, AuditEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the entries of this log that affected an
+    /// entity, oldest first.
+    pub fn entity(
+        &self,
+        entity: EntityId,
+    ) -> impl Iterator<Item = &AuditEntry> + '_ {
+        self.iter().filter(move |entry| entry.op.entity() == Some(entity))
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn push(&mut self, op: AuditOp) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(AuditEntry { op, system: self.system });
+    }
+
+    /// Records an entity moving from one table to another.
+    ///
+    /// `None` means the entity was spawned or despawned.
+    pub(crate) fn record_move(
+        &mut self,
+        entity: EntityId,
+        old: Option<&Table>,
+        new: Option<&Table>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.push(AuditOp::Spawn(entity)),
+            (Some(_), None) => self.push(AuditOp::Despawn(entity)),
+            (Some(old), Some(new)) => {
+                for component in new.components().iter() {
+                    let component = component.id();
+
+                    if !old.components().contains(component) {
+                        self.push(AuditOp::Insert { entity, component });
+                    }
+                }
+
+                for component in old.components().iter() {
+                    let component = component.id();
+
+                    if !new.components().contains(component) {
+                        self.push(AuditOp::Remove { entity, component });
+                    }
+                }
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+impl AuditEntry {
+    /// Returns the recorded operation.
+    pub const fn op(&self) -> AuditOp {
+        self.op
+    }
+
+    /// Returns the name of the system that performed the operation, if it was
+    /// performed by one.
+    ///
+    /// Includes the deferred work of the system.
+    pub const fn system(&self) -> Option<&'static str> {
+        self.system
+    }
+}
+
+impl AuditOp {
+    /// Returns the entity affected by this operation.
+    pub const fn entity(&self) -> Option<EntityId> {
+        match *self {
+            Self::Spawn(entity)
+            | Self::Despawn(entity)
+            | Self::Insert { entity, .. }
+            | Self::Remove { entity, .. } => Some(entity),
+            Self::CreateResource(_) | Self::DestroyResource(_) => None,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a AuditLog {
+    type IntoIter = vec_deque::Iter<'a, AuditEntry>;
+    type Item = &'a AuditEntry;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Health;
+
+    #[derive(Resource)]
+    struct Score;
+
+    #[test]
+    fn audit_records_structural_operations() {
+        fn spawn(mut queue: WorldQueue) {
+            queue.spawn(());
+        }
+
+        let mut world = World::new();
+
+        world.enable_audit(16);
+
+        let entity = world.spawn(()).id();
+
+        world.entity_mut(entity).unwrap().insert(Health);
+        // replacing doesn't change the archetype
+        world.entity_mut(entity).unwrap().insert(Health);
+        world.entity_mut(entity).unwrap().remove::<Health>().unwrap();
+        world.create(Score);
+        world.destroy::<Score>().unwrap();
+        world.despawn(entity).unwrap();
+        world.run_system(spawn).unwrap();
+
+        let log = world.audit_log().unwrap();
+        let ops: Vec<_> = log.iter().map(AuditEntry::op).collect();
+
+        assert_eq!(
+            ops[..6],
+            [
+                AuditOp::Spawn(entity),
+                AuditOp::Insert { entity, component: Health::id() },
+                AuditOp::Remove { entity, component: Health::id() },
+                AuditOp::CreateResource(ResourceId::of::<Score>()),
+                AuditOp::DestroyResource(ResourceId::of::<Score>()),
+                AuditOp::Despawn(entity),
+            ],
+        );
+        assert_eq!(log.entity(entity).count(), 4);
+        assert!(log.iter().take(6).all(|entry| entry.system().is_none()));
+
+        let spawned = log.iter().last().unwrap();
+
+        assert!(matches!(spawned.op(), AuditOp::Spawn(_)));
+        assert!(spawned.system().unwrap().ends_with("spawn"));
+    }
+
+    #[test]
+    fn audit_log_is_a_ring_buffer() {
+        let mut world = World::new();
+
+        world.enable_audit(2);
+
+        let entities: Vec<_> = (0..3).map(|_| world.spawn(()).id()).collect();
+        let log = world.audit_log().unwrap();
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(
+            log.iter().map(AuditEntry::op).collect::<Vec<_>>(),
+            [AuditOp::Spawn(entities[1]), AuditOp::Spawn(entities[2])],
+        );
+        assert_eq!(world.disable_audit().unwrap().capacity(), 2);
+        assert!(world.audit_log().is_none());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::panic::Location;
+use std::sync::{Mutex, PoisonError};
+
+use super::WorldPtr;
+use crate::access::WorldAccess;
+
+/// The outstanding borrows of a world made through [`WorldPtr::track`].
+#[derive(Debug, Default)]
+pub(crate) struct BorrowTracker {
+    /// Borrows indexed by their [`WorldBorrow`], `None` once released.
+    borrows: Mutex<Vec<Option<TrackedBorrow>>>,
+}
+
+/// A borrow of a world registered with [`WorldPtr::track`].
+///
+/// The borrow is released when this is dropped.
+///
+/// Requires the `borrow_tracker` feature.
+pub struct WorldBorrow<'w> {
+    world: WorldPtr<'w>,
+    index: usize,
+}
+
+#[derive(Debug)]
+struct TrackedBorrow {
+    access: WorldAccess,
+    caller: &'static Location<'static>,
+}
+
+impl<'w> WorldPtr<'w> {
+    /// Registers a borrow of the world for the duration of the returned guard.
+    ///
+    /// Used by [`Query`](crate::query::Query) and unsafe code that accesses
+    /// the world through a pointer, such as manual calls to
+    /// [`System::run`](crate::system::System::run), to catch aliasing during
+    /// development instead of corrupting memory. Resources are additionally
+    /// checked when they are borrowed, with or without tracking.
+    ///
+    /// Leaking the guard leaks the borrow.
+    ///
+    /// Requires the `borrow_tracker` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the access conflicts with an outstanding borrow.
+    ///
+    /// ```should_panic
+    /// # use worldlines::prelude::*;
+    /// # use worldlines::access::{Level, WorldAccess};
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// let world = World::new();
+    /// let mut access = WorldAccess::new();
+    ///
+    /// access.borrows_component::<Health>(Level::Write);
+    ///
+    /// let _first = world.as_ptr().track(access);
+    /// let mut access = WorldAccess::new();
+    ///
+    /// access.borrows_component::<Health>(Level::Read);
+    ///
+    /// // panics, as the first borrow is still alive
+    /// let _second = world.as_ptr().track(access);
+    /// ```
+    #[track_caller]
+    pub fn track(self, access: WorldAccess) -> WorldBorrow<'w> {
+        let caller = Location::caller();
+        // SAFETY: access to world metadata is always valid
+        let tracker = unsafe { &self.as_ref().borrows };
+        let mut borrows =
+            tracker.borrows.lock().unwrap_or_else(PoisonError::into_inner);
+        let conflict = borrows.iter().flatten().find_map(|borrow| {
+            access
+                .compatible_with(&borrow.access)
+                .err()
+                .map(|error| (error, borrow.caller))
+        });
+
+        if let Some((error, other)) = conflict {
+            drop(borrows);
+
+            panic!(
+                "borrow at {caller} conflicts with borrow at {other}: {error}"
+            );
+        }
+
+        let borrow = TrackedBorrow { access, caller };
+        let index = match borrows.iter().position(Option::is_none) {
+            Some(index) => {
+                borrows[index] = Some(borrow);
+
+                index
+            },
+            None => {
+                borrows.push(Some(borrow));
+
+                borrows.len() - 1
+            },
+        };
+
+        WorldBorrow { world: self, index }
+    }
+}
+
+impl BorrowTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of outstanding borrows.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.borrows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .flatten()
+            .count()
+    }
+}
+
+impl Drop for WorldBorrow<'_> {
+    fn drop(&mut self) {
+        // SAFETY: access to world metadata is always valid
+        let tracker = unsafe { &self.world.as_ref().borrows };
+        let mut borrows =
+            tracker.borrows.lock().unwrap_or_else(PoisonError::into_inner);
+
+        borrows[self.index] = None;
+
+        while borrows.last().is_some_and(Option::is_none) {
+            borrows.pop();
+        }
+    }
+}
+
+impl fmt::Debug for WorldBorrow<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorldBorrow")
+            .field("world", &self.world)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use crate::prelude::*;
+    use crate::query::Query;
+
+    #[derive(Component)]
+    struct Health;
+
+    #[test]
+    fn aliasing_queries_panic() {
+        let mut world = World::new();
+
+        world.spawn(Health);
+
+        let ptr = world.as_ptr_mut();
+        // SAFETY: the queries only read the world
+        let first = unsafe { Query::<&Health>::new(ptr) }.unwrap();
+        let second = unsafe { Query::<&Health>::new(ptr) }.unwrap();
+
+        // SAFETY: access to world metadata is always valid
+        assert_eq!(unsafe { ptr.as_ref() }.borrows.len(), 2);
+
+        drop((first, second));
+
+        let _first = unsafe { Query::<&mut Health>::new(ptr) }.unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            // SAFETY: this aliases the first query, which is caught
+            unsafe { Query::<&Health>::new(ptr) }.map(drop)
+        }));
+
+        assert!(result.is_err());
+        // SAFETY: see above
+        assert_eq!(unsafe { ptr.as_ref() }.borrows.len(), 1);
+    }
+}
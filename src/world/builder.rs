@@ -0,0 +1,152 @@
+use std::panic::Location;
+
+use super::{ResourceConflict, SpawnIter, World};
+use crate::component::Bundle;
+use crate::entity::{EntityHashMap, EntityId, EntityNotFound, EntityWorld};
+use crate::resource::Resource;
+
+/// A world that can be populated on another thread.
+///
+/// Worlds can't be sent between threads, as registered systems don't have to
+/// be [`Send`]. A builder only allows spawning entities and creating
+/// resources, so it can be built on a worker thread and then
+/// [merged](WorldBuilder::merge_into) into a world on the main thread.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Wall;
+///
+/// let builder = std::thread::spawn(|| {
+///     let mut builder = WorldBuilder::new();
+///
+///     builder.spawn_iter((0..16).map(|_| Wall));
+///     builder
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let mut world = World::new();
+///
+/// builder.merge_into(&mut world, ResourceConflict::Replace);
+///
+/// assert_eq!(world.len(), 16);
+/// ```
+#[derive(Debug, Default)]
+pub struct WorldBuilder {
+    world: World,
+}
+
+// SAFETY: systems are the only part of a world that isn't `Send`, and none can
+// be registered through a builder
+unsafe impl Send for WorldBuilder {}
+
+impl WorldBuilder {
+    /// Creates a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of entities in this builder.
+    pub fn len(&self) -> usize {
+        self.world.len()
+    }
+
+    /// Returns `true` if this builder contains no entities.
+    pub fn is_empty(&self) -> bool {
+        self.world.is_empty()
+    }
+
+    /// Spawns a new entity with its components.
+    ///
+    /// See [`World::spawn`].
+    #[track_caller]
+    pub fn spawn(&mut self, bundle: impl Bundle) -> EntityWorld<'_> {
+        self.world.spawn(bundle)
+    }
+
+    /// Spawns an entity for each bundle in an iterator.
+    ///
+    /// See [`World::spawn_iter`].
+    #[track_caller]
+    pub fn spawn_iter(
+        &mut self,
+        bundles: impl IntoIterator<Item: Bundle>,
+    ) -> SpawnIter<'_> {
+        self.world.spawn_iter_with_caller(bundles, Location::caller())
+    }
+
+    /// Mutably borrows an entity in this builder.
+    ///
+    /// Returns an error if the entity doesn't exist.
+    pub fn entity_mut(
+        &mut self,
+        entity: EntityId,
+    ) -> Result<EntityWorld<'_>, EntityNotFound> {
+        self.world.entity_mut(entity)
+    }
+
+    /// Inserts a resource.
+    ///
+    /// Returns the previous value if there was one.
+    pub fn create<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.world.create(resource)
+    }
+
+    /// Returns the built world.
+    pub fn build(self) -> World {
+        self.world
+    }
+
+    /// Moves the built entities and resources into a world.
+    ///
+    /// See [`World::append`].
+    #[track_caller]
+    pub fn merge_into(
+        mut self,
+        world: &mut World,
+        resources: ResourceConflict,
+    ) -> EntityHashMap<EntityId> {
+        world.append(&mut self.world, resources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Tile(u32);
+
+    #[derive(Resource)]
+    struct Seed(u64);
+
+    #[test]
+    fn build_on_thread() {
+        let (builder, first) = thread::spawn(|| {
+            let mut builder = WorldBuilder::new();
+
+            builder.create(Seed(42));
+
+            let first = builder.spawn(Tile(0)).id();
+
+            builder.spawn_iter((1..8).map(Tile));
+
+            (builder, first)
+        })
+        .join()
+        .unwrap();
+
+        let mut world = World::new();
+        let map = builder.merge_into(&mut world, ResourceConflict::Keep);
+
+        assert_eq!(world.len(), 8);
+        assert_eq!(
+            world.entity(map[&first]).unwrap().get::<Tile>().unwrap().0,
+            0
+        );
+        assert_eq!(world.resource::<Seed>().unwrap().0, 42);
+    }
+}
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+
+use super::{World, WorldPtr};
+use crate::access::{AccessError, WorldAccess};
+use crate::commands::{Commands, WorldQueue};
+use crate::query::{Query, QueryData};
+use crate::resource::{Res, ResMut, Resource, ResourceError};
+
+/// Shared access to a [`World`] that is validated at runtime.
+///
+/// Created by [`World::cell`]. Any number of queries can be live at once as
+/// long as their [access](WorldAccess) doesn't conflict. Structural changes are
+/// queued with [`WorldCell::queue`] and applied when the cell is dropped.
+pub struct WorldCell<'w> {
+    world: WorldPtr<'w>,
+    /// The accesses of the queries currently in use.
+    live: RefCell<Vec<WorldAccess>>,
+    commands: RefCell<Commands>,
+}
+
+/// Removes the most recent live access when dropped, including on panic.
+struct LiveGuard<'c> {
+    live: &'c RefCell<Vec<WorldAccess>>,
+}
+
+impl<'w> WorldCell<'w> {
+    pub(super) fn new(world: &'w mut World) -> Self {
+        let world = world.as_ptr_mut();
+        let live = RefCell::new(Vec::new());
+        let commands = RefCell::new(Commands::new());
+
+        Self { world, live, commands }
+    }
+
+    /// Runs a function with a query of data from the world.
+    ///
+    /// Returns an error if the query access is invalid or conflicts with a
+    /// query that is still in use, such as a query of an outer call.
+    pub fn query<D: QueryData, T>(
+        &self,
+        f: impl FnOnce(Query<'_, D>) -> T,
+    ) -> Result<T, AccessError> {
+        let mut access = WorldAccess::new();
+
+        D::world_access(&mut access);
+        access.result()?;
+
+        for live in self.live.borrow().iter() {
+            access.compatible_with(live)?;
+        }
+
+        self.live.borrow_mut().push(access);
+
+        let _guard = LiveGuard { live: &self.live };
+        // SAFETY: the cell was created from a mutable reference and the access
+        // doesn't conflict with any other access through the cell
+        let query = unsafe { Query::new(self.world) }?;
+
+        Ok(f(query))
+    }
+
+    /// Immutably borrows a resource.
+    ///
+    /// Returns an error if the resource doesn't exist or is borrowed mutably.
+    pub fn resource<R: Resource>(&self) -> Result<Res<'_, R>, ResourceError> {
+        // SAFETY: resource borrows are checked at runtime and queries don't
+        // access resources
+        unsafe { self.world.as_ref() }.resource()
+    }
+
+    /// Mutably borrows a resource.
+    ///
+    /// Returns an error if the resource doesn't exist or is already borrowed.
+    pub fn resource_mut<R: Resource>(
+        &self,
+    ) -> Result<ResMut<'_, R>, ResourceError> {
+        // SAFETY: resource borrows are checked at runtime and queries don't
+        // access resources
+        unsafe { self.world.as_ref() }.resource_mut()
+    }
+
+    /// Runs a function with a [`WorldQueue`] to queue structural changes.
+    ///
+    /// The commands are applied when the cell is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within `f`.
+    pub fn queue<T>(&self, f: impl FnOnce(&mut WorldQueue<'_, '_>) -> T) -> T {
+        let mut commands = self.commands.borrow_mut();
+        // SAFETY: entity metadata is only read, and reserving entities is
+        // thread-safe
+        let entities = unsafe { &self.world.as_ref().entities };
+
+        f(&mut WorldQueue::from_entities(entities, &mut commands))
+    }
+}
+
+impl World {
+    /// Returns a [`WorldCell`] for runtime-validated shared access to this
+    /// world.
+    pub fn cell(&mut self) -> WorldCell<'_> {
+        WorldCell::new(self)
+    }
+}
+
+// ---
+
+impl Drop for WorldCell<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the cell was created from a mutable reference and nothing
+        // borrows from it anymore
+        let world = unsafe { self.world.as_mut() };
+
+        self.commands.get_mut().apply(world);
+    }
+}
+
+impl Drop for LiveGuard<'_> {
+    fn drop(&mut self) {
+        self.live.borrow_mut().pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Poisoned;
+
+    #[derive(Resource)]
+    struct Damage(u32);
+
+    #[test]
+    fn nested_queries_are_validated() {
+        let mut world = World::new();
+
+        world.spawn((Health(10), Poisoned));
+        world.spawn(Health(5));
+        world.create(Damage(2));
+
+        let cell = world.cell();
+
+        cell.query::<(EntityId, &Poisoned), _>(|poisoned| {
+            let damage = cell.resource::<Damage>().unwrap();
+
+            cell.query::<&mut Health, _>(|mut health| {
+                for (entity, _) in &poisoned {
+                    health.get_mut(entity).unwrap().0 -= damage.0;
+                }
+            })
+            .unwrap();
+
+            assert!(cell.query::<&mut Poisoned, _>(|_| ()).is_err());
+        })
+        .unwrap();
+
+        assert!(cell.query::<&mut Poisoned, _>(|_| ()).is_ok());
+        drop(cell);
+
+        let mut health: Vec<_> =
+            world.query::<&Health>().unwrap().iter().map(|h| h.0).collect();
+
+        health.sort();
+
+        assert_eq!(health, [5, 8]);
+    }
+
+    #[test]
+    fn queued_changes_are_applied_on_drop() {
+        let mut world = World::new();
+        let cell = world.cell();
+
+        cell.query::<&Health, _>(|query| {
+            assert!(query.is_empty());
+
+            cell.queue(|queue| queue.spawn(Health(1)).id());
+        })
+        .unwrap();
+
+        drop(cell);
+
+        assert_eq!(world.len(), 1);
+    }
+}
@@ -0,0 +1,16 @@
+use super::World;
+
+/// Trait for values that can be created from a world.
+///
+/// Implemented for all types that implement [`Default`]. Used by
+/// [`World::init_resource`] for resources that depend on other resources.
+pub trait FromWorld {
+    /// Creates this value from a world.
+    fn from_world(world: &mut World) -> Self;
+}
+
+impl<T: Default> FromWorld for T {
+    fn from_world(_world: &mut World) -> Self {
+        T::default()
+    }
+}
@@ -0,0 +1,463 @@
+use std::any::{type_name, Any};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::{fmt, mem};
+
+use super::World;
+use crate::component::{Component, ComponentId, ComponentInfo};
+use crate::entity::{EntityHashMap, EntityId, EntityRef, EntityWorld};
+use crate::marker::MaybeSendSync;
+
+/// An index of entities by a key computed from one of their components.
+///
+/// Created with [`World::add_index`] or [`World::add_unique_index`] and
+/// retrieved with [`World::index`]. The index is updated when the component is
+/// inserted, replaced or removed, including when the entity is spawned or
+/// despawned. Changing the value through a mutable reference isn't tracked, so
+/// insert a new value to change the key of an entity.
+pub struct ComponentIndex<C, K> {
+    key: Box<KeyFn<C, K>>,
+    entities: HashMap<K, Vec<EntityId>>,
+    keys: EntityHashMap<K>,
+    unique: bool,
+    _marker: PhantomData<fn() -> C>,
+}
+
+/// The function that computes the key of a component.
+#[cfg(not(feature = "single_threaded"))]
+type KeyFn<C, K> = dyn Fn(&C) -> K + Send + Sync;
+
+/// The function that computes the key of a component.
+#[cfg(feature = "single_threaded")]
+type KeyFn<C, K> = dyn Fn(&C) -> K;
+
+/// A type-erased [`ComponentIndex`].
+pub(crate) trait ErasedIndex: Any + MaybeSendSync {
+    /// Indexes an entity by the current value of its component.
+    ///
+    /// Returns the entity that already has the key if the index is unique.
+    /// The index is left unchanged if the key is taken.
+    fn insert(&mut self, entity: EntityRef<'_>) -> Result<(), EntityId>;
+
+    /// Returns another entity that has the key of a component value if the
+    /// index is unique, before the value is written to the entity.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to a value of the indexed component.
+    unsafe fn conflict(
+        &self,
+        _entity: EntityId,
+        _value: NonNull<u8>,
+    ) -> Option<EntityId> {
+        None
+    }
+
+    /// Removes an entity from the index.
+    fn remove(&mut self, entity: EntityId);
+
+    fn as_any(&self) -> &dyn Any;
+
+    #[cfg(feature = "spatial")]
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl World {
+    /// Adds an index of the entities with a component by a key.
+    ///
+    /// Entities that already contain the component are indexed immediately.
+    /// Replaces an existing index with the same component and key types.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// #[derive(Component)]
+    /// struct PlayerId(u32);
+    ///
+    /// let mut world = World::new();
+    ///
+    /// world.add_index(|id: &PlayerId| id.0);
+    ///
+    /// let player = world.spawn(PlayerId(7)).id();
+    /// let index = world.index::<PlayerId, u32>().unwrap();
+    ///
+    /// assert_eq!(index.get(&7).collect::<Vec<_>>(), [player]);
+    /// ```
+    pub fn add_index<C, K>(
+        &mut self,
+        key: impl Fn(&C) -> K + MaybeSendSync + 'static,
+    ) where
+        C: Component,
+        K: Eq + Hash + Clone + MaybeSendSync + 'static,
+    {
+        self.add_index_inner(ComponentIndex::new(key, false));
+    }
+
+    /// Adds an index of the entities with a component by a key that is
+    /// unique to each entity.
+    ///
+    /// See [`World::add_index`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if two entities have the same key, either when the index is
+    /// added or when a component is inserted afterwards. A component inserted
+    /// with [`EntityWorld::insert`](crate::entity::EntityWorld::insert) is
+    /// rejected before it is written, so the entity keeps its previous value.
+    /// A component written by a bundle is removed from the entity again.
+    pub fn add_unique_index<C, K>(
+        &mut self,
+        key: impl Fn(&C) -> K + MaybeSendSync + 'static,
+    ) where
+        C: Component,
+        K: Eq + Hash + Clone + MaybeSendSync + 'static,
+    {
+        self.add_index_inner(ComponentIndex::new(key, true));
+    }
+
+    fn add_index_inner<C, K>(&mut self, mut index: ComponentIndex<C, K>)
+    where
+        C: Component,
+        K: Eq + Hash + Clone + MaybeSendSync + 'static,
+    {
+        let entities: Vec<_> = self
+            .components
+            .tables()
+            .filter(|(_, table)| table.components().contains(C::id()))
+            .flat_map(|(_, table)| table.entities().copied())
+            .collect();
+
+        for entity in entities {
+            // SAFETY: the entity is contained in a table, so it is alive
+            let entity = unsafe { EntityRef::new_unchecked(entity, self) };
+
+            if let Err(other) = index.insert(entity) {
+                unique_violation(entity.id(), other, type_name::<C>());
+            }
+        }
+
+        self.listeners
+            .indexes
+            .retain(|(_, other)| !other.as_any().is::<ComponentIndex<C, K>>());
+        self.listeners.indexes.push((C::id(), Box::new(index)));
+    }
+
+    /// Returns the index of a component by a key, if it was added.
+    pub fn index<C, K>(&self) -> Option<&ComponentIndex<C, K>>
+    where
+        C: Component,
+        K: Eq + Hash + Clone + MaybeSendSync + 'static,
+    {
+        self.listeners
+            .indexes
+            .iter()
+            .find_map(|(_, index)| index.as_any().downcast_ref())
+    }
+
+    /// Panics if a component value has the key of another entity in a unique
+    /// index, before the value is written to the entity.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to a value of the component.
+    pub(crate) unsafe fn check_indexes(
+        &self,
+        entity: EntityId,
+        component: ComponentId,
+        value: NonNull<u8>,
+    ) {
+        for (_, index) in
+            self.listeners.indexes.iter().filter(|(id, _)| *id == component)
+        {
+            // SAFETY: the caller ensures that the pointer is to a value of the
+            // component
+            if let Some(other) = unsafe { index.conflict(entity, value) } {
+                unique_violation(
+                    entity,
+                    other,
+                    ComponentInfo::of_id(component),
+                );
+            }
+        }
+    }
+
+    /// Updates the indexes of a component that was inserted into an entity.
+    ///
+    /// If the key is taken in a unique index, the component is removed from
+    /// the entity before panicking, so that the indexes match the components.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be alive.
+    pub(crate) unsafe fn notify_insert(
+        &mut self,
+        entity: EntityId,
+        component: ComponentId,
+    ) {
+        if self.listeners.indexes.is_empty() {
+            return;
+        }
+
+        let mut indexes = mem::take(&mut self.listeners.indexes);
+        // SAFETY: the caller ensures that the entity is alive
+        let entity_ref = unsafe { EntityRef::new_unchecked(entity, self) };
+        let mut conflict = None;
+
+        for (_, index) in indexes.iter_mut().filter(|(id, _)| *id == component)
+        {
+            if let Err(other) = index.insert(entity_ref) {
+                conflict = Some(other);
+            }
+        }
+
+        self.listeners.indexes = indexes;
+
+        if let Some(other) = conflict {
+            // SAFETY: the caller ensures that the entity is alive
+            let mut entity_world =
+                unsafe { EntityWorld::new_unchecked(entity, self) };
+
+            _ = entity_world.remove_by_id(component);
+
+            unique_violation(entity, other, ComponentInfo::of_id(component));
+        }
+    }
+}
+
+impl<C: Component, K: Eq + Hash + Clone> ComponentIndex<C, K> {
+    fn new(
+        key: impl Fn(&C) -> K + MaybeSendSync + 'static,
+        unique: bool,
+    ) -> Self {
+        Self {
+            key: Box::new(key),
+            entities: HashMap::new(),
+            keys: EntityHashMap::default(),
+            unique,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `true` if keys in this index are unique to each entity.
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns the amount of indexed entities.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no entities are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns an iterator over the entities with the key, in insertion order.
+    pub fn get(&self, key: &K) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities.get(key).into_iter().flatten().copied()
+    }
+
+    /// Returns the first entity with the key.
+    pub fn first(&self, key: &K) -> Option<EntityId> {
+        self.get(key).next()
+    }
+
+    /// Returns the key of an entity, if it is indexed.
+    pub fn key(&self, entity: EntityId) -> Option<&K> {
+        self.keys.get(&entity)
+    }
+
+    /// Returns `true` if an entity has the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entities.contains_key(key)
+    }
+
+    /// Returns an entity other than `entity` that has the key, if this index
+    /// is unique.
+    fn other_with(&self, key: &K, entity: EntityId) -> Option<EntityId> {
+        if !self.unique {
+            return None;
+        }
+
+        self.get(key).find(|&other| other != entity)
+    }
+}
+
+impl<C, K> ErasedIndex for ComponentIndex<C, K>
+where
+    C: Component,
+    K: Eq + Hash + Clone + MaybeSendSync + 'static,
+{
+    fn insert(&mut self, entity: EntityRef<'_>) -> Result<(), EntityId> {
+        let Ok(component) = entity.get::<C>() else {
+            return Ok(());
+        };
+        let key = (self.key)(component);
+
+        // checked before removing the previous key, which is kept on failure
+        if let Some(other) = self.other_with(&key, entity.id()) {
+            return Err(other);
+        }
+
+        // the key changes when the component is replaced
+        self.remove(entity.id());
+        self.entities.entry(key.clone()).or_default().push(entity.id());
+        self.keys.insert(entity.id(), key);
+
+        Ok(())
+    }
+
+    unsafe fn conflict(
+        &self,
+        entity: EntityId,
+        value: NonNull<u8>,
+    ) -> Option<EntityId> {
+        // SAFETY: the caller ensures that the pointer is to a `C`
+        let component = unsafe { value.cast::<C>().as_ref() };
+
+        self.other_with(&(self.key)(component), entity)
+    }
+
+    fn remove(&mut self, entity: EntityId) {
+        let Some(key) = self.keys.remove(&entity) else {
+            return;
+        };
+
+        if let Some(entities) = self.entities.get_mut(&key) {
+            entities.retain(|&other| other != entity);
+
+            if entities.is_empty() {
+                self.entities.remove(&key);
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[cfg(feature = "spatial")]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn unique_violation(
+    entity: EntityId,
+    other: EntityId,
+    component: impl fmt::Display,
+) -> ! {
+    panic!(
+        "entities {entity:?} and {other:?} have the same key in a unique \
+         index of {component}",
+    );
+}
+
+impl<C, K: fmt::Debug> fmt::Debug for ComponentIndex<C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(&format!("ComponentIndex<{}>", type_name::<C>()))
+            .field("entities", &self.entities)
+            .field("unique", &self.unique)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct PlayerId(u32);
+
+    #[derive(Component)]
+    struct Team(&'static str);
+
+    #[test]
+    fn index_is_maintained() {
+        let mut world = World::new();
+        let red = world.spawn(Team("red")).id();
+
+        world.add_index(|team: &Team| team.0);
+
+        let blue = world.spawn(Team("blue")).id();
+        let other = world.spawn(Team("red")).id();
+        let index = world.index::<Team, &str>().unwrap();
+
+        assert_eq!(index.get(&"red").collect::<Vec<_>>(), [red, other]);
+        assert_eq!(index.first(&"blue"), Some(blue));
+
+        world.entity_mut(blue).unwrap().insert(Team("red"));
+        world.entity_mut(other).unwrap().remove::<Team>().unwrap();
+        world.despawn(red).unwrap();
+
+        let index = world.index::<Team, &str>().unwrap();
+
+        assert_eq!(index.get(&"red").collect::<Vec<_>>(), [blue]);
+        assert!(!index.contains_key(&"blue"));
+        assert_eq!(index.key(other), None);
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    #[should_panic = "same key in a unique index"]
+    fn unique_index_panics_on_duplicate() {
+        let mut world = World::new();
+
+        world.add_unique_index(|id: &PlayerId| id.0);
+
+        let player = world.spawn(PlayerId(7)).id();
+
+        // replacing with the same key is allowed
+        world.entity_mut(player).unwrap().insert(PlayerId(7));
+
+        assert!(world.index::<PlayerId, u32>().unwrap().is_unique());
+
+        world.spawn(PlayerId(7));
+    }
+
+    #[test]
+    fn unique_index_rejects_duplicate() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut world = World::new();
+
+        world.add_unique_index(|id: &PlayerId| id.0);
+
+        let first = world.spawn(PlayerId(1)).id();
+        let second = world.spawn(PlayerId(2)).id();
+
+        // rejected before the component is written
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            world.entity_mut(second).unwrap().insert(PlayerId(1));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(
+            world.entity(second).unwrap().get::<PlayerId>().unwrap().0,
+            2
+        );
+
+        // written by a bundle, so removed again
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            world.spawn((PlayerId(2), Team("red")));
+        }));
+
+        assert!(result.is_err());
+
+        let (third, _) =
+            world.query::<(EntityId, &Team)>().unwrap().iter().next().unwrap();
+
+        assert!(!world.entity(third).unwrap().contains::<PlayerId>());
+
+        let index = world.index::<PlayerId, u32>().unwrap();
+
+        assert_eq!(index.key(first), Some(&1));
+        assert_eq!(index.key(second), Some(&2));
+        assert_eq!(index.key(third), None);
+        assert_eq!(index.get(&1).collect::<Vec<_>>(), [first]);
+        assert_eq!(index.get(&2).collect::<Vec<_>>(), [second]);
+        assert_eq!(index.len(), 2);
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use super::World;
+use crate::component::{ComponentInfo, TableId};
+use crate::entity::{EntityAddr, EntityId};
+use crate::storage::TableRow;
+
+/// The result of [`World::check_invariants`].
+///
+/// Formatting with [`Display`](fmt::Display) lists every violation found.
+#[derive(Debug, Clone, Default)]
+pub struct InvariantReport {
+    /// The violations found, in the order they were checked.
+    pub violations: Vec<InvariantViolation>,
+}
+
+/// A broken invariant of the storage of a [`World`].
+///
+/// These can only be caused by misusing unsafe APIs or by bugs in this crate.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvariantViolation {
+    /// The address of a live entity refers to a table that doesn't exist.
+    #[error("entity {entity:?} refers to missing table {:?}", .addr.table)]
+    MissingTable { entity: EntityId, addr: EntityAddr },
+    /// The row at the address of a live entity contains a different entity.
+    #[error(
+        "entity {entity:?} refers to row {} of table {:?}, which contains \
+         {found:?}",
+        .addr.row.0,
+        .addr.table,
+    )]
+    AddressMismatch {
+        entity: EntityId,
+        addr: EntityAddr,
+        found: Option<EntityId>,
+    },
+    /// A table contains an entity that isn't alive.
+    #[error("row {} of table {table:?} contains dead entity {entity:?}", .row.0)]
+    DeadEntity { entity: EntityId, table: TableId, row: TableRow },
+    /// A table contains a live entity whose address is elsewhere.
+    #[error(
+        "row {} of table {table:?} contains entity {entity:?}, which is \
+         located at {addr:?}",
+        .row.0,
+    )]
+    StrayEntity {
+        entity: EntityId,
+        table: TableId,
+        row: TableRow,
+        addr: Option<EntityAddr>,
+    },
+    /// A column can't hold a component for every row of its table.
+    #[error(
+        "column {component} of table {table:?} has capacity {capacity} for \
+         {rows} rows"
+    )]
+    ColumnCapacity {
+        table: TableId,
+        component: ComponentInfo,
+        capacity: usize,
+        rows: usize,
+    },
+    /// The internal command buffer wasn't flushed.
+    #[error("{0} commands are pending in the world's command buffer")]
+    PendingCommands(usize),
+}
+
+impl World {
+    /// Validates the internal invariants of this world.
+    ///
+    /// Checks that the address of each entity matches its table row, that
+    /// tables contain no dead entities, that columns can hold every row of
+    /// their table and that no internal commands are pending.
+    ///
+    /// Only available in debug builds or with the `check_invariants` feature.
+    /// Takes linear time in the amount of entities and tables.
+    pub fn check_invariants(&self) -> InvariantReport {
+        let mut violations = Vec::new();
+
+        for (entity, addr) in &self.entities {
+            let Some(addr) = addr else {
+                continue;
+            };
+
+            let Some(table) = self.components.get(addr.table) else {
+                violations
+                    .push(InvariantViolation::MissingTable { entity, addr });
+
+                continue;
+            };
+            let found = table.entity(addr.row);
+
+            if found != Some(entity) {
+                violations.push(InvariantViolation::AddressMismatch {
+                    entity,
+                    addr,
+                    found,
+                });
+            }
+        }
+
+        for (index, table) in self.components.tables() {
+            for (row, entity) in table.rows() {
+                if !self.entities.contains(entity) {
+                    violations.push(InvariantViolation::DeadEntity {
+                        entity,
+                        table: index,
+                        row,
+                    });
+
+                    continue;
+                }
+
+                let addr = self.entities.get(entity);
+
+                if addr != Some(EntityAddr { table: index, row }) {
+                    violations.push(InvariantViolation::StrayEntity {
+                        entity,
+                        table: index,
+                        row,
+                        addr,
+                    });
+                }
+            }
+
+            let rows = table.rows_len();
+
+            for column in table.columns() {
+                if column.capacity() < rows {
+                    violations.push(InvariantViolation::ColumnCapacity {
+                        table: index,
+                        component: column.component(),
+                        capacity: column.capacity(),
+                        rows,
+                    });
+                }
+            }
+        }
+
+        if !self.commands.is_empty() {
+            violations
+                .push(InvariantViolation::PendingCommands(self.commands.len()));
+        }
+
+        InvariantReport { violations }
+    }
+}
+
+impl InvariantReport {
+    /// Returns `true` if no invariants were violated.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Panics with this report if any invariants were violated.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        assert!(self.is_ok(), "world invariants violated:\n{self}");
+    }
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "no violations");
+        }
+
+        writeln!(f, "{} violations", self.violations.len())?;
+
+        for violation in &self.violations {
+            writeln!(f, "- {violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A(#[expect(unused)] u32);
+
+    #[test]
+    fn valid_world() {
+        let mut world = World::new();
+        let entities: Vec<_> = world.spawn_iter((0..8).map(A)).collect();
+
+        world.spawn(());
+
+        for entity in entities.into_iter().step_by(2) {
+            world.despawn(entity).unwrap();
+        }
+
+        world.check_invariants().assert_ok();
+        world.maintain(Duration::MAX);
+        world.check_invariants().assert_ok();
+    }
+
+    #[test]
+    fn dead_entity_in_table() {
+        let mut world = World::new();
+        let dead = world.spawn(()).id();
+        let table = world.entities.get(dead).unwrap().table;
+
+        world.despawn(dead).unwrap();
+
+        // SAFETY: the table has no columns, so nothing is read or dropped
+        let row =
+            unsafe { world.components.get_unchecked_mut(table).push_new(dead) };
+
+        let report = world.check_invariants();
+
+        assert_eq!(
+            report.violations,
+            [InvariantViolation::DeadEntity { entity: dead, table, row }],
+        );
+        assert!(report.to_string().contains("dead entity"));
+    }
+
+    #[test]
+    fn pending_commands() {
+        let mut world = World::new();
+
+        world.commands.push_fn(|_| ());
+
+        assert_eq!(
+            world.check_invariants().violations,
+            [InvariantViolation::PendingCommands(1)],
+        );
+
+        world.flush();
+        world.check_invariants().assert_ok();
+    }
+}
@@ -0,0 +1,234 @@
+use std::{fmt, mem};
+
+use super::{ErasedIndex, World};
+use crate::component::{ComponentId, Components, TableId};
+use crate::entity::{EntityId, EntityRef};
+use crate::marker::MaybeSendSync;
+use crate::storage::Table;
+
+/// A component being added to or removed from the archetype of an entity.
+///
+/// Passed to listeners registered with [`World::on_archetype_change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchetypeChange {
+    entity: EntityId,
+    component: ComponentId,
+    added: bool,
+}
+
+/// Why an entity was despawned.
+///
+/// Passed to listeners registered with [`World::on_despawn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DespawnReason {
+    /// Despawned individually, such as by [`World::despawn`] or a queued
+    /// despawn.
+    Explicit,
+    /// Despawned along with other entities by [`World::despawn_where`] or
+    /// [`World::retain_entities`].
+    Batch,
+    /// Despawned by [`World::despawn_all`] or [`World::clear`].
+    Clear,
+    /// Moved out of the world by [`World::take_entity`].
+    Taken,
+}
+
+/// Listeners for changes to entities registered on a world.
+#[derive(Default)]
+pub(crate) struct Listeners {
+    archetype: Vec<(ComponentId, ArchetypeListener)>,
+    despawn: Vec<DespawnListener>,
+    /// Indexes added with [`World::add_index`].
+    pub(crate) indexes: Vec<(ComponentId, Box<dyn ErasedIndex>)>,
+    /// The log enabled with [`World::enable_audit`].
+    #[cfg(feature = "audit")]
+    pub(crate) audit: Option<super::AuditLog>,
+}
+
+#[cfg(not(feature = "single_threaded"))]
+type ArchetypeListener = Box<dyn FnMut(ArchetypeChange) + Send + Sync>;
+
+#[cfg(feature = "single_threaded")]
+type ArchetypeListener = Box<dyn FnMut(ArchetypeChange)>;
+
+#[cfg(not(feature = "single_threaded"))]
+type DespawnListener =
+    Box<dyn FnMut(EntityRef<'_>, DespawnReason) + Send + Sync>;
+
+#[cfg(feature = "single_threaded")]
+type DespawnListener = Box<dyn FnMut(EntityRef<'_>, DespawnReason)>;
+
+impl World {
+    /// Registers a function that is called whenever a component is added to
+    /// or removed from an entity, moving it to another table.
+    ///
+    /// Called for spawned and despawned entities, but not when a component is
+    /// replaced, as the entity stays in its table. Intended for maintaining
+    /// external indexes, such as spatial indexes, without wrapping every
+    /// mutation of the world.
+    pub fn on_archetype_change(
+        &mut self,
+        component: ComponentId,
+        listener: impl FnMut(ArchetypeChange) + MaybeSendSync + 'static,
+    ) {
+        self.listeners.archetype.push((component, Box::new(listener)));
+    }
+
+    /// Registers a function that is called with every entity that is about to
+    /// be despawned.
+    ///
+    /// The entity still contains all of its components when the listener is
+    /// called. Any [`Component::before_remove`] hooks are run first.
+    ///
+    /// [`Component::before_remove`]: crate::component::Component::before_remove
+    pub fn on_despawn(
+        &mut self,
+        listener: impl FnMut(EntityRef<'_>, DespawnReason) + MaybeSendSync + 'static,
+    ) {
+        self.listeners.despawn.push(Box::new(listener));
+    }
+
+    /// Calls the despawn listeners with an entity that is about to be
+    /// despawned.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be alive.
+    pub(crate) unsafe fn notify_despawn(
+        &mut self,
+        entity: EntityId,
+        reason: DespawnReason,
+    ) {
+        if self.listeners.despawn.is_empty() {
+            return;
+        }
+
+        // listeners can't modify the world, so none are registered meanwhile
+        let mut listeners = mem::take(&mut self.listeners.despawn);
+        // SAFETY: the caller ensures that the entity is alive
+        let entity = unsafe { EntityRef::new_unchecked(entity, self) };
+
+        for listener in &mut listeners {
+            listener(entity, reason);
+        }
+
+        self.listeners.despawn = listeners;
+    }
+}
+
+impl ArchetypeChange {
+    /// Returns the entity that moved.
+    pub const fn entity(&self) -> EntityId {
+        self.entity
+    }
+
+    /// Returns the id of the component the listener was registered for.
+    pub const fn component(&self) -> ComponentId {
+        self.component
+    }
+
+    /// Returns `true` if the component was added to the entity.
+    pub const fn is_added(&self) -> bool {
+        self.added
+    }
+
+    /// Returns `true` if the component was removed from the entity.
+    pub const fn is_removed(&self) -> bool {
+        !self.added
+    }
+}
+
+impl Listeners {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "audit")]
+        if self.audit.is_some() {
+            return false;
+        }
+
+        self.archetype.is_empty()
+            && self.despawn.is_empty()
+            && self.indexes.is_empty()
+    }
+
+    /// Notifies the listeners of an entity moving from one table to another.
+    ///
+    /// `None` means the entity was spawned or despawned.
+    ///
+    /// # Safety
+    ///
+    /// The table ids must refer to tables in `components`.
+    pub unsafe fn archetype_changed(
+        &mut self,
+        components: &Components,
+        entity: EntityId,
+        old: Option<TableId>,
+        new: Option<TableId>,
+    ) {
+        #[cfg(feature = "audit")]
+        let audit = self.audit.is_some();
+        #[cfg(not(feature = "audit"))]
+        let audit = false;
+
+        if (self.archetype.is_empty() && self.indexes.is_empty() && !audit)
+            || old == new
+        {
+            return;
+        }
+
+        // SAFETY: the caller ensures that the tables exist
+        let [old, new] = [old, new].map(|table| {
+            table.map(|table| unsafe { components.get_unchecked(table) })
+        });
+
+        #[cfg(feature = "audit")]
+        if let Some(log) = &mut self.audit {
+            log.record_move(entity, old, new);
+        }
+
+        for (component, listener) in &mut self.archetype {
+            let component = *component;
+            let contains = |table: Option<&Table>| {
+                table
+                    .is_some_and(|table| table.components().contains(component))
+            };
+
+            match (contains(old), contains(new)) {
+                (false, true) => {
+                    listener(ArchetypeChange { entity, component, added: true })
+                },
+                (true, false) => listener(ArchetypeChange {
+                    entity,
+                    component,
+                    added: false,
+                }),
+                _ => {},
+            }
+        }
+
+        for (component, index) in &mut self.indexes {
+            let component = *component;
+            let contains = |table: Option<&Table>| {
+                table
+                    .is_some_and(|table| table.components().contains(component))
+            };
+
+            if contains(old) && !contains(new) {
+                index.remove(entity);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listeners")
+            .field("archetype", &self.archetype.len())
+            .field("despawn", &self.despawn.len())
+            .field("indexes", &self.indexes.len())
+            .finish_non_exhaustive()
+    }
+}
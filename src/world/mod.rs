@@ -1,47 +1,199 @@
 //! Defines the [`World`], the center of an ECS.
 
+use std::any::type_name;
+use std::cmp::Reverse;
+use std::marker::PhantomData;
 use std::mem;
+use std::ops::Range;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
+pub use self::append::*;
+#[cfg(feature = "audit")]
+pub use self::audit::*;
+#[cfg(feature = "borrow_tracker")]
+use self::borrows::BorrowTracker;
+#[cfg(feature = "borrow_tracker")]
+pub use self::borrows::WorldBorrow;
+#[cfg(not(feature = "single_threaded"))]
+pub use self::builder::*;
+pub use self::cell::*;
+pub use self::from_world::*;
+pub use self::index::*;
+#[cfg(any(debug_assertions, feature = "check_invariants"))]
+pub use self::invariants::*;
+pub use self::listeners::*;
+pub use self::prefab::*;
 pub use self::ptr::*;
+pub use self::record::*;
+#[cfg(not(feature = "single_threaded"))]
+pub use self::split::*;
+pub use self::stats::*;
+pub use self::view::*;
+use crate::marker::MaybeSendSync;
 use crate::prelude::*;
+use crate::storage::{CriticalSection, TableRow};
+pub use crate::storage::{GlobalAllocator, WorldAllocator};
 
+mod append;
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "borrow_tracker")]
+mod borrows;
+#[cfg(not(feature = "single_threaded"))]
+mod builder;
+mod cell;
+mod from_world;
+mod index;
+#[cfg(any(debug_assertions, feature = "check_invariants"))]
+mod invariants;
+mod listeners;
+mod prefab;
 mod ptr;
+mod record;
+#[cfg(not(feature = "single_threaded"))]
+mod split;
+mod stats;
 #[cfg(test)]
 mod tests;
+mod view;
 
 /// Stores all ECS data.
 ///
 /// - [Entity methods](#entity-methods)
 #[derive(Debug)]
 pub struct World {
+    id: WorldId,
     pub(crate) entities: Entities,
     pub(crate) components: Components,
     pub(crate) resources: Resources,
+    /// Index of entities by their [`Name`].
+    pub(crate) names: Names,
+    /// Index of entities by the targets of their [`Relation`]s.
+    pub(crate) relations: Relations,
     /// Storage for internally-buffered commands.
     pub(crate) commands: Commands,
+    /// Swapped in for `commands` while they are applied, so that both buffers
+    /// keep their allocations between flushes.
+    spare_commands: Commands,
+    /// Commands queued through [`EntityCommands`] query items, applied by
+    /// [`World::apply_deferred`].
+    pub(crate) deferred: Mutex<Commands>,
+    /// Listeners registered with [`World::on_archetype_change`].
+    pub(crate) listeners: Listeners,
+    /// Systems registered with [`World::register_system`].
+    systems: SystemRegistry,
+    /// Whether [`World::query`] and [`World::query_mut`] panic on invalid
+    /// access in debug builds.
+    panic_on_access_error: bool,
+    /// How errors returned by commands are handled.
+    command_error_handler: CommandErrorHandler,
+    /// Errors stored by [`CommandErrorHandler::Collect`].
+    command_errors: Vec<CommandError>,
+    /// Whether the components of despawned entities are dropped on flush.
+    ///
+    /// See [`World::set_graveyard`].
+    graveyard: bool,
+    /// Tables with buried rows.
+    buried: Vec<TableId>,
+    /// Borrows registered with [`WorldPtr::track`].
+    #[cfg(feature = "borrow_tracker")]
+    borrows: BorrowTracker,
 }
 
+/// A unique identifier for a [`World`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WorldId(u64);
+
 /// An iterator over all entities in a [`World`].
 #[derive(Clone)]
 pub struct EntitiesIter<'w> {
     inner: EntitySlots<'w>,
+    /// The amount of live entities left.
+    len: usize,
+}
+
+/// An iterator over references to all entities in a [`World`].
+///
+/// Created by [`World::iter_entities`].
+#[derive(Clone)]
+pub struct EntityRefIter<'w> {
+    inner: EntitiesIter<'w>,
+    world: WorldPtr<'w>,
+}
+
+/// An iterator over mutable references to all entities in a [`World`].
+///
+/// Created by [`World::iter_entities_mut`].
+pub struct EntityMutIter<'w> {
+    inner: EntitiesIter<'w>,
+    world: WorldPtr<'w>,
 }
 
 /// An iterator over entities created by [`World::spawn_iter`].
 #[derive(Clone)]
 pub struct SpawnIter<'w> {
-    inner: EntitySlots<'w>,
+    inner: Range<u32>,
+    _world: PhantomData<&'w World>,
 }
 
 impl World {
     /// Creates a new empty world.
     pub fn new() -> Self {
+        Self::new_in(GlobalAllocator)
+    }
+
+    /// Creates a new empty world that allocates component storage with an
+    /// allocator.
+    ///
+    /// Entity and resource metadata still use the global allocator.
+    pub fn new_in(alloc: impl WorldAllocator) -> Self {
+        let id = WorldId::new();
         let entities = Entities::new();
-        let components = Components::new();
+        let components = Components::new_in(alloc);
         let resources = Resources::new();
+        let names = Names::new();
+        let relations = Relations::new();
         let commands = Commands::new();
+        let spare_commands = Commands::new();
+        let deferred = Mutex::new(Commands::new());
+        let listeners = Listeners::new();
+        let systems = SystemRegistry::new();
+        let panic_on_access_error = false;
+        let command_error_handler = CommandErrorHandler::Panic;
+        let command_errors = Vec::new();
+        let graveyard = false;
+        let buried = Vec::new();
+        #[cfg(feature = "borrow_tracker")]
+        let borrows = BorrowTracker::new();
+
+        Self {
+            id,
+            entities,
+            components,
+            resources,
+            names,
+            relations,
+            commands,
+            spare_commands,
+            deferred,
+            listeners,
+            systems,
+            panic_on_access_error,
+            command_error_handler,
+            command_errors,
+            graveyard,
+            buried,
+            #[cfg(feature = "borrow_tracker")]
+            borrows,
+        }
+    }
 
-        Self { entities, components, resources, commands }
+    /// Returns the unique id of this world.
+    pub const fn id(&self) -> WorldId {
+        self.id
     }
 
     /// Returns a pointer to this world.
@@ -68,28 +220,141 @@ impl World {
              after use, as such it should be empty"
         );
     }
+
+    /// Sets whether [`World::query`] and [`World::query_mut`] panic on invalid
+    /// access instead of returning an error.
+    ///
+    /// Invalid access is almost always a bug, so panicking at the call site
+    /// can be more helpful than propagating the error. Only has an effect in
+    /// debug builds.
+    pub fn set_panic_on_access_error(&mut self, panic: bool) {
+        self.panic_on_access_error = panic;
+    }
+
+    /// Returns `true` if invalid access panics in [`World::query`] and
+    /// [`World::query_mut`].
+    ///
+    /// See [`World::set_panic_on_access_error`].
+    pub fn panics_on_access_error(&self) -> bool {
+        cfg!(debug_assertions) && self.panic_on_access_error
+    }
+
+    /// Sets how errors returned by commands are handled.
+    ///
+    /// Defaults to [`CommandErrorHandler::Panic`].
+    pub fn set_command_error_handler(&mut self, handler: CommandErrorHandler) {
+        self.command_error_handler = handler;
+    }
+
+    /// Returns how errors returned by commands are handled.
+    pub fn command_error_handler(&self) -> CommandErrorHandler {
+        self.command_error_handler
+    }
+
+    /// Sets whether the components of despawned entities are dropped the next
+    /// time commands are applied instead of immediately.
+    ///
+    /// Smooths out frames where many entities are despawned at once, as the
+    /// components are dropped a table at a time after the despawns. The
+    /// entities are still despawned immediately and their removal hooks still
+    /// run, but the memory of their rows isn't reused until the components are
+    /// dropped. Commands are applied after running a system and by
+    /// [`World::apply_deferred`], or the components can be dropped with
+    /// [`World::drop_despawned`].
+    ///
+    /// Disabling drops the components of entities that were already despawned.
+    pub fn set_graveyard(&mut self, enabled: bool) {
+        self.graveyard = enabled;
+
+        if !enabled {
+            self.drop_despawned();
+        }
+    }
+
+    /// Returns `true` if the components of despawned entities are dropped the
+    /// next time commands are applied.
+    ///
+    /// See [`World::set_graveyard`].
+    pub fn has_graveyard(&self) -> bool {
+        self.graveyard
+    }
+
+    /// Drops the components of entities despawned while
+    /// [graveyard](World::set_graveyard) mode is enabled.
+    pub fn drop_despawned(&mut self) {
+        for index in mem::take(&mut self.buried) {
+            // SAFETY: tables are never removed, so the index is valid
+            unsafe { self.components.get_unchecked_mut(index) }.drop_buried();
+        }
+    }
+
+    /// Takes the command errors stored by [`CommandErrorHandler::Collect`].
+    pub fn take_command_errors(&mut self) -> Vec<CommandError> {
+        mem::take(&mut self.command_errors)
+    }
+
+    #[track_caller]
+    pub(crate) fn handle_command_error(&mut self, error: CommandError) {
+        match self.command_error_handler {
+            CommandErrorHandler::Panic => panic!("{error}"),
+            CommandErrorHandler::Log => eprintln!("{error}"),
+            CommandErrorHandler::Collect => self.command_errors.push(error),
+        }
+    }
 }
 
 /// # Entity methods
 impl World {
     /// Returns the count of live entities in this world.
+    ///
+    /// Excludes [reserved](World::reserve_entity) entities until they are
+    /// spawned.
     pub fn len(&self) -> usize {
-        self.entities.len()
+        self.entities.spawned()
     }
 
     /// Returns `true` if this world contains no entities.
     pub fn is_empty(&self) -> bool {
-        self.entities.is_empty()
+        self.len() == 0
     }
 
     /// Returns `true` if this world contains this entity.
+    ///
+    /// Returns `false` for [reserved](World::reserve_entity) entities until
+    /// they are spawned.
     pub fn contains(&self, entity: EntityId) -> bool {
-        self.entities.contains(entity)
+        // only live entities have an address, as it is taken when freed
+        self.entities.get(entity).is_some()
     }
 
     /// Returns an iterator over the entities in this world.
     pub fn iter(&self) -> EntitiesIter<'_> {
-        EntitiesIter { inner: self.entities.iter() }
+        EntitiesIter {
+            inner: self.entities.iter(),
+            len: self.entities.spawned(),
+        }
+    }
+
+    /// Returns an iterator over references to the entities in this world.
+    ///
+    /// Unlike calling [`World::entity`] for each id of [`World::iter`],
+    /// entities aren't looked up again.
+    pub fn iter_entities(&self) -> EntityRefIter<'_> {
+        EntityRefIter { inner: self.iter(), world: self.as_ptr() }
+    }
+
+    /// Returns an iterator over mutable references to the entities in this
+    /// world.
+    ///
+    /// Each [`EntityMut`] can only access the components of its own entity,
+    /// so all of them can be held at once.
+    pub fn iter_entities_mut(&mut self) -> EntityMutIter<'_> {
+        let world = self.as_ptr_mut();
+        // SAFETY: the world is mutably borrowed for the lifetime of the
+        // iterator, which only accesses the entity allocator
+        let inner = unsafe { world.as_ref() }.iter();
+
+        EntityMutIter { inner, world }
     }
 
     /// Borrows an entity in this world.
@@ -102,6 +367,16 @@ impl World {
         EntityRef::new(entity, self)
     }
 
+    /// Returns an iterator over the info of the components of an entity.
+    ///
+    /// Returns an error if the entity doesn't exist in this world.
+    pub fn components_of(
+        &self,
+        entity: EntityId,
+    ) -> Result<impl Iterator<Item = ComponentInfo> + '_, EntityNotFound> {
+        self.entity(entity).map(EntityRef::components)
+    }
+
     /// Mutably borrows an entity and this world.
     ///
     /// Returns an error if the entity doesn't exist in this world.
@@ -112,33 +387,188 @@ impl World {
         EntityWorld::new(entity, self)
     }
 
-    /// Returns a query of data from this world.
+    /// Mutably borrows several distinct entities at once.
+    ///
+    /// Unlike [`World::entity_mut`], the returned references can't add or
+    /// remove components, as that could move the other entities.
+    ///
+    /// Returns an error if an entity doesn't exist in this world or is
+    /// requested more than once.
+    pub fn get_many_entities_mut<const N: usize>(
+        &mut self,
+        entities: [EntityId; N],
+    ) -> Result<[EntityMut<'_>; N], ManyEntitiesError> {
+        for (index, &entity) in entities.iter().enumerate() {
+            if !self.contains(entity) {
+                return Err(ManyEntitiesError::NotFound(entity));
+            }
+
+            if entities[..index].contains(&entity) {
+                return Err(ManyEntitiesError::Duplicate(entity));
+            }
+        }
+
+        let world = self.as_ptr_mut();
+
+        // SAFETY: the entities are alive and distinct, and the world is
+        // mutably borrowed for the lifetime of the references
+        Ok(entities.map(|entity| unsafe { world.entity(entity).as_mut() }))
+    }
+
+    /// Returns a checked handle to a component of an entity.
+    ///
+    /// Returns `None` if the entity doesn't exist or doesn't contain the
+    /// component.
+    pub fn component_ptr<C: Component>(
+        &self,
+        entity: EntityId,
+    ) -> Option<ComponentPtr<C>> {
+        let ptr = ComponentPtr::new(entity, self.entities.get(entity)?);
+
+        ptr.is_valid(self).then_some(ptr)
+    }
+
+    /// Returns a cached query of data from this world.
+    ///
+    /// Unlike [`Query`], the state can be stored and reused. It only checks
+    /// tables created since it was last used.
     ///
     /// Returns an error if the query access is invalid.
+    pub fn query_cached<D: QueryData>(
+        &self,
+    ) -> Result<QueryState<D>, AccessError> {
+        QueryState::new(self)
+    }
+
+    /// Returns a query of data from this world.
+    ///
+    /// Returns an error if the query access is invalid, or panics if
+    /// [enabled](World::set_panic_on_access_error).
     ///
     /// The query data must implement [`ReadOnlyQueryData`].
+    #[track_caller]
     pub fn query<D: ReadOnlyQueryData>(
         &self,
     ) -> Result<Query<'_, D>, AccessError> {
-        Query::from_ref(self)
+        let panic = self.panics_on_access_error();
+
+        Query::from_ref(self).inspect_err(|&error| {
+            if panic {
+                access_panic::<D>(error)
+            }
+        })
     }
 
     /// Returns a mutable query of data from this world.
     ///
-    /// Returns an error if the query access is invalid.
+    /// Returns an error if the query access is invalid, or panics if
+    /// [enabled](World::set_panic_on_access_error).
+    #[track_caller]
     pub fn query_mut<D: QueryData>(
         &mut self,
     ) -> Result<Query<'_, D>, AccessError> {
-        Query::from_mut(self)
+        let panic = self.panics_on_access_error();
+
+        Query::from_mut(self).inspect_err(|&error| {
+            if panic {
+                access_panic::<D>(error)
+            }
+        })
+    }
+
+    /// Returns a query of data from this world.
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query access is invalid.
+    #[track_caller]
+    pub fn query_expect<D: ReadOnlyQueryData>(&self) -> Query<'_, D> {
+        Query::from_ref(self).unwrap_or_else(|error| access_panic::<D>(error))
+    }
+
+    /// Returns a mutable query of data from this world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query access is invalid.
+    #[track_caller]
+    pub fn query_mut_expect<D: QueryData>(&mut self) -> Query<'_, D> {
+        Query::from_mut(self).unwrap_or_else(|error| access_panic::<D>(error))
     }
 
     /// Spawns a new entity with its components.
     ///
     /// Returns an [`EntityWorld`] to allow editing of the produced entity.
+    #[track_caller]
     pub fn spawn(&mut self, bundle: impl Bundle) -> EntityWorld<'_> {
         let entity = self.entities.alloc();
 
-        unsafe { self.spawn_at(entity, bundle) }
+        unsafe { self.spawn_at(entity, bundle, Location::caller()) }
+    }
+
+    /// Reserves the id of an entity without spawning it.
+    ///
+    /// Takes a shared reference, so ids can be handed out from parallel systems
+    /// and jobs that write the components of the entity later, with
+    /// [`World::spawn_reserved`] or
+    /// [`WorldQueue::spawn_reserved`](crate::commands::WorldQueue::spawn_reserved).
+    /// Until then, the entity isn't counted by [`World::len`] or
+    /// [`World::contains`] and can't be accessed or despawned. Its id stays
+    /// allocated, so every reserved entity should be spawned.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// #[derive(Component)]
+    /// struct Chunk(u32);
+    ///
+    /// let mut world = World::new();
+    /// let chunks: Vec<_> = world.reserve_entities(4).collect();
+    /// let extra = world.reserve_entity();
+    ///
+    /// for (i, &chunk) in chunks.iter().enumerate() {
+    ///     world.spawn_reserved(chunk, Chunk(i as u32)).unwrap();
+    /// }
+    ///
+    /// world.spawn_reserved(extra, ()).unwrap();
+    ///
+    /// assert_eq!(world.entity(chunks[2]).unwrap().get::<Chunk>().unwrap().0, 2);
+    /// assert_eq!(world.len(), 5);
+    /// ```
+    pub fn reserve_entity(&self) -> EntityId {
+        self.entities.reserve()
+    }
+
+    /// Reserves the ids of many entities without spawning them.
+    ///
+    /// See [`World::reserve_entity`].
+    pub fn reserve_entities(
+        &self,
+        count: u32,
+    ) -> impl ExactSizeIterator<Item = EntityId> + '_ {
+        self.entities.reserve_many(count)
+    }
+
+    /// Spawns an entity reserved with [`World::reserve_entity`] with its
+    /// components.
+    ///
+    /// Returns an error if the entity wasn't reserved or was already spawned.
+    #[track_caller]
+    pub fn spawn_reserved(
+        &mut self,
+        entity: EntityId,
+        bundle: impl Bundle,
+    ) -> Result<EntityWorld<'_>, EntityNotFound> {
+        // reused ids are only marked alive once flushed
+        self.entities.flush();
+
+        if !self.entities.is_unspawned(entity) {
+            return Err(EntityNotFound(entity));
+        }
+
+        // SAFETY: the entity is allocated but wasn't spawned
+        Ok(unsafe { self.spawn_at(entity, bundle, Location::caller()) })
     }
 
     #[inline]
@@ -146,17 +576,19 @@ impl World {
         &mut self,
         entity: EntityId,
         bundle: impl Bundle,
+        caller: &'static Location<'static>,
     ) -> EntityWorld<'_> {
-        #[track_caller]
         #[inline(always)]
-        unsafe fn spawn_at_inner<B: Bundle>(
-            world: &mut World,
+        unsafe fn spawn_at_inner<'w, B: Bundle>(
+            world: &'w mut World,
             entity: EntityId,
             bundle: B,
-        ) -> EntityWorld<'_> {
-            {
+            caller: &'static Location<'static>,
+        ) -> EntityWorld<'w> {
+            let addr = {
                 let queue = EntityQueue::new(entity, &mut world.commands);
                 let addr = world.components.alloc::<B>(1);
+                let section = CriticalSection::new("spawning an entity");
 
                 world.entities.set(entity, addr);
                 // SAFETY: the index is valid as it was just allocated and the
@@ -165,46 +597,67 @@ impl World {
                 unsafe {
                     world.components.get_unchecked_mut(addr.table).push(entity)
                 };
-                bundle.write(&mut ComponentWriter::new(
+                let mut writer = ComponentWriter::new(
                     queue,
                     &mut world.components,
                     addr,
-                ));
-            }
+                    ComponentSet::new(),
+                    caller,
+                );
+
+                bundle.write(&mut writer);
+                writer.write_required::<B>();
+                section.end();
+
+                addr
+            };
 
+            // SAFETY: the table was allocated above
+            unsafe {
+                world.listeners.archetype_changed(
+                    &world.components,
+                    entity,
+                    None,
+                    Some(addr.table),
+                );
+            }
             world.flush();
 
             // SAFETY: the entity was allocated above, so it must exist
             unsafe { EntityWorld::new_unchecked(entity, world) }
         }
 
-        unsafe { spawn_at_inner(self, entity, bundle) }
+        unsafe { spawn_at_inner(self, entity, bundle, caller) }
     }
 
     /// Spawns an entity for each bundle in an iterator.
     ///
     /// More efficient than calling [`World::spawn`] on each bundle.
+    #[track_caller]
     pub fn spawn_iter(
         &mut self,
         bundles: impl IntoIterator<Item: Bundle>,
     ) -> SpawnIter<'_> {
-        #[track_caller]
+        self.spawn_iter_with_caller(bundles, Location::caller())
+    }
+
+    pub(crate) fn spawn_iter_with_caller(
+        &mut self,
+        bundles: impl IntoIterator<Item: Bundle>,
+        caller: &'static Location<'static>,
+    ) -> SpawnIter<'_> {
         #[inline(always)]
-        fn spawn_iter_inner<B: Bundle>(
-            world: &mut World,
+        fn spawn_iter_inner<'w, B: Bundle>(
+            world: &'w mut World,
             bundles: impl IntoIterator<Item = B>,
-        ) -> SpawnIter<'_> {
-            world.entities.flush();
-
+            caller: &'static Location<'static>,
+        ) -> SpawnIter<'w> {
             let bundles = bundles.into_iter();
-
-            let (lower, upper) = bundles.size_hint();
-            let count = upper.unwrap_or(lower);
-
-            let first_index = world.entities.len();
-            // allocates enough space to hold the last entity
-            let addr = world.components.alloc::<B>((first_index + count) as _);
+            let (count, _) = bundles.size_hint();
+            // new entities are allocated at the end, so their indices are
+            // contiguous
             let mut allocated = world.entities.alloc_many(count);
+            let start = allocated.start;
 
             for bundle in bundles {
                 let entity = allocated
@@ -212,44 +665,413 @@ impl World {
                     .map(|index| index as _)
                     .map(EntityId::from_index)
                     .unwrap_or_else(|| world.entities.alloc_end());
+                let addr = world.components.alloc::<B>(count);
+                let section = CriticalSection::new("spawning an entity");
 
                 world.entities.set(entity, addr);
-                bundle.write(&mut ComponentWriter::new(
+                // SAFETY: the entity was only allocated above
+                unsafe {
+                    world
+                        .components
+                        .get_unchecked_mut(addr.table)
+                        .push_new(entity)
+                };
+
+                let mut writer = ComponentWriter::new(
                     EntityQueue::new(entity, &mut world.commands),
                     &mut world.components,
                     addr,
-                ));
+                    ComponentSet::new(),
+                    caller,
+                );
+
+                bundle.write(&mut writer);
+                writer.write_required::<B>();
+                section.end();
+                // SAFETY: the table was allocated above
+                unsafe {
+                    world.listeners.archetype_changed(
+                        &world.components,
+                        entity,
+                        None,
+                        Some(addr.table),
+                    );
+                }
             }
 
             world.flush();
 
-            SpawnIter { inner: world.entities.iter_slice(first_index..) }
+            SpawnIter {
+                inner: start as u32..world.entities.slots_len() as u32,
+                _world: PhantomData,
+            }
+        }
+
+        spawn_iter_inner(self, bundles, caller)
+    }
+
+    /// Inserts a bundle into each entity in an iterator.
+    ///
+    /// See [`EntityWorld::insert_bundle`]. Entities that don't exist are
+    /// skipped, and the first of them is returned as an error.
+    #[track_caller]
+    pub fn insert_iter<B: Bundle>(
+        &mut self,
+        entities: impl IntoIterator<Item = (EntityId, B)>,
+    ) -> Result<(), EntityNotFound> {
+        self.insert_iter_with_caller(entities, Location::caller())
+    }
+
+    pub(crate) fn insert_iter_with_caller<B: Bundle>(
+        &mut self,
+        entities: impl IntoIterator<Item = (EntityId, B)>,
+        caller: &'static Location<'static>,
+    ) -> Result<(), EntityNotFound> {
+        let mut result = Ok(());
+
+        for (entity, bundle) in entities {
+            match self.entity_mut(entity) {
+                Ok(entity) => entity.with_caller(caller).insert_bundle(bundle),
+                Err(error) => result = result.and(Err(error)),
+            }
         }
 
-        spawn_iter_inner(self, bundles)
+        result
     }
 
     /// Despawns an entity.
     ///
     /// Returns an error if the entity doesn't exist in the world.
+    #[track_caller]
     pub fn despawn(&mut self, entity: EntityId) -> Result<(), EntityNotFound> {
-        self.entity_mut(entity).map(EntityWorld::despawn)
+        let caller = Location::caller();
+
+        self.entity_mut(entity)
+            .map(|entity| entity.with_caller(caller).despawn())
+    }
+
+    /// Despawns the entities in an iterator.
+    ///
+    /// The entities are grouped by table and despawned a table at a time,
+    /// which is more efficient than despawning them individually. Entities
+    /// that don't exist are skipped. See also [`World::set_graveyard`].
+    ///
+    /// Returns the amount of despawned entities.
+    #[track_caller]
+    pub fn despawn_batch(
+        &mut self,
+        entities: impl IntoIterator<Item = EntityId>,
+    ) -> usize {
+        let caller = Location::caller();
+        let mut addrs: Vec<_> = entities
+            .into_iter()
+            .filter_map(|entity| {
+                self.entities.get(entity).map(|addr| (addr, entity))
+            })
+            .collect();
+
+        addrs.sort_unstable_by_key(|&(addr, _)| (addr.table, addr.row.0));
+        addrs.dedup_by_key(|&mut (_, entity)| entity);
+
+        for group in addrs.chunk_by(|(lhs, _), (rhs, _)| lhs.table == rhs.table)
+        {
+            let index = group[0].0.table;
+            let rows: Vec<_> = group
+                .iter()
+                .map(|&(addr, entity)| (addr.row, entity))
+                .collect();
+
+            // SAFETY: each entity is located at its address
+            unsafe { self.despawn_rows(index, &rows, caller) };
+        }
+
+        addrs.len()
     }
 
     /// Despawns all entities.
     pub fn despawn_all(&mut self) {
+        if !self.listeners.is_empty() {
+            let rows: Vec<_> = self
+                .components
+                .tables()
+                .flat_map(|(index, table)| {
+                    table.rows().map(move |(_, entity)| (index, entity))
+                })
+                .collect();
+
+            for (index, entity) in rows {
+                // SAFETY: the entity is contained in the table, so it is alive
+                unsafe {
+                    self.notify_despawn(entity, DespawnReason::Clear);
+                    self.listeners.archetype_changed(
+                        &self.components,
+                        entity,
+                        Some(index),
+                        None,
+                    );
+                }
+            }
+        }
+
         self.entities.clear();
         self.components.clear();
+        self.buried.clear();
+        self.names.clear();
+        self.relations.clear();
+    }
+
+    /// Despawns all entities matched by the query data.
+    ///
+    /// Usually used with filters, like `world.despawn_where::<With<A>>()`.
+    /// Entities are despawned a table at a time, which is more efficient than
+    /// despawning them individually.
+    ///
+    /// Returns the amount of despawned entities, or an error if the query
+    /// access is invalid.
+    #[track_caller]
+    pub fn despawn_where<F: QueryData>(
+        &mut self,
+    ) -> Result<usize, AccessError> {
+        let caller = Location::caller();
+        let mut access = WorldAccess::new();
+
+        F::world_access(&mut access);
+        access.result()?;
+
+        let tables: Vec<_> = self
+            .components
+            .tables()
+            .filter(|(_, table)| access.matches(table.components()))
+            .map(|(index, _)| index)
+            .collect();
+        let mut count = 0;
+
+        for index in tables {
+            // SAFETY: the table index was just retrieved from storage
+            let rows: Vec<_> = unsafe { self.components.get_unchecked(index) }
+                .rows()
+                .collect();
+
+            count += rows.len();
+            // SAFETY: the rows were just retrieved from the table
+            unsafe { self.despawn_rows(index, &rows, caller) };
+        }
+
+        Ok(count)
+    }
+
+    /// Despawns all entities for which the predicate returns `false`.
+    ///
+    /// The predicate is called for every entity before any are despawned, so
+    /// it always observes the world as it was before the call. Entities are
+    /// then despawned a table at a time.
+    ///
+    /// Returns the amount of despawned entities.
+    #[track_caller]
+    pub fn retain_entities(
+        &mut self,
+        mut f: impl FnMut(EntityRef<'_>) -> bool,
+    ) -> usize {
+        let caller = Location::caller();
+        let world = self.as_ptr();
+        let despawned: Vec<(TableId, Vec<_>)> = self
+            .components
+            .tables()
+            .map(|(index, table)| {
+                let rows = table
+                    .rows()
+                    .filter(|&(row, entity)| {
+                        let addr = EntityAddr { table: index, row };
+
+                        // SAFETY: the entity is located at the address and the
+                        // world is borrowed for reads
+                        !f(unsafe {
+                            EntityRef::from_addr(world.entity(entity), addr)
+                        })
+                    })
+                    .collect();
+
+                (index, rows)
+            })
+            .filter(|(_, rows): &(_, Vec<_>)| !rows.is_empty())
+            .collect();
+        let mut count = 0;
+
+        for (index, rows) in despawned {
+            count += rows.len();
+            // SAFETY: the rows were retrieved from the table above and the
+            // predicate couldn't modify the world
+            unsafe { self.despawn_rows(index, &rows, caller) };
+        }
+
+        count
+    }
+
+    /// Runs the removal hooks of the entities at the rows of a table, then
+    /// despawns them.
+    ///
+    /// # Safety
+    ///
+    /// The table must exist and contain each entity at its row.
+    unsafe fn despawn_rows(
+        &mut self,
+        index: TableId,
+        rows: &[(TableRow, EntityId)],
+        caller: &'static Location<'static>,
+    ) {
+        // SAFETY: the caller ensures that the table exists
+        let components = unsafe { self.components.get_unchecked(index) }
+            .components()
+            .clone();
+
+        for &(_, entity) in rows {
+            for component in &components {
+                let hook = component.before_remove();
+                let ctx =
+                    HookContext::new(entity, component.id(), false, caller);
+
+                // SAFETY: the entity is contained in the table, so it is alive
+                hook(unsafe { EntityMut::new_unchecked(entity, self) }, ctx);
+            }
+
+            // SAFETY: same as above
+            unsafe { self.notify_despawn(entity, DespawnReason::Batch) };
+            self.unrelate_target(entity);
+        }
+
+        for &(row, entity) in rows {
+            _ = self.entities.free(entity);
+            // SAFETY: the table contains the entity at the row
+            unsafe {
+                self.free_row(index, row);
+                self.listeners.archetype_changed(
+                    &self.components,
+                    entity,
+                    Some(index),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Drops the components of a despawned entity, or buries them if
+    /// [graveyard](World::set_graveyard) mode is enabled.
+    ///
+    /// # Safety
+    ///
+    /// The table must exist and contain the entity at the row.
+    pub(crate) unsafe fn free_row(&mut self, index: TableId, row: TableRow) {
+        // SAFETY: the caller ensures that the table exists
+        let table = unsafe { self.components.get_unchecked_mut(index) };
+
+        if self.graveyard {
+            if table.buried() == 0 {
+                self.buried.push(index);
+            }
+
+            // SAFETY: the caller ensures that the table contains the entity
+            unsafe { table.bury(row) };
+        } else {
+            // SAFETY: see above
+            unsafe { table.free(row) };
+        }
+    }
+
+    /// Compacts fragmented tables until the time budget runs out.
+    ///
+    /// Removing entities from a table leaves holes in its storage. This moves
+    /// entities into those holes, starting with the most fragmented tables, so
+    /// that storage converges back to being dense. Intended to be called with
+    /// the spare time of a frame.
+    ///
+    /// Returns the amount of moved entities.
+    pub fn maintain(&mut self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut tables: Vec<_> = self
+            .components
+            .tables()
+            .filter(|(_, table)| table.holes() > 0)
+            .map(|(index, table)| (index, table.holes()))
+            .collect();
+        let mut moved = 0;
+
+        tables.sort_by_key(|&(_, holes)| Reverse(holes));
+
+        for (index, _) in tables {
+            // SAFETY: the table index was just retrieved from storage
+            let table = unsafe { self.components.get_unchecked_mut(index) };
+
+            while start.elapsed() < budget {
+                let Some((entity, row)) = table.compact_one() else {
+                    break;
+                };
+
+                self.entities.set(entity, EntityAddr { table: index, row });
+                moved += 1;
+            }
+        }
+
+        moved
+    }
+
+    /// Compacts all tables and shrinks their storage to fit.
+    ///
+    /// Unlike [`World::maintain`], this isn't bounded by time, and the freed
+    /// memory is returned to the allocator. Intended for loading screens, as
+    /// storage otherwise only ever grows. Components of despawned entities in
+    /// the [graveyard](World::set_graveyard) are dropped.
+    pub fn compact(&mut self) -> CompactStats {
+        let mut stats = CompactStats::default();
+        let tables: Vec<_> =
+            self.components.tables().map(|(index, _)| index).collect();
+
+        self.drop_despawned();
+
+        for index in tables {
+            // SAFETY: the table index was just retrieved from storage
+            let table = unsafe { self.components.get_unchecked_mut(index) };
+
+            while let Some((entity, row)) = table.compact_one() {
+                self.entities.set(entity, EntityAddr { table: index, row });
+                stats.moved += 1;
+            }
+
+            stats.reclaimed += table.shrink_to_fit();
+        }
+
+        stats
+    }
+
+    /// Applies the commands queued through [`EntityCommands`] query items.
+    ///
+    /// Called after running a system with [`World::run_system`].
+    pub fn apply_deferred(&mut self) {
+        let deferred =
+            self.deferred.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        self.commands.append(deferred);
+        self.flush();
     }
 
     /// Ensures all entities are allocated and applies all buffered commands.
     pub(crate) fn flush(&mut self) {
         self.entities.flush();
 
-        let mut commands = mem::replace(&mut self.commands, Commands::new());
+        if !self.buried.is_empty() {
+            self.drop_despawned();
+        }
+
+        if self.commands.is_empty() {
+            return;
+        }
+
+        // commands queued while applying go to the spare buffer and are
+        // flushed by nested calls
+        let spare = mem::take(&mut self.spare_commands);
+        let mut commands = mem::replace(&mut self.commands, spare);
 
         commands.apply(self);
-        self.commands = commands;
+        self.spare_commands = mem::replace(&mut self.commands, commands);
     }
 }
 
@@ -282,22 +1104,254 @@ impl World {
     /// Returns the previous value if it exists.
     #[doc(alias = "insert_resource")]
     pub fn create<R: Resource>(&mut self, resource: R) -> Option<R> {
+        #[cfg(feature = "audit")]
+        self.audit(AuditOp::CreateResource(R::id()));
+
         self.resources.insert(resource)
     }
 
+    /// Inserts a resource under an interface, such as a trait object.
+    ///
+    /// The resource is stored as a `Box<T>`, which is borrowed with
+    /// `Res<Box<T>>`. This lets code depend on an interface that is provided
+    /// by another part of the program:
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// trait Renderer: Send + Sync {
+    ///     fn name(&self) -> &'static str;
+    /// }
+    ///
+    /// struct Vulkan;
+    ///
+    /// impl Renderer for Vulkan {
+    ///     fn name(&self) -> &'static str {
+    ///         "vulkan"
+    ///     }
+    /// }
+    ///
+    /// fn render(renderer: Res<Box<dyn Renderer>>) -> &'static str {
+    ///     renderer.name()
+    /// }
+    ///
+    /// let mut world = World::new();
+    ///
+    /// world.create_as::<dyn Renderer>(Box::new(Vulkan));
+    ///
+    /// assert_eq!(world.run_system(render).unwrap(), "vulkan");
+    /// ```
+    ///
+    /// Returns the previous value if it exists.
+    pub fn create_as<T: ?Sized + MaybeSendSync + 'static>(
+        &mut self,
+        resource: Box<T>,
+    ) -> Option<Box<T>> {
+        self.create(resource)
+    }
+
+    /// Inserts a resource created with [`FromWorld`] if the world doesn't
+    /// already contain it.
+    ///
+    /// Returns `true` if the resource was inserted.
+    pub fn init_resource<R: Resource + FromWorld>(&mut self) -> bool {
+        if self.has::<R>() {
+            return false;
+        }
+
+        let resource = R::from_world(self);
+
+        self.create(resource);
+
+        true
+    }
+
     /// Removes a resource from the world.
     ///
     /// Returns an error if the resource doesn't exist.
     #[doc(alias = "remove_resource")]
     pub fn destroy<R: Resource>(&mut self) -> Result<R, ResourceError> {
-        self.resources.remove()
+        let resource = self.resources.remove();
+
+        #[cfg(feature = "audit")]
+        if resource.is_ok() {
+            self.audit(AuditOp::DestroyResource(R::id()));
+        }
+
+        resource
+    }
+
+    /// Temporarily removes a resource to access it alongside the world.
+    ///
+    /// The resource is reinserted after the function returns, replacing any
+    /// value of `R` inserted by the function. If the function panics, the
+    /// resource is lost.
+    ///
+    /// Returns an error if the resource doesn't exist.
+    pub fn resource_scope<R: Resource, T>(
+        &mut self,
+        f: impl FnOnce(&mut World, &mut R) -> T,
+    ) -> Result<T, ResourceError> {
+        let mut resource = self.destroy::<R>()?;
+        let output = f(self, &mut resource);
+
+        self.create(resource);
+
+        Ok(output)
     }
 
     /// Removes all resources from the world.
     #[doc(alias = "remove_all_resources")]
     pub fn destroy_all(&mut self) {
+        #[cfg(feature = "audit")]
+        self.destroy_where(|_| true);
+
         self.resources.clear();
     }
+
+    /// Removes all resources for which the predicate returns `true`.
+    ///
+    /// Returns the amount of removed resources.
+    pub fn destroy_where(
+        &mut self,
+        mut f: impl FnMut(ResourceInfo) -> bool,
+    ) -> usize {
+        #[cfg(feature = "audit")]
+        let mut destroyed = Vec::new();
+        let removed = self.resources.retain(|info| {
+            let destroy = f(info);
+
+            #[cfg(feature = "audit")]
+            if destroy {
+                destroyed.push(info.id());
+            }
+
+            !destroy
+        });
+
+        #[cfg(feature = "audit")]
+        for resource in destroyed {
+            self.audit(AuditOp::DestroyResource(resource));
+        }
+
+        removed
+    }
+
+    /// Runs a system once on the world and applies its deferred work.
+    ///
+    /// The state of the system is discarded. To run a system repeatedly, see
+    /// [`World::register_system`].
+    ///
+    /// Returns an error if the system access is invalid, a required resource
+    /// doesn't exist or an input is unavailable, in which case the system is
+    /// skipped.
+    pub fn run_system<I, O>(
+        &mut self,
+        system: impl IntoSystem<I, O>,
+    ) -> Result<O, RunSystemError> {
+        self.run_system_in(&mut system.into_system())
+    }
+
+    /// Registers a system to be run with [`World::run_system_by_id`].
+    ///
+    /// The system is initialized when first run, and its state is retained
+    /// between runs.
+    pub fn register_system<I, O: 'static>(
+        &mut self,
+        system: impl IntoSystem<I, O, Output: MaybeSendSync + 'static>,
+    ) -> SystemId<O> {
+        self.systems.register(system.into_system())
+    }
+
+    /// Removes a system registered with [`World::register_system`].
+    ///
+    /// Returns `false` if the system doesn't exist.
+    pub fn unregister_system<O>(&mut self, id: SystemId<O>) -> bool {
+        self.systems.unregister(id)
+    }
+
+    /// Runs a system registered with [`World::register_system`] and applies
+    /// its deferred work.
+    ///
+    /// Returns an error if the system doesn't exist or is already running, if
+    /// its access is invalid, if a required resource doesn't exist or if an
+    /// input is unavailable.
+    pub fn run_system_by_id<O: 'static>(
+        &mut self,
+        id: SystemId<O>,
+    ) -> Result<O, RunSystemError> {
+        let mut system =
+            self.systems.take(id).ok_or(RunSystemError::NotFound)?;
+        let output = self.run_system_in(&mut *system);
+
+        self.systems.restore(id, system);
+
+        output
+    }
+
+    fn run_system_in<O>(
+        &mut self,
+        system: &mut (impl System<Output = O> + ?Sized),
+    ) -> Result<O, RunSystemError> {
+        system.init_if_needed(self);
+
+        // SAFETY: the system was initialized above
+        let access = unsafe { system.world_access() };
+
+        access.result()?;
+
+        // resources are only created if all others exist, as they may be
+        // created from them
+        if let Some(info) = access.required_resources().find(|info| {
+            !self.resources.contains_id(info.id())
+                && access.resource_inits().all(|(init, _)| init != *info)
+        }) {
+            return Err(RunSystemError::ResourceNotFound {
+                resource: info.type_name(),
+                system: system.name(),
+            });
+        }
+
+        for (info, init) in access.resource_inits() {
+            if !self.resources.contains_id(info.id()) {
+                init(self);
+            }
+        }
+
+        // SAFETY: the system was initialized above
+        unsafe { system.validate(self) }.map_err(|reason| {
+            RunSystemError::Skipped { system: system.name(), reason }
+        })?;
+
+        #[cfg(feature = "audit")]
+        let previous = self.audit_system(Some(system.name()));
+        // SAFETY: the system is initialized, its access is valid, all required
+        // resources exist and its inputs are available. the world pointer is
+        // valid for all access
+        let output = unsafe { system.run(self.as_ptr_mut()) };
+
+        // SAFETY: the system is initialized
+        unsafe { system.sync_if_needed(self) };
+        self.apply_deferred();
+
+        #[cfg(feature = "audit")]
+        self.audit_system(previous);
+
+        Ok(output)
+    }
+}
+
+#[cold]
+#[track_caller]
+fn access_panic<D: QueryData>(error: AccessError) -> ! {
+    panic!("invalid access in query of `{}`: {error}", type_name::<D>())
+}
+
+impl WorldId {
+    fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
 }
 
 impl Default for World {
@@ -306,25 +1360,77 @@ impl Default for World {
     }
 }
 
+impl<'w> EntitiesIter<'w> {
+    /// Returns the next live entity and its address.
+    ///
+    /// Skips entities without an address, which were reserved or allocated
+    /// but weren't spawned.
+    fn next_addr(&mut self) -> Option<(EntityId, EntityAddr)> {
+        let (entity, addr) = self
+            .inner
+            .find_map(|(entity, addr)| addr.map(|addr| (entity, addr)))?;
+
+        self.len -= 1;
+
+        Some((entity, addr))
+    }
+}
+
 impl Iterator for EntitiesIter<'_> {
     type Item = EntityId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(entity, _)| entity)
+        self.next_addr().map(|(entity, _)| entity)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.inner.size_hint()
+        (self.len, Some(self.len))
     }
 }
 
 impl ExactSizeIterator for EntitiesIter<'_> {}
 
+impl<'w> Iterator for EntityRefIter<'w> {
+    type Item = EntityRef<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_addr().map(|(entity, addr)| {
+            // SAFETY: the entity is alive at the address and the world is
+            // borrowed for `'w`
+            unsafe { EntityRef::from_addr(self.world.entity(entity), addr) }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EntityRefIter<'_> {}
+
+impl<'w> Iterator for EntityMutIter<'w> {
+    type Item = EntityMut<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_addr().map(|(entity, addr)| {
+            // SAFETY: the entity is alive at the address, the world is mutably
+            // borrowed for `'w` and each entity is only yielded once
+            unsafe { EntityMut::from_addr(self.world.entity(entity), addr) }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EntityMutIter<'_> {}
+
 impl Iterator for SpawnIter<'_> {
     type Item = EntityId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(entity, _)| entity)
+        self.inner.next().map(EntityId::from_index)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use super::World;
+use crate::component::Bundle;
+use crate::entity::{EntityId, EntityWorld};
+use crate::marker::MaybeSendSync;
+use crate::resource::Resource;
+
+/// A resource of bundles that can be spawned by a key.
+///
+/// Spawned with [`World::spawn_prefab`] or
+/// [`WorldQueue::spawn_prefab`](crate::commands::WorldQueue::spawn_prefab).
+/// Components inserted into the spawned entity afterwards override those of
+/// the prefab.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// #[derive(Component)]
+/// struct Name(&'static str);
+///
+/// let mut prefabs = Prefabs::new();
+///
+/// prefabs.register("goblin", || (Name("goblin"), Health(5)));
+///
+/// let mut world = World::new();
+///
+/// world.create(prefabs);
+///
+/// let mut goblin = world.spawn_prefab("goblin").unwrap();
+///
+/// goblin.insert(Health(8));
+///
+/// assert_eq!(goblin.get::<Health>().unwrap().0, 8);
+/// assert_eq!(goblin.get::<Name>().unwrap().0, "goblin");
+/// ```
+#[derive(Resource, Default)]
+pub struct Prefabs {
+    prefabs: HashMap<String, Arc<dyn Prefab>>,
+}
+
+/// Error when spawning a prefab that isn't registered in [`Prefabs`].
+#[derive(Debug, Clone, Error)]
+#[error("prefab not found: {0}")]
+pub struct PrefabNotFound(pub String);
+
+/// A type-erased function that produces a bundle.
+trait Prefab: MaybeSendSync + 'static {
+    /// Spawns the bundle at an entity.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be allocated and not be spawned.
+    unsafe fn spawn_at<'w>(
+        &self,
+        world: &'w mut World,
+        entity: EntityId,
+        caller: &'static Location<'static>,
+    ) -> EntityWorld<'w>;
+}
+
+/// A [`Prefab`] for a function that produces a bundle.
+struct BundleFn<F, B> {
+    f: F,
+    _marker: PhantomData<fn() -> B>,
+}
+
+impl World {
+    /// Spawns the prefab registered with the key in the [`Prefabs`] of this
+    /// world.
+    ///
+    /// Returns an error if the world doesn't contain [`Prefabs`] or the prefab
+    /// isn't registered.
+    #[track_caller]
+    pub fn spawn_prefab(
+        &mut self,
+        key: &str,
+    ) -> Result<EntityWorld<'_>, PrefabNotFound> {
+        let prefab = self.prefab(key)?;
+        let entity = self.entities.alloc();
+
+        // SAFETY: the entity was just allocated
+        Ok(unsafe { prefab.spawn_at(self, entity, Location::caller()) })
+    }
+
+    /// Spawns a prefab at an entity reserved by a command.
+    ///
+    /// If the prefab isn't found, the entity is spawned empty and then
+    /// despawned, so that the reservation is released.
+    ///
+    /// # Safety
+    ///
+    /// The entity must be allocated and not be spawned.
+    pub(crate) unsafe fn spawn_prefab_at(
+        &mut self,
+        entity: EntityId,
+        key: &str,
+        caller: &'static Location<'static>,
+    ) -> Result<EntityWorld<'_>, PrefabNotFound> {
+        match self.prefab(key) {
+            // SAFETY: the caller ensures that the entity is allocated
+            Ok(prefab) => Ok(unsafe { prefab.spawn_at(self, entity, caller) }),
+            Err(error) => {
+                // SAFETY: see above
+                unsafe { self.spawn_at(entity, (), caller) }.despawn();
+
+                Err(error)
+            },
+        }
+    }
+
+    fn prefab(&self, key: &str) -> Result<Arc<dyn Prefab>, PrefabNotFound> {
+        self.resource::<Prefabs>()
+            .ok()
+            .and_then(|prefabs| prefabs.prefabs.get(key).cloned())
+            .ok_or_else(|| PrefabNotFound(key.to_owned()))
+    }
+}
+
+impl Prefabs {
+    /// Creates an empty prefab registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of registered prefabs.
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+
+    /// Returns `true` if no prefabs are registered.
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+
+    /// Registers a function that produces the bundle of a prefab.
+    ///
+    /// The function is called each time the prefab is spawned. Returns `true`
+    /// if an existing prefab with the key was replaced.
+    pub fn register<B: Bundle>(
+        &mut self,
+        key: impl Into<String>,
+        f: impl Fn() -> B + MaybeSendSync + 'static,
+    ) -> bool {
+        let prefab = Arc::new(BundleFn { f, _marker: PhantomData });
+
+        self.prefabs.insert(key.into(), prefab).is_some()
+    }
+
+    /// Returns `true` if a prefab is registered with the key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.prefabs.contains_key(key)
+    }
+
+    /// Unregisters a prefab, returning `true` if it was registered.
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.prefabs.remove(key).is_some()
+    }
+
+    /// Returns an iterator over the keys of the registered prefabs.
+    pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        self.prefabs.keys().map(String::as_str)
+    }
+}
+
+impl<F, B> Prefab for BundleFn<F, B>
+where
+    F: Fn() -> B + MaybeSendSync + 'static,
+    B: Bundle,
+{
+    unsafe fn spawn_at<'w>(
+        &self,
+        world: &'w mut World,
+        entity: EntityId,
+        caller: &'static Location<'static>,
+    ) -> EntityWorld<'w> {
+        // SAFETY: the caller ensures that the entity is allocated
+        unsafe { world.spawn_at(entity, (self.f)(), caller) }
+    }
+}
+
+impl fmt::Debug for Prefabs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Goblin;
+
+    #[test]
+    fn spawn_prefab_with_commands() {
+        fn spawn(mut queue: WorldQueue) {
+            queue.spawn_prefab("goblin").insert(Health(8));
+            queue.spawn_prefab("goblin");
+        }
+
+        fn spawn_missing(mut queue: WorldQueue) {
+            queue.spawn_prefab("orc");
+        }
+
+        let mut world = World::new();
+        let mut prefabs = Prefabs::new();
+
+        assert!(!prefabs.register("goblin", || Goblin));
+        assert!(prefabs.register("goblin", || (Goblin, Health(5))));
+
+        world.create(prefabs);
+        world.run_system(spawn).unwrap();
+
+        let mut healths: Vec<_> = world
+            .query::<(&Health, With<Goblin>)>()
+            .unwrap()
+            .iter()
+            .map(|(health, ())| health.0)
+            .collect();
+
+        healths.sort();
+
+        assert_eq!(healths, [5, 8]);
+
+        world.set_command_error_handler(CommandErrorHandler::Collect);
+        world.run_system(spawn_missing).unwrap();
+
+        let errors = world.take_command_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].downcast_ref::<PrefabNotFound>().is_some());
+        assert_eq!(world.len(), 2);
+        assert!(world.spawn_prefab("orc").is_err());
+    }
+}
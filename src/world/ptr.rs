@@ -42,6 +42,11 @@ impl<'w> WorldPtr<'w> {
         unsafe { &mut *self.world }
     }
 
+    /// Returns the raw pointer to the world.
+    pub(crate) const fn as_raw(self) -> *mut World {
+        self.world
+    }
+
     /// Returns an entity pointer for the given id.
     pub fn entity(self, entity: EntityId) -> EntityPtr<'w> {
         EntityPtr::new(entity, self)
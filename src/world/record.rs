@@ -0,0 +1,304 @@
+use std::panic::Location;
+
+use super::World;
+use crate::component::{
+    Component,
+    ComponentId,
+    ComponentInfo,
+    ComponentNotFound,
+    ComponentVTable,
+    Components,
+    HookContext,
+};
+use crate::entity::{EntityAddr, EntityId, EntityMut, EntityNotFound};
+use crate::storage::{Table, TableRow};
+
+/// The components of an entity that was moved out of a world with
+/// [`World::take_entity`].
+///
+/// Inserted into a world with [`World::insert_entity_record`], which may be a
+/// different world, such as when handing off entities from a loading world to
+/// the live world. Components that aren't inserted are dropped with the
+/// record.
+///
+/// ```
+/// # use worldlines::prelude::*;
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// let mut loading = World::new();
+/// let mut live = World::new();
+///
+/// let entity = loading.spawn(Health(5)).id();
+/// let record = loading.take_entity(entity).unwrap();
+///
+/// assert!(!loading.contains(entity));
+/// assert_eq!(record.get::<Health>().unwrap().0, 5);
+///
+/// let entity = live.insert_entity_record(record);
+///
+/// assert_eq!(live.entity(entity).unwrap().get::<Health>().unwrap().0, 5);
+/// ```
+#[derive(Debug)]
+pub struct EntityRecord {
+    id: EntityId,
+    /// A table containing only the entity, at [`EntityRecord::ROW`].
+    table: Table,
+}
+
+impl World {
+    /// Moves an entity and its components out of this world.
+    ///
+    /// The [`Component::before_remove`] hooks of its components are run and
+    /// listeners are notified with [`DespawnReason::Taken`], as when the entity
+    /// is despawned, but the components are moved into the returned record
+    /// instead of being dropped.
+    ///
+    /// Returns an error if the entity doesn't exist in this world.
+    ///
+    /// [`Component::before_remove`]: crate::component::Component::before_remove
+    /// [`DespawnReason::Taken`]: super::DespawnReason::Taken
+    #[track_caller]
+    pub fn take_entity(
+        &mut self,
+        entity: EntityId,
+    ) -> Result<EntityRecord, EntityNotFound> {
+        let caller = Location::caller();
+
+        self.entity_mut(entity).map(|entity| entity.with_caller(caller).take())
+    }
+
+    /// Spawns the entity of a record taken with [`World::take_entity`].
+    ///
+    /// The entity keeps its id if it is free in this world, and is given a new
+    /// id otherwise. Entity ids stored in its components aren't remapped. The
+    /// [`Component::after_insert`] hooks of its components are run.
+    ///
+    /// Returns the id of the spawned entity.
+    ///
+    /// [`Component::after_insert`]: crate::component::Component::after_insert
+    #[track_caller]
+    pub fn insert_entity_record(
+        &mut self,
+        mut record: EntityRecord,
+    ) -> EntityId {
+        let caller = Location::caller();
+        let entity = if self.entities.alloc_at(record.id) {
+            record.id
+        } else {
+            self.entities.alloc()
+        };
+        let components = record.table.components().clone();
+        let addr = self.components.alloc_set(1, components.clone());
+        // SAFETY: the table was allocated above
+        let table = unsafe { self.components.get_unchecked_mut(addr.table) };
+
+        // SAFETY: the entity was only allocated above
+        unsafe { table.push_new(entity) };
+
+        for component in &components {
+            // SAFETY: the component is initialized and is removed from the
+            // record without being dropped below
+            unsafe {
+                let ptr = record
+                    .table
+                    .get_unchecked_mut(EntityRecord::ROW, component.id());
+
+                table.init_component(addr.row, component.id(), ptr);
+            }
+        }
+
+        record.table.remove(EntityRecord::ROW);
+        self.entities.set(entity, addr);
+
+        // SAFETY: the entity was moved into the table above
+        unsafe {
+            self.listeners.archetype_changed(
+                &self.components,
+                entity,
+                None,
+                Some(addr.table),
+            );
+        }
+
+        for component in &components {
+            let hook = component.after_insert();
+            let ctx = HookContext::new(entity, component.id(), false, caller);
+
+            // SAFETY: the entity was moved into the table above
+            unsafe {
+                hook(EntityMut::new_unchecked(entity, self), ctx);
+                self.notify_insert(entity, component.id());
+            }
+        }
+
+        self.flush();
+
+        entity
+    }
+}
+
+impl EntityRecord {
+    const ROW: TableRow = TableRow(0);
+
+    /// Moves the components of an entity out of its table.
+    ///
+    /// # Safety
+    ///
+    /// The table must contain the entity at the address.
+    pub(crate) unsafe fn take(
+        id: EntityId,
+        components: &mut Components,
+        addr: EntityAddr,
+    ) -> Self {
+        let alloc = components.allocator().clone();
+        // SAFETY: the caller ensures that the table exists
+        let old_table = unsafe { components.get_unchecked_mut(addr.table) };
+        let components = old_table.components().clone();
+        let mut table = Table::with_capacity(components.clone(), 1, &alloc);
+
+        // SAFETY: the table is empty
+        let row = unsafe { table.push_new(id) };
+
+        debug_assert_eq!(row, Self::ROW);
+
+        for component in &components {
+            // SAFETY: the caller ensures that the entity is in the table, and
+            // it is removed without dropping its components below
+            unsafe {
+                let ptr = old_table.get_unchecked_mut(addr.row, component.id());
+
+                table.init_component(row, component.id(), ptr);
+            }
+        }
+
+        old_table.remove(addr.row);
+
+        Self { id, table }
+    }
+
+    /// Returns the id the entity had in the world it was taken from.
+    pub const fn id(&self) -> EntityId {
+        self.id
+    }
+
+    /// Returns the amount of components in this record.
+    pub const fn len(&self) -> usize {
+        self.table.components().len()
+    }
+
+    /// Returns `true` if this record doesn't contain any components.
+    pub const fn is_empty(&self) -> bool {
+        self.table.components().is_empty()
+    }
+
+    /// Returns an iterator over the info of the components in this record.
+    pub fn components(&self) -> impl Iterator<Item = ComponentInfo> + '_ {
+        self.table.components().iter()
+    }
+
+    /// Returns `true` if this record contains the component.
+    pub fn contains<C: Component>(&self) -> bool {
+        self.table.components().contains(ComponentId::of::<C>())
+    }
+
+    /// Returns a reference to a component in this record.
+    ///
+    /// Returns an error if the component doesn't exist.
+    pub fn get<C: Component>(&self) -> Result<&C, ComponentNotFound> {
+        let component = ComponentId::of::<C>();
+
+        self.table
+            .components()
+            .contains(component)
+            // SAFETY: the record contains the component
+            .then(|| unsafe {
+                self.table.get_unchecked(Self::ROW, component).cast().as_ref()
+            })
+            .ok_or(ComponentNotFound::new::<C>(self.id))
+    }
+
+    /// Returns a mutable reference to a component in this record.
+    ///
+    /// Returns an error if the component doesn't exist.
+    pub fn get_mut<C: Component>(
+        &mut self,
+    ) -> Result<&mut C, ComponentNotFound> {
+        let component = ComponentId::of::<C>();
+
+        self.table
+            .components()
+            .contains(component)
+            // SAFETY: the record contains the component
+            .then(|| unsafe {
+                self.table
+                    .get_unchecked_mut(Self::ROW, component)
+                    .cast()
+                    .as_mut()
+            })
+            .ok_or(ComponentNotFound::new::<C>(self.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn move_entity_between_worlds() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut loading = World::new();
+        let mut live = World::new();
+
+        live.spawn(Health(1));
+
+        let first = loading.spawn(Health(2)).id();
+        let second = loading.spawn((Health(3), Tracked(drops.clone()))).id();
+
+        let mut record = loading.take_entity(second).unwrap();
+
+        assert_eq!(record.id(), second);
+        assert_eq!(record.len(), 2);
+        assert!(loading.take_entity(second).is_err());
+
+        record.get_mut::<Health>().unwrap().0 += 1;
+
+        let moved = live.insert_entity_record(record);
+
+        assert_eq!(moved, second);
+        assert_eq!(live.entity(moved).unwrap().get::<Health>().unwrap().0, 4);
+
+        // the id is taken by the entity of the live world
+        let record = loading.take_entity(first).unwrap();
+        let first = live.insert_entity_record(record);
+
+        assert_ne!(first, moved);
+        assert_eq!(live.entity(first).unwrap().get::<Health>().unwrap().0, 2);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        assert!(loading.is_empty());
+        assert_eq!(live.len(), 3);
+
+        let record = live.take_entity(moved).unwrap();
+
+        assert!(record.contains::<Tracked>());
+
+        drop(record);
+
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,231 @@
+use super::{World, WorldPtr, WorldView, WorldViewError};
+use crate::access::{AccessError, AccessSignature, Level, WorldAccess};
+use crate::query::{Query, QueryData, ReadOnlyQueryData};
+use crate::resource::{Res, ResMut, Resource};
+
+/// A part of a [`World`] that can only perform a set of access.
+///
+/// Created with [`World::split_access`]. The parts of a world have disjoint
+/// access, so they can be used at the same time, such as on
+/// [scoped threads](std::thread::scope). Anything outside of the access of a
+/// part returns [`WorldViewError::NotAllowed`].
+///
+/// Not available with the `single_threaded` feature.
+#[derive(Debug)]
+pub struct WorldPart<'w> {
+    world: WorldPtr<'w>,
+    allowed: AccessSignature,
+}
+
+impl World {
+    /// Splits this world into parts limited to disjoint access.
+    ///
+    /// Returns an error if any access is invalid or conflicts with another.
+    ///
+    /// ```
+    /// # use worldlines::prelude::*;
+    /// #[derive(Component)]
+    /// struct Position(f32);
+    ///
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// let mut world = World::new();
+    ///
+    /// world.spawn((Position(0.0), Health(3)));
+    ///
+    /// let mut movement = WorldAccess::new();
+    /// let mut damage = WorldAccess::new();
+    ///
+    /// movement.borrows_component::<Position>(Level::Write);
+    /// damage.borrows_component::<Health>(Level::Write);
+    ///
+    /// let [mut movement, mut damage] =
+    ///     world.split_access([&movement, &damage]).unwrap();
+    ///
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(move || {
+    ///         let mut query = movement.query_mut::<&mut Position>().unwrap();
+    ///
+    ///         for position in query.iter_mut() {
+    ///             position.0 += 1.0;
+    ///         }
+    ///     });
+    ///     scope.spawn(move || {
+    ///         let mut query = damage.query_mut::<&mut Health>().unwrap();
+    ///
+    ///         for health in query.iter_mut() {
+    ///             health.0 -= 1;
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn split_access<const N: usize>(
+        &mut self,
+        accesses: [&WorldAccess; N],
+    ) -> Result<[WorldPart<'_>; N], AccessError> {
+        for (i, access) in accesses.iter().enumerate() {
+            access.result()?;
+
+            for other in &accesses[i + 1..] {
+                access.compatible_with(other)?;
+            }
+        }
+
+        let world = self.as_ptr_mut();
+
+        Ok(accesses
+            .map(|access| WorldPart { world, allowed: access.signature() }))
+    }
+}
+
+impl<'w> WorldPart<'w> {
+    /// Returns the access signature this part is limited to.
+    pub fn allowed(&self) -> &AccessSignature {
+        &self.allowed
+    }
+
+    /// Returns a read-only [`WorldView`] limited to the access of this part.
+    pub fn view(&self) -> WorldView<'_> {
+        // SAFETY: the pointer is valid for the access of this part, which no
+        // other part can access mutably
+        unsafe { WorldView::from_ptr(self.world, &self.allowed) }
+    }
+
+    /// Returns an error if the access isn't allowed by this part.
+    fn check(&self, access: &WorldAccess) -> Result<(), WorldViewError> {
+        access.result()?;
+
+        if self.allowed.allows(&access.signature()) {
+            Ok(())
+        } else {
+            Err(WorldViewError::NotAllowed)
+        }
+    }
+
+    /// Returns a query of data from the world.
+    ///
+    /// The query data must implement [`ReadOnlyQueryData`].
+    pub fn query<D: ReadOnlyQueryData>(
+        &self,
+    ) -> Result<Query<'_, D>, WorldViewError> {
+        self.view().query()
+    }
+
+    /// Returns a query of data from the world.
+    pub fn query_mut<D: QueryData>(
+        &mut self,
+    ) -> Result<Query<'_, D>, WorldViewError> {
+        let mut access = WorldAccess::new();
+
+        D::world_access(&mut access);
+        self.check(&access)?;
+
+        // SAFETY: the access is allowed by this part, no other part can access
+        // the same data and this part is borrowed mutably
+        Ok(unsafe { Query::new(self.world)? })
+    }
+
+    /// Immutably borrows a resource.
+    pub fn resource<R: Resource>(&self) -> Result<Res<'_, R>, WorldViewError> {
+        self.view().resource()
+    }
+
+    /// Mutably borrows a resource.
+    pub fn resource_mut<R: Resource>(
+        &self,
+    ) -> Result<ResMut<'_, R>, WorldViewError> {
+        let mut access = WorldAccess::new();
+
+        access.borrows_resource::<R>(Level::Write);
+        self.check(&access)?;
+
+        // SAFETY: resource borrows are checked at runtime and the resource
+        // can't be borrowed by another part
+        Ok(unsafe { self.world.as_ref() }.resource_mut()?)
+    }
+}
+
+// SAFETY: components and resources are `Send + Sync` and parts have disjoint
+// access, so a part can be used on another thread
+unsafe impl Send for WorldPart<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Position(f32);
+
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Resource)]
+    struct Score(u32);
+
+    #[test]
+    fn split_parts_are_disjoint() {
+        let mut world = World::new();
+        let entity = world.spawn((Position(0.0), Health(3))).id();
+
+        world.create(Score(0));
+
+        let mut movement = WorldAccess::new();
+        let mut scoring = WorldAccess::new();
+
+        movement.borrows_component::<Position>(Level::Write);
+        scoring.borrows_component::<Health>(Level::Read);
+        scoring.borrows_resource::<Score>(Level::Write);
+
+        let [mut movement, scoring] =
+            world.split_access([&movement, &scoring]).unwrap();
+
+        assert!(matches!(
+            movement.query_mut::<&mut Health>(),
+            Err(WorldViewError::NotAllowed),
+        ));
+        assert_eq!(scoring.resource::<Score>().unwrap().0, 0);
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for position in
+                    movement.query_mut::<&mut Position>().unwrap().iter_mut()
+                {
+                    position.0 += 1.0;
+                }
+            });
+            scope.spawn(move || {
+                let health: u32 = scoring
+                    .query::<&Health>()
+                    .unwrap()
+                    .iter()
+                    .map(|h| h.0)
+                    .sum();
+
+                scoring.resource_mut::<Score>().unwrap().0 += health;
+            });
+        });
+
+        let entity = world.entity(entity).unwrap();
+
+        assert_eq!(entity.get::<Position>().unwrap().0, 1.0);
+        assert_eq!(world.resource::<Score>().unwrap().0, 3);
+    }
+
+    #[test]
+    fn split_conflicts() {
+        let mut world = World::new();
+        let mut lhs = WorldAccess::new();
+        let mut rhs = WorldAccess::new();
+
+        lhs.borrows_component::<Health>(Level::Write);
+        rhs.borrows_component::<Health>(Level::Read);
+
+        assert!(world.split_access([&lhs, &rhs]).is_err());
+
+        rhs = WorldAccess::new();
+        rhs.borrows_component::<Position>(Level::Read);
+
+        assert!(world.split_access([&lhs, &rhs]).is_ok());
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use super::World;
+use crate::component::{ComponentInfo, ComponentSet, ComponentVTable};
+use crate::resource::ResourceInfo;
+use crate::storage::{Column, Table};
+
+/// Statistics about the storage of a [`World`].
+///
+/// Returned by [`World::stats`]. Formatting with [`Display`](fmt::Display)
+/// produces the report of [`World::debug_dump`].
+#[derive(Debug, Clone)]
+pub struct WorldStats {
+    /// The amount of live entities.
+    pub entities: usize,
+    /// Statistics of each table, in creation order.
+    pub tables: Vec<TableStats>,
+    /// The resources in the world.
+    pub resources: Vec<ResourceInfo>,
+}
+
+/// Statistics about a table, the storage for entities with the same
+/// components.
+#[derive(Debug, Clone)]
+pub struct TableStats {
+    /// The components of entities in this table.
+    pub components: ComponentSet,
+    /// The amount of entities in this table.
+    pub entities: usize,
+    /// The amount of rows in this table, including empty rows left by removed
+    /// entities.
+    pub rows: usize,
+    /// Statistics of each column.
+    pub columns: Vec<ColumnStats>,
+}
+
+/// Statistics about the storage of a single component in a table.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnStats {
+    /// The component stored in this column.
+    pub component: ComponentInfo,
+    /// The amount of components that fit in this column without reallocating.
+    ///
+    /// Is [`usize::MAX`] for zero-sized components.
+    pub capacity: usize,
+    /// The amount of bytes allocated by this column.
+    pub bytes: usize,
+}
+
+/// Statistics about a [`World::compact`] pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompactStats {
+    /// The amount of entities moved into the holes of their table.
+    pub moved: usize,
+    /// The amount of bytes of component storage that was freed.
+    pub reclaimed: usize,
+}
+
+impl World {
+    /// Returns statistics about the storage of this world.
+    pub fn stats(&self) -> WorldStats {
+        let entities = self.len();
+        let tables =
+            self.components.tables().map(|(_, table)| table.into()).collect();
+        let resources = self.resources.iter().collect();
+
+        WorldStats { entities, tables, resources }
+    }
+
+    /// Returns a human-readable report of the storage of this world.
+    ///
+    /// See [`World::stats`].
+    pub fn debug_dump(&self) -> String {
+        self.stats().to_string()
+    }
+}
+
+impl WorldStats {
+    /// Returns the amount of tables that contain entities.
+    pub fn occupied_tables(&self) -> usize {
+        self.tables.iter().filter(|table| table.entities > 0).count()
+    }
+
+    /// Returns the amount of bytes allocated for components.
+    pub fn bytes(&self) -> usize {
+        self.tables.iter().map(TableStats::bytes).sum()
+    }
+}
+
+impl TableStats {
+    /// Returns the amount of bytes allocated for the components of this table.
+    pub fn bytes(&self) -> usize {
+        self.columns.iter().map(|column| column.bytes).sum()
+    }
+}
+
+// ---
+
+impl From<&Table> for TableStats {
+    fn from(table: &Table) -> Self {
+        let components = table.components().clone();
+        let entities = table.len();
+        let rows = table.rows_len();
+        let columns = table.columns().map(Into::into).collect();
+
+        Self { components, entities, rows, columns }
+    }
+}
+
+impl From<&Column> for ColumnStats {
+    fn from(column: &Column) -> Self {
+        let component = column.component();
+        let capacity = column.capacity();
+        let bytes = component.layout().size().saturating_mul(capacity);
+
+        Self { component, capacity, bytes }
+    }
+}
+
+impl fmt::Display for WorldStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "entities: {}", self.entities)?;
+        writeln!(
+            f,
+            "tables: {} ({} occupied, {} bytes)",
+            self.tables.len(),
+            self.occupied_tables(),
+            self.bytes(),
+        )?;
+
+        for (index, table) in self.tables.iter().enumerate() {
+            writeln!(
+                f,
+                "- table {index}: {} entities, {} rows, {} bytes",
+                table.entities,
+                table.rows,
+                table.bytes(),
+            )?;
+
+            for column in &table.columns {
+                if column.capacity == usize::MAX {
+                    writeln!(f, "  - {}: zero-sized", column.component)?;
+                } else {
+                    writeln!(
+                        f,
+                        "  - {}: capacity {}, {} bytes",
+                        column.component, column.capacity, column.bytes,
+                    )?;
+                }
+            }
+        }
+
+        writeln!(f, "resources: {}", self.resources.len())?;
+
+        for resource in &self.resources {
+            writeln!(f, "- {resource}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::prelude::*;
 
 #[test]
@@ -28,3 +30,889 @@ fn spawn_many() {
 
     world.spawn_iter(iter);
 }
+
+#[test]
+fn spawn_iter_after_despawn() {
+    #[derive(Component)]
+    struct A(u32);
+
+    let mut world = World::new();
+    let first = world.spawn(A(0)).id();
+
+    world.spawn(A(1));
+    world.despawn(first).unwrap();
+
+    let entities: Vec<_> = world.spawn_iter((2..5).map(A)).collect();
+
+    assert_eq!(world.len(), 4);
+    assert_eq!(entities.len(), 3);
+
+    for (i, entity) in entities.into_iter().enumerate() {
+        assert_eq!(
+            world.entity(entity).unwrap().get::<A>().unwrap().0,
+            i as u32 + 2
+        );
+    }
+
+    assert_eq!(world.query::<&A>().unwrap().iter().count(), 4);
+}
+
+#[test]
+fn despawn_where_despawns_matching_entities() {
+    #[derive(Component)]
+    struct Projectile;
+
+    #[derive(Component)]
+    struct Persistent;
+
+    let mut world = World::new();
+
+    let player = world.spawn(Persistent).id();
+    let projectiles: Vec<_> =
+        (0..4).map(|_| world.spawn(Projectile).id()).collect();
+    let persistent_projectile = world.spawn((Projectile, Persistent)).id();
+
+    let count = world
+        .despawn_where::<(With<Projectile>, Without<Persistent>)>()
+        .unwrap();
+
+    assert_eq!(count, 4);
+    assert_eq!(world.len(), 2);
+    assert!(world.contains(player));
+    assert!(world.contains(persistent_projectile));
+
+    for entity in projectiles {
+        assert!(!world.contains(entity));
+    }
+}
+
+#[test]
+fn retain_entities_despawns_rejected_entities() {
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Corpse;
+
+    let mut world = World::new();
+
+    let alive: Vec<_> =
+        (1..=3).map(|health| world.spawn(Health(health)).id()).collect();
+    let dead: Vec<_> =
+        (0..2).map(|_| world.spawn((Health(0), Corpse)).id()).collect();
+    let empty = world.spawn(()).id();
+
+    let count = world.retain_entities(|entity| {
+        entity.get::<Health>().map_or(true, |health| health.0 > 0)
+    });
+
+    assert_eq!(count, 2);
+    assert_eq!(world.len(), 4);
+    assert!(world.contains(empty));
+
+    for entity in alive {
+        assert!(world.contains(entity));
+    }
+
+    for entity in dead {
+        assert!(!world.contains(entity));
+    }
+}
+
+#[test]
+fn archetype_change_listeners() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Component)]
+    struct Position;
+
+    #[derive(Component)]
+    struct Velocity;
+
+    let mut world = World::new();
+    let changes = Arc::new(Mutex::new(Vec::new()));
+
+    world.on_archetype_change(ComponentInfo::of::<Position>().id(), {
+        let changes = Arc::clone(&changes);
+
+        move |change| {
+            changes.lock().unwrap().push((change.entity(), change.is_added()))
+        }
+    });
+
+    let a = world.spawn(Position).id();
+    let b = world.spawn(Velocity).id();
+
+    // moves without adding or removing `Position` aren't reported
+    world.entity_mut(a).unwrap().insert(Velocity);
+    world.entity_mut(a).unwrap().insert(Position);
+    world.entity_mut(b).unwrap().insert(Position);
+    world.entity_mut(b).unwrap().remove::<Position>().unwrap();
+    world.entity_mut(b).unwrap().insert_bundle((Position, Velocity));
+    world.entity_mut(b).unwrap().retain::<Velocity>();
+    world.despawn(a).unwrap();
+
+    let c = world.spawn_iter([Position]).next().unwrap();
+
+    world.despawn_where::<With<Position>>().unwrap();
+
+    let d = world.spawn(Position).id();
+
+    world.despawn_all();
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [
+            (a, true),
+            (b, true),
+            (b, false),
+            (b, true),
+            (b, false),
+            (a, false),
+            (c, true),
+            (c, false),
+            (d, true),
+            (d, false),
+        ],
+    );
+}
+
+#[test]
+fn despawn_listeners() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Component)]
+    struct Sound(u32);
+
+    let mut world = World::new();
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+
+    world.on_despawn({
+        let stopped = Arc::clone(&stopped);
+
+        move |entity, reason| {
+            if let Ok(sound) = entity.get::<Sound>() {
+                stopped.lock().unwrap().push((sound.0, reason));
+            }
+        }
+    });
+
+    let a = world.spawn(Sound(0)).id();
+
+    world.spawn(Sound(1));
+    world.spawn(());
+    world.despawn(a).unwrap();
+    world.despawn_where::<With<Sound>>().unwrap();
+    world.spawn(Sound(2));
+    world.retain_entities(|_| false);
+    world.spawn(Sound(3));
+    world.clear();
+
+    assert_eq!(
+        *stopped.lock().unwrap(),
+        [
+            (0, DespawnReason::Explicit),
+            (1, DespawnReason::Batch),
+            (2, DespawnReason::Batch),
+            (3, DespawnReason::Clear),
+        ],
+    );
+}
+
+#[test]
+fn destroy_where_destroys_matching_resources() {
+    #[derive(Resource)]
+    struct Level;
+
+    #[derive(Resource)]
+    struct Settings;
+
+    let mut world = World::new();
+
+    world.create(Level);
+    world.create(Settings);
+
+    let count = world.destroy_where(|info| info == ResourceInfo::of::<Level>());
+
+    assert_eq!(count, 1);
+    assert!(!world.has::<Level>());
+    assert!(world.has::<Settings>());
+}
+
+#[test]
+fn components_of_entity() {
+    #[derive(Component)]
+    struct Position;
+
+    #[derive(Component)]
+    struct Velocity;
+
+    let mut world = World::new();
+    let entity = world.spawn((Position, Velocity)).id();
+    let empty = world.spawn(()).id();
+
+    let mut components: Vec<_> = world.components_of(entity).unwrap().collect();
+
+    components.sort_by_key(|info| info.id());
+
+    let mut expected =
+        vec![ComponentInfo::of::<Position>(), ComponentInfo::of::<Velocity>()];
+
+    expected.sort_by_key(|info| info.id());
+
+    assert_eq!(components, expected);
+    assert_eq!(world.components_of(empty).unwrap().count(), 0);
+
+    let entity = world.entity(entity).unwrap();
+
+    assert!(entity.contains_id(ComponentId::of::<Position>()));
+    assert!(entity.archetype().contains(ComponentId::of::<Velocity>()));
+
+    world.despawn(empty).unwrap();
+
+    assert!(world.components_of(empty).is_err());
+}
+
+#[test]
+fn iter_entities() {
+    #[derive(Component)]
+    struct Health(u32);
+
+    let mut world = World::new();
+    let entities: Vec<_> = world.spawn_iter((0..4).map(Health)).collect();
+
+    world.despawn(entities[1]).unwrap();
+    world.spawn(());
+
+    assert_eq!(world.iter().len(), 4);
+    assert_eq!(world.iter_entities().len(), 4);
+
+    let mut iter = world.iter_entities_mut();
+
+    assert_eq!(iter.len(), 4);
+
+    let held: Vec<_> = iter.by_ref().take(2).collect();
+
+    assert_eq!(iter.len(), 2);
+
+    for mut entity in held.into_iter().chain(iter) {
+        if let Ok(health) = entity.get_mut::<Health>() {
+            health.0 += 10;
+        }
+    }
+
+    let mut healths: Vec<_> = world
+        .iter_entities()
+        .filter_map(|entity| entity.get::<Health>().ok())
+        .map(|health| health.0)
+        .collect();
+
+    healths.sort();
+
+    assert_eq!(healths, [10, 12, 13]);
+}
+
+#[test]
+fn resource_scope() {
+    #[derive(Component)]
+    struct Enemy;
+
+    #[derive(Resource)]
+    struct Spawner {
+        spawned: usize,
+    }
+
+    let mut world = World::new();
+
+    assert!(world.resource_scope(|_, _: &mut Spawner| ()).is_err());
+
+    world.create(Spawner { spawned: 0 });
+    world
+        .resource_scope(|world, spawner: &mut Spawner| {
+            assert!(!world.has::<Spawner>());
+
+            world.spawn(Enemy);
+            spawner.spawned += 1;
+        })
+        .unwrap();
+
+    assert_eq!(world.len(), 1);
+    assert_eq!(world.resource::<Spawner>().unwrap().spawned, 1);
+}
+
+#[test]
+fn init_resource() {
+    #[derive(Resource, Default)]
+    struct TileSize(u32);
+
+    #[derive(Resource)]
+    struct Grid {
+        cell: u32,
+    }
+
+    impl FromWorld for Grid {
+        fn from_world(world: &mut World) -> Self {
+            world.init_resource::<TileSize>();
+
+            Self { cell: world.resource::<TileSize>().unwrap().0 * 2 }
+        }
+    }
+
+    let mut world = World::new();
+
+    world.create(TileSize(8));
+
+    assert!(world.init_resource::<Grid>());
+    assert!(!world.init_resource::<Grid>());
+    assert_eq!(world.resource::<Grid>().unwrap().cell, 16);
+}
+
+#[test]
+fn stats_describe_tables() {
+    #[derive(Component)]
+    struct A(#[expect(unused)] u32);
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Resource)]
+    struct R;
+
+    let mut world = World::new();
+
+    world.spawn(A(0));
+    world.spawn(A(1));
+    world.spawn((A(2), B));
+    world.create(R);
+
+    let stats = world.stats();
+    let a =
+        stats.tables.iter().find(|table| table.components.len() == 1).unwrap();
+
+    assert_eq!(stats.entities, 3);
+    assert_eq!(stats.occupied_tables(), 2);
+    assert_eq!(stats.resources.len(), 1);
+    assert_eq!(a.entities, 2);
+    assert!(a.columns[0].capacity >= 2);
+    assert_eq!(a.bytes(), a.columns[0].capacity * size_of::<u32>());
+
+    let dump = world.debug_dump();
+
+    assert!(dump.contains("entities: 3"));
+    assert!(dump.contains("B: zero-sized"));
+}
+
+#[test]
+fn query_expect() {
+    #[derive(Component)]
+    struct A;
+
+    let mut world = World::new();
+
+    world.spawn(A);
+
+    assert_eq!(world.query_expect::<&A>().iter().count(), 1);
+    assert!(world.query_mut::<(&A, &mut A)>().is_err());
+}
+
+#[test]
+#[should_panic = "invalid access in query"]
+fn query_mut_expect_panics() {
+    #[derive(Component)]
+    struct A;
+
+    World::new().query_mut_expect::<(&A, &mut A)>();
+}
+
+#[test]
+#[cfg_attr(not(debug_assertions), ignore)]
+#[should_panic = "invalid access in query"]
+fn panic_on_access_error() {
+    #[derive(Component)]
+    struct A;
+
+    let mut world = World::new();
+
+    world.set_panic_on_access_error(true);
+    _ = world.query_mut::<(&A, &mut A)>();
+}
+
+#[test]
+fn spawn_after_despawn_keeps_entities() {
+    #[derive(Component)]
+    struct A(u32);
+
+    let mut world = World::new();
+    let a = world.spawn(A(0)).id();
+    let b = world.spawn(A(1)).id();
+    let c = world.spawn(A(2)).id();
+
+    world.despawn(a).unwrap();
+
+    let d = world.spawn(A(3)).id();
+
+    assert_eq!(world.entity(b).unwrap().get::<A>().unwrap().0, 1);
+    assert_eq!(world.entity(c).unwrap().get::<A>().unwrap().0, 2);
+    assert_eq!(world.entity(d).unwrap().get::<A>().unwrap().0, 3);
+}
+
+#[test]
+fn get_many_entities_mut() {
+    #[derive(Component)]
+    struct Hp(u32);
+
+    let mut world = World::new();
+    let attacker = world.spawn(Hp(10)).id();
+    let defender = world.spawn(Hp(10)).id();
+
+    let [attacker_ref, mut defender_ref] =
+        world.get_many_entities_mut([attacker, defender]).unwrap();
+
+    defender_ref.get_mut::<Hp>().unwrap().0 -=
+        attacker_ref.get::<Hp>().unwrap().0 / 2;
+
+    assert_eq!(world.entity(defender).unwrap().get::<Hp>().unwrap().0, 5);
+    assert!(matches!(
+        world.get_many_entities_mut([attacker, attacker]),
+        Err(ManyEntitiesError::Duplicate(entity)) if entity == attacker,
+    ));
+
+    world.despawn(defender).unwrap();
+
+    assert!(matches!(
+        world.get_many_entities_mut([attacker, defender]),
+        Err(ManyEntitiesError::NotFound(entity)) if entity == defender,
+    ));
+}
+
+#[test]
+fn maintain_compacts_tables() {
+    #[derive(Component)]
+    struct A(u32);
+
+    let mut world = World::new();
+    let entities: Vec<_> = (0..8).map(|i| world.spawn(A(i)).id()).collect();
+
+    for &entity in entities.iter().step_by(2) {
+        world.despawn(entity).unwrap();
+    }
+
+    assert_eq!(world.maintain(Duration::ZERO), 0);
+    assert!(world
+        .stats()
+        .tables
+        .iter()
+        .any(|table| table.rows > table.entities));
+
+    let moved = world.maintain(Duration::MAX);
+
+    assert!(moved > 0);
+    assert!(world
+        .stats()
+        .tables
+        .iter()
+        .all(|table| table.rows == table.entities));
+
+    for (i, &entity) in entities.iter().enumerate().skip(1).step_by(2) {
+        assert_eq!(
+            world.entity(entity).unwrap().get::<A>().unwrap().0,
+            i as u32
+        );
+    }
+}
+
+#[test]
+fn compact_shrinks_tables() {
+    #[derive(Component)]
+    struct A(u64);
+
+    let mut world = World::new();
+    let entities: Vec<_> = world.spawn_iter((0..100).map(A)).collect();
+
+    for &entity in &entities[..90] {
+        world.despawn(entity).unwrap();
+    }
+
+    let bytes = world.stats().bytes();
+    let stats = world.compact();
+
+    assert_eq!(stats.moved, 10);
+    assert!(stats.reclaimed > 0);
+    assert_eq!(world.stats().bytes(), bytes - stats.reclaimed);
+    assert_eq!(world.compact(), CompactStats::default());
+
+    for (i, &entity) in entities.iter().enumerate().skip(90) {
+        assert_eq!(
+            world.entity(entity).unwrap().get::<A>().unwrap().0,
+            i as u64
+        );
+    }
+
+    world.spawn(A(100));
+
+    assert_eq!(world.query::<&A>().unwrap().iter().count(), 11);
+}
+
+#[test]
+fn despawn_batch() {
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    let mut world = World::new();
+    let a: Vec<_> = world.spawn_iter((0..4).map(|_| A)).collect();
+    let b: Vec<_> = world.spawn_iter((0..4).map(|_| (A, B))).collect();
+    let dead = world.spawn(()).id();
+
+    world.despawn(dead).unwrap();
+
+    let despawned =
+        world.despawn_batch([a[0], b[1], a[2], dead, b[1], b[3], a[3]]);
+
+    assert_eq!(despawned, 5);
+    assert_eq!(world.len(), 3);
+    assert!(world.contains(a[1]));
+    assert!(!world.contains(a[3]));
+    assert_eq!(world.query::<&B>().unwrap().len(), 2);
+}
+
+#[test]
+fn graveyard_defers_drops() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Component)]
+    struct A(u32);
+
+    impl Drop for A {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn noop() {}
+
+    let mut world = World::new();
+
+    world.set_graveyard(true);
+
+    let entities: Vec<_> = world.spawn_iter((0..6).map(A)).collect();
+
+    world.despawn_batch(entities[..3].iter().copied());
+    world.despawn(entities[5]).unwrap();
+
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    assert_eq!(world.query::<&A>().unwrap().iter().count(), 2);
+
+    // buried rows aren't reused
+    let spawned = world.spawn(A(6)).id();
+
+    assert_eq!(world.entity(entities[4]).unwrap().get::<A>().unwrap().0, 4);
+    assert_eq!(world.entity(spawned).unwrap().get::<A>().unwrap().0, 6);
+
+    world.run_system(noop).unwrap();
+
+    assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+
+    world.despawn(spawned).unwrap();
+    world.set_graveyard(false);
+
+    assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+
+    world.maintain(Duration::MAX);
+
+    let mut values: Vec<_> =
+        world.query::<&A>().unwrap().iter().map(|a| a.0).collect();
+
+    values.sort();
+
+    assert_eq!(values, [3, 4]);
+}
+
+/// Applies random structural changes to a world and checks it against a
+/// simple model after every step: component values, iteration, drop counts and
+/// hook calls.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn random_round_trip() {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    thread_local! {
+        static HOOKED: Cell<isize> = const { Cell::new(0) };
+    }
+
+    #[derive(Component)]
+    struct A(u64, #[expect(unused)] Arc<()>);
+
+    #[derive(Component)]
+    #[component(
+        after_insert = |_, _| HOOKED.set(HOOKED.get() + 1),
+        on_replace = |_, _| HOOKED.set(HOOKED.get() - 1),
+        before_remove = |_, _| HOOKED.set(HOOKED.get() - 1),
+    )]
+    struct B(u64, #[expect(unused)] Arc<()>);
+
+    /// A xorshift generator, so that failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    let mut world = World::new();
+    let mut model: HashMap<EntityId, (Option<u64>, Option<u64>)> =
+        HashMap::new();
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let token = Arc::new(());
+
+    for step in 0..2_000u64 {
+        let entities: Vec<_> = model.keys().copied().collect();
+        let target =
+            (!entities.is_empty()).then(|| entities[rng.next(entities.len())]);
+
+        match (rng.next(8), target) {
+            (0, _) => {
+                let entity = world.spawn(A(step, token.clone())).id();
+
+                model.insert(entity, (Some(step), None));
+            },
+            (1, _) => {
+                let entity = world
+                    .spawn((A(step, token.clone()), B(step, token.clone())))
+                    .id();
+
+                model.insert(entity, (Some(step), Some(step)));
+            },
+            (2, Some(entity)) => {
+                world
+                    .entity_mut(entity)
+                    .unwrap()
+                    .insert(A(step, token.clone()));
+                model.get_mut(&entity).unwrap().0 = Some(step);
+            },
+            (3, Some(entity)) => {
+                world
+                    .entity_mut(entity)
+                    .unwrap()
+                    .insert(B(step, token.clone()));
+                model.get_mut(&entity).unwrap().1 = Some(step);
+            },
+            (4, Some(entity)) => {
+                let removed = world.entity_mut(entity).unwrap().remove::<A>();
+
+                assert_eq!(removed.ok().map(|a| a.0), model[&entity].0);
+                model.get_mut(&entity).unwrap().0 = None;
+            },
+            (5, Some(entity)) => {
+                let removed = world.entity_mut(entity).unwrap().remove::<B>();
+
+                assert_eq!(removed.ok().map(|b| b.0), model[&entity].1);
+                model.get_mut(&entity).unwrap().1 = None;
+            },
+            (6, Some(entity)) => {
+                world.despawn(entity).unwrap();
+                model.remove(&entity);
+            },
+            (7, _) => {
+                world.maintain(Duration::MAX);
+            },
+            _ => {},
+        }
+
+        assert_eq!(world.len(), model.len());
+
+        for (&entity, &(a, b)) in &model {
+            let entity = world.entity(entity).unwrap();
+
+            assert_eq!(entity.get::<A>().ok().map(|a| a.0), a);
+            assert_eq!(entity.get::<B>().ok().map(|b| b.0), b);
+        }
+
+        let query = world.query::<(EntityId, Option<&A>)>().unwrap();
+        let mut seen: Vec<_> = query.iter().map(|(entity, _)| entity).collect();
+
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), model.len());
+
+        let live = model
+            .values()
+            .map(|(a, b)| a.is_some() as usize + b.is_some() as usize)
+            .sum::<usize>();
+        let hooked =
+            model.values().filter(|(_, b)| b.is_some()).count() as isize;
+
+        assert_eq!(Arc::strong_count(&token) - 1, live);
+        assert_eq!(HOOKED.get(), hooked);
+    }
+
+    drop(world);
+
+    assert_eq!(Arc::strong_count(&token), 1);
+}
+
+#[test]
+fn run_system() {
+    #[derive(Component)]
+    struct Enemy;
+
+    #[derive(Resource)]
+    struct Wave(usize);
+
+    fn spawn_wave(wave: Res<Wave>, mut queue: WorldQueue) -> usize {
+        for _ in 0..wave.0 {
+            queue.spawn(Enemy);
+        }
+
+        wave.0
+    }
+
+    let mut world = World::new();
+
+    assert!(matches!(
+        world.run_system(spawn_wave),
+        Err(RunSystemError::ResourceNotFound { .. }),
+    ));
+
+    world.create(Wave(3));
+
+    assert_eq!(world.run_system(spawn_wave).unwrap(), 3);
+    assert_eq!(world.len(), 3);
+}
+
+#[test]
+fn registered_systems() {
+    fn count(mut runs: Var<usize>) -> usize {
+        let runs = runs.get_or_default();
+
+        *runs += 1;
+        *runs
+    }
+
+    fn spawn(mut queue: WorldQueue) {
+        queue.spawn(());
+    }
+
+    let mut world = World::new();
+    let counter = world.register_system(count);
+    let spawner = world.register_system(spawn);
+
+    assert_eq!(world.run_system_by_id(counter).unwrap(), 1);
+    assert_eq!(world.run_system_by_id(counter).unwrap(), 2);
+
+    let mut commands = Commands::new();
+
+    commands.as_world_queue(&world).run_system(spawner);
+    commands.apply(&mut world);
+
+    assert_eq!(world.len(), 1);
+    assert!(world.unregister_system(counter));
+    assert!(matches!(
+        world.run_system_by_id(counter),
+        Err(RunSystemError::NotFound),
+    ));
+}
+
+#[test]
+fn reserved_entities_are_not_accessible() {
+    #[derive(Component)]
+    struct A;
+
+    let mut world = World::new();
+    let reserved = world.reserve_entity();
+
+    assert!(!world.contains(reserved));
+    assert!(world.entity(reserved).is_err());
+    assert!(world.entity_mut(reserved).is_err());
+
+    // flushes the reserved entity without spawning it
+    let spawned = world.spawn(A).id();
+
+    assert!(!world.contains(reserved));
+    assert!(world.entity(reserved).is_err());
+    assert!(world.entity_mut(reserved).is_err());
+    assert!(world.despawn(reserved).is_err());
+    assert_eq!(world.len(), 1);
+    assert_eq!(world.iter().len(), 1);
+    assert_eq!(world.iter().collect::<Vec<_>>(), [spawned]);
+    assert_eq!(
+        world.iter_entities().map(|entity| entity.id()).collect::<Vec<_>>(),
+        [spawned]
+    );
+    assert_eq!(world.iter_entities_mut().count(), 1);
+
+    world.spawn_reserved(reserved, A).unwrap();
+
+    assert!(world.contains(reserved));
+    assert_eq!(world.len(), 2);
+    assert_eq!(world.iter_entities().len(), 2);
+
+    world.despawn(reserved).unwrap();
+
+    assert_eq!(world.len(), 1);
+}
+
+#[test]
+#[cfg(not(feature = "single_threaded"))]
+fn spawn_reserved_entities() {
+    #[derive(Component)]
+    struct Chunk(usize);
+
+    let mut world = World::new();
+    let reserved: Vec<_> = std::thread::scope(|scope| {
+        let world = &world;
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| world.reserve_entities(8).collect::<Vec<_>>())
+            })
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    });
+    let mut unique = reserved.clone();
+
+    unique.sort();
+    unique.dedup();
+
+    assert_eq!(unique.len(), 32);
+
+    let (&first, rest) = reserved.split_first().unwrap();
+
+    assert_eq!(
+        world
+            .spawn_reserved(first, Chunk(0))
+            .unwrap()
+            .get::<Chunk>()
+            .unwrap()
+            .0,
+        0
+    );
+
+    assert!(world.spawn_reserved(first, ()).is_err());
+
+    let mut commands = Commands::new();
+    let mut queue = commands.as_world_queue(&world);
+
+    for (i, &entity) in rest.iter().enumerate() {
+        queue.spawn_reserved(entity, Chunk(i + 1));
+    }
+
+    queue.spawn_reserved(first, ());
+    world.set_command_error_handler(CommandErrorHandler::Collect);
+    commands.apply(&mut world);
+
+    assert_eq!(world.len(), 32);
+    assert_eq!(world.query::<&Chunk>().unwrap().len(), 32);
+    assert_eq!(world.take_command_errors().len(), 1);
+}
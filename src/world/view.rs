@@ -0,0 +1,224 @@
+use thiserror::Error;
+
+use super::{World, WorldId, WorldPtr};
+use crate::access::{AccessError, AccessSignature, Level, WorldAccess};
+use crate::entity::{EntityId, EntityNotFound, EntityRef};
+use crate::query::{Query, ReadOnlyQueryData};
+use crate::resource::{Res, Resource, ResourceError};
+
+/// A read-only view of a [`World`].
+///
+/// Only exposes reads: read-only queries, immutable resource borrows and
+/// entity references. Unlike a `&World`, resources can't be borrowed mutably
+/// through a view, so it can be handed to code that shouldn't modify the world.
+///
+/// A view can be limited to a whitelist of access with
+/// [`WorldView::with_access`], in which case anything outside of it returns
+/// [`WorldViewError::NotAllowed`].
+#[derive(Debug, Clone, Copy)]
+pub struct WorldView<'w> {
+    world: WorldPtr<'w>,
+    allowed: Option<&'w AccessSignature>,
+}
+
+/// An error for a failed read through a [`WorldView`].
+#[derive(Debug, Error)]
+pub enum WorldViewError {
+    /// The access isn't allowed by the whitelist of the view.
+    #[error("access is not allowed by the world view")]
+    NotAllowed,
+    /// The access is invalid.
+    #[error(transparent)]
+    Access(#[from] AccessError),
+    /// The resource doesn't exist or is borrowed mutably.
+    #[error(transparent)]
+    Resource(#[from] ResourceError),
+    /// The entity doesn't exist.
+    #[error(transparent)]
+    Entity(#[from] EntityNotFound),
+}
+
+impl<'w> WorldView<'w> {
+    /// Creates a view of the entire world.
+    pub fn new(world: &'w World) -> Self {
+        Self { world: world.as_ptr(), allowed: None }
+    }
+
+    /// Creates a view of a world that only allows reads permitted by an
+    /// [access signature](AccessSignature).
+    pub fn with_access(world: &'w World, allowed: &'w AccessSignature) -> Self {
+        Self { world: world.as_ptr(), allowed: Some(allowed) }
+    }
+
+    /// Creates a view from a world pointer that only allows reads permitted by
+    /// an [access signature](AccessSignature).
+    ///
+    /// # Safety
+    ///
+    /// The world pointer must be valid for reads of the allowed access for the
+    /// lifetime of the view.
+    pub unsafe fn from_ptr(
+        world: WorldPtr<'w>,
+        allowed: &'w AccessSignature,
+    ) -> Self {
+        Self { world, allowed: Some(allowed) }
+    }
+
+    /// Returns the world, for reads that were checked to be allowed.
+    fn world(self) -> &'w World {
+        // SAFETY: the pointer is valid for reads of the allowed access and
+        // only allowed reads are performed through the reference
+        unsafe { self.world.as_ref() }
+    }
+
+    /// Returns an error if the access isn't allowed by this view.
+    fn check(self, access: &WorldAccess) -> Result<(), WorldViewError> {
+        access.result()?;
+
+        match self.allowed {
+            Some(allowed) if !allowed.allows(&access.signature()) => {
+                Err(WorldViewError::NotAllowed)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the unique id of the viewed world.
+    pub fn id(self) -> WorldId {
+        self.world().id()
+    }
+
+    /// Returns the amount of entities in the world.
+    pub fn len(self) -> usize {
+        self.world().len()
+    }
+
+    /// Returns `true` if the world contains no entities.
+    pub fn is_empty(self) -> bool {
+        self.world().is_empty()
+    }
+
+    /// Returns `true` if the world contains the entity.
+    pub fn contains(self, entity: EntityId) -> bool {
+        self.world().contains(entity)
+    }
+
+    /// Borrows an entity in the world.
+    ///
+    /// Requires read access to all entities.
+    pub fn entity(
+        self,
+        entity: EntityId,
+    ) -> Result<EntityRef<'w>, WorldViewError> {
+        let mut access = WorldAccess::new();
+
+        access.borrows_all_entities(Level::Read);
+        self.check(&access)?;
+
+        Ok(self.world().entity(entity)?)
+    }
+
+    /// Returns a query of data from the world.
+    pub fn query<D: ReadOnlyQueryData>(
+        self,
+    ) -> Result<Query<'w, D>, WorldViewError> {
+        let mut access = WorldAccess::new();
+
+        D::world_access(&mut access);
+        self.check(&access)?;
+
+        Ok(Query::from_ref(self.world())?)
+    }
+
+    /// Returns `true` if the world contains the resource.
+    pub fn has<R: Resource>(self) -> bool {
+        self.world().has::<R>()
+    }
+
+    /// Immutably borrows a resource.
+    pub fn resource<R: Resource>(self) -> Result<Res<'w, R>, WorldViewError> {
+        let mut access = WorldAccess::new();
+
+        access.borrows_resource::<R>(Level::Read);
+        self.check(&access)?;
+
+        Ok(self.world().resource()?)
+    }
+}
+
+impl World {
+    /// Returns a read-only [`WorldView`] of this world.
+    pub fn view(&self) -> WorldView<'_> {
+        WorldView::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Secret;
+
+    #[derive(Resource)]
+    struct Score(u32);
+
+    #[test]
+    fn view_reads() {
+        let mut world = World::new();
+        let entity = world.spawn(Health(3)).id();
+
+        world.create(Score(7));
+
+        let view = world.view();
+
+        assert_eq!(view.len(), 1);
+        assert_eq!(view.entity(entity).unwrap().get::<Health>().unwrap().0, 3);
+        assert_eq!(view.query::<&Health>().unwrap().len(), 1);
+        assert_eq!(view.resource::<Score>().unwrap().0, 7);
+    }
+
+    #[test]
+    fn view_whitelist() {
+        let mut world = World::new();
+        let entity = world.spawn((Health(3), Secret)).id();
+
+        world.create(Score(7));
+
+        let mut access = WorldAccess::new();
+
+        access.borrows_component::<Health>(Level::Read);
+
+        let allowed = access.signature();
+        let view = WorldView::with_access(&world, &allowed);
+
+        assert_eq!(view.query::<&Health>().unwrap().len(), 1);
+        assert!(matches!(
+            view.query::<&Secret>(),
+            Err(WorldViewError::NotAllowed),
+        ));
+        assert!(matches!(
+            view.resource::<Score>(),
+            Err(WorldViewError::NotAllowed),
+        ));
+        assert!(matches!(view.entity(entity), Err(WorldViewError::NotAllowed)));
+        assert!(view.contains(entity));
+
+        // entity commands queue structural changes, so they aren't read-only
+        // query data and aren't allowed by a whitelist of reads
+        let mut commands = WorldAccess::new();
+
+        <(&Health, EntityCommands)>::world_access(&mut commands);
+
+        assert!(!allowed.allows(&commands.signature()));
+
+        let mut all_entities = WorldAccess::new();
+
+        all_entities.borrows_all_entities(Level::Read);
+
+        assert!(!all_entities.signature().allows(&commands.signature()));
+    }
+}